@@ -44,8 +44,9 @@ pub unsafe fn attributes_tostring(attr: grid_attr) -> *const u8 {
             if attr.intersects(grid_attr::GRID_ATTR_UNDERSCORE_5) { "dashed-underscore," } else { "" },
             if attr.intersects(grid_attr::GRID_ATTR_OVERLINE) { "overline," } else { "" },
         ).unwrap() as isize;
-        if len > 0 {
-            BUF[len as usize - 1] = b'\0';
+        // The length includes the terminating nul; drop the trailing comma.
+        if len > 1 {
+            BUF[len as usize - 2] = b'\0';
         }
 
         &raw mut BUF as _
@@ -19,9 +19,9 @@ pub static CMD_CAPTURE_PANE_ENTRY: cmd_entry = cmd_entry {
     name: SyncCharPtr::new(c"capture-pane"),
     alias: SyncCharPtr::new(c"capturep"),
 
-    args: args_parse::new(c"ab:CeE:JNpPqS:Tt:", 0, 0, None),
+    args: args_parse::new(c"ab:c:CeE:f:JNpPqS:Tt:", 0, 0, None),
     usage: SyncCharPtr::new(
-        c"[-aCeJNpPqT] [-b buffer-name] [-E end-line] [-S start-line] [-t target-pane]",
+        c"[-aCeJNpPqT] [-b buffer-name] [-c checkpoint] [-E end-line] [-f output-format] [-S start-line] [-t target-pane]",
     ),
 
     source: cmd_entry_flag::zeroed(),
@@ -103,11 +103,63 @@ unsafe fn cmd_capture_pane_pending(
     }
 }
 
+unsafe fn cmd_capture_pane_export(
+    args: *mut args,
+    wp: *mut window_pane,
+    gd: *mut grid,
+    top: u32,
+    bottom: u32,
+    format: grid_export_format,
+    len: *mut usize,
+) -> *mut u8 {
+    unsafe {
+        let mut gc: grid_cell = zeroed();
+        let join_lines = args_has(args, b'J') != 0;
+
+        let mut ge = grid_export_create(format, &raw const (*wp).palette, (*wp).screen);
+        for i in top..=bottom {
+            let gl = grid_peek_line(gd, i);
+            let end = if join_lines {
+                (*gl).cellused
+            } else if args_has(args, b'N') != 0 {
+                (*gl).cellsize
+            } else {
+                // Trim trailing spaces, but not those with a background colour.
+                let mut px = (*gl).cellused.min((*gd).sx);
+                while px > 0 {
+                    grid_get_cell(gd, px - 1, i, &raw mut gc);
+                    if gc.flags.intersects(grid_flag::PADDING)
+                        || gc.data.size != 1
+                        || gc.data.data[0] != b' '
+                        || !COLOUR_DEFAULT(gc.bg)
+                        || gc.attr.intersects(grid_attr::GRID_ATTR_REVERSE)
+                    {
+                        break;
+                    }
+                    px -= 1;
+                }
+                px
+            };
+            for xx in 0..end.min((*gd).sx) {
+                grid_get_cell(gd, xx, i, &raw mut gc);
+                if !gc.flags.intersects(grid_flag::PADDING) {
+                    grid_export_cell(&mut ge, &gc);
+                }
+            }
+            if !join_lines || !(*gl).flags.intersects(grid_line_flag::WRAPPED) {
+                grid_export_newline(&mut ge);
+            }
+        }
+        grid_export_finish(ge, len)
+    }
+}
+
 unsafe fn cmd_capture_pane_history(
     args: *mut args,
     item: *mut cmdq_item,
     wp: *mut window_pane,
     len: *mut usize,
+    lost: *mut bool,
 ) -> *mut u8 {
     unsafe {
         let mut gd: *mut grid = null_mut();
@@ -196,6 +248,46 @@ unsafe fn cmd_capture_pane_history(
             top = tmp;
         }
 
+        if args_has(args, b'c') != 0 {
+            // Capture the lines finished since the checkpoint and move it to
+            // the cursor line. If some have already gone from the history,
+            // the rest are still captured but the command fails unless -q.
+            let cy = if args_has(args, b'a') != 0 {
+                (*wp).base.saved_cy
+            } else {
+                (*wp).base.cy
+            };
+            let name = args_get(args, b'c');
+            let now = (*gd).htrimmed + ((*gd).hsize + cy) as u64;
+            let mut from = window_pane_set_checkpoint(wp, name, now);
+            if from < (*gd).htrimmed {
+                if args_has(args, b'q') == 0 {
+                    *lost = true;
+                    cmdq_error!(
+                        item,
+                        "{} lines lost since checkpoint {}",
+                        (*gd).htrimmed - from,
+                        _s(name)
+                    );
+                }
+                from = (*gd).htrimmed;
+            }
+            if from >= now {
+                return xstrdup(c!("")).as_ptr();
+            }
+            top = (from - (*gd).htrimmed) as u32;
+            bottom = (now - (*gd).htrimmed) as u32 - 1;
+        }
+
+        let format = args_get(args, b'f');
+        if !format.is_null() {
+            let Some(format) = grid_export_find(format) else {
+                cmdq_error!(item, "unknown output format: {}", _s(format));
+                return null_mut();
+            };
+            return cmd_capture_pane_export(args, wp, gd, top, bottom, format, len);
+        }
+
         join_lines = args_has(args, b'J');
         if args_has(args, b'e') != 0 {
             flags |= grid_string_flags::GRID_STRING_WITH_SEQUENCES;
@@ -245,16 +337,28 @@ unsafe fn cmd_capture_pane_exec(self_: *mut cmd, item: *mut cmdq_item) -> cmd_re
         }
 
         let mut len = 0;
+        let mut lost = false;
         let buf = if args_has(args, b'P') != 0 {
             cmd_capture_pane_pending(args, wp, &raw mut len)
         } else {
-            cmd_capture_pane_history(args, item, wp, &raw mut len)
+            cmd_capture_pane_history(args, item, wp, &raw mut len, &raw mut lost)
         };
         if buf.is_null() {
             return cmd_retval::CMD_RETURN_ERROR;
         }
+        let retval = if lost {
+            cmd_retval::CMD_RETURN_ERROR
+        } else {
+            cmd_retval::CMD_RETURN_NORMAL
+        };
 
         if args_has(args, b'p') != 0 {
+            // Print nothing rather than an empty line when there is nothing
+            // new since a checkpoint, so a blank line is never made up.
+            if len == 0 && args_has(args, b'c') != 0 {
+                free_(buf);
+                return retval;
+            }
             if len > 0 && *buf.add(len - 1) == b'\n' as _ {
                 len -= 1;
             }
@@ -285,6 +389,6 @@ unsafe fn cmd_capture_pane_exec(self_: *mut cmd, item: *mut cmdq_item) -> cmd_re
             }
         }
 
-        cmd_retval::CMD_RETURN_NORMAL
+        retval
     }
 }
@@ -14,6 +14,8 @@
 
 use crate::*;
 
+use crate::libc::{regcomp, regex_t, regfree};
+
 pub static CMD_CHOOSE_TREE_ENTRY: cmd_entry = cmd_entry {
     name: SyncCharPtr::new(c"choose-tree"),
     alias: SyncCharPtr::null(),
@@ -56,6 +58,50 @@ pub static CMD_CHOOSE_BUFFER_ENTRY: cmd_entry = cmd_entry {
     exec: cmd_choose_tree_exec,
 };
 
+pub static CMD_CHOOSE_LAYOUT_ENTRY: cmd_entry = cmd_entry {
+    name: SyncCharPtr::new(c"choose-layout"),
+    alias: SyncCharPtr::null(),
+
+    args: args_parse::new(c"F:f:K:NO:rt:Z", 0, 1, Some(cmd_choose_tree_args_parse)),
+    usage: SyncCharPtr::new(c"[-NrZ] [-F format] [-f filter] [-K key-format] [-O sort-order] [-t target-pane] [template]"),
+
+    target: cmd_entry_flag::new(b't', cmd_find_type::CMD_FIND_PANE, 0),
+    source: cmd_entry_flag::zeroed(),
+
+    flags: cmd_flag::empty(),
+    exec: cmd_choose_tree_exec,
+};
+
+pub static CMD_CHOOSE_TRASH_ENTRY: cmd_entry = cmd_entry {
+    name: SyncCharPtr::new(c"choose-trash"),
+    alias: SyncCharPtr::null(),
+
+    args: args_parse::new(c"F:f:K:NO:rt:Z", 0, 1, Some(cmd_choose_tree_args_parse)),
+    usage: SyncCharPtr::new(c"[-NrZ] [-F format] [-f filter] [-K key-format] [-O sort-order] [-t target-pane] [template]"),
+
+    target: cmd_entry_flag::new(b't', cmd_find_type::CMD_FIND_PANE, 0),
+    source: cmd_entry_flag::zeroed(),
+
+    flags: cmd_flag::empty(),
+    exec: cmd_choose_tree_exec,
+};
+
+pub static CMD_SEARCH_PANES_ENTRY: cmd_entry = cmd_entry {
+    name: SyncCharPtr::new(c"search-panes"),
+    alias: SyncCharPtr::new(c"searchp"),
+
+    args: args_parse::new(c"F:f:iK:NO:rt:Z", 1, 1, None),
+    usage: SyncCharPtr::new(
+        c"[-iNrZ] [-F format] [-f filter] [-K key-format] [-O sort-order] [-t target-pane] pattern",
+    ),
+
+    target: cmd_entry_flag::new(b't', cmd_find_type::CMD_FIND_PANE, 0),
+    source: cmd_entry_flag::zeroed(),
+
+    flags: cmd_flag::empty(),
+    exec: cmd_choose_tree_exec,
+};
+
 pub static CMD_CUSTOMIZE_MODE_ENTRY: cmd_entry = cmd_entry {
     name: SyncCharPtr::new(c"customize-mode"),
     alias: SyncCharPtr::null(),
@@ -94,6 +140,30 @@ unsafe fn cmd_choose_tree_exec(self_: *mut cmd, item: *mut cmdq_item) -> cmd_ret
                 return cmd_retval::CMD_RETURN_NORMAL;
             }
             &raw const WINDOW_CLIENT_MODE
+        } else if std::ptr::eq(cmd_get_entry(self_), &CMD_CHOOSE_LAYOUT_ENTRY) {
+            if layout_saved_count() == 0 {
+                return cmd_retval::CMD_RETURN_NORMAL;
+            }
+            &raw const WINDOW_LAYOUT_MODE
+        } else if std::ptr::eq(cmd_get_entry(self_), &CMD_CHOOSE_TRASH_ENTRY) {
+            if trash_count() == 0 {
+                return cmd_retval::CMD_RETURN_NORMAL;
+            }
+            &raw const WINDOW_TRASH_MODE
+        } else if std::ptr::eq(cmd_get_entry(self_), &CMD_SEARCH_PANES_ENTRY) {
+            let pattern = args_string(args, 0);
+            let mut r: regex_t = zeroed();
+            if regcomp(&raw mut r, pattern, window_search_flags(args)) != 0 {
+                cmdq_error!(item, "invalid pattern: {}", _s(pattern));
+                return cmd_retval::CMD_RETURN_ERROR;
+            }
+            let found = window_search_any(&raw const r);
+            regfree(&raw mut r);
+            if !found {
+                cmdq_error!(item, "no matches: {}", _s(pattern));
+                return cmd_retval::CMD_RETURN_ERROR;
+            }
+            &raw const WINDOW_SEARCH_MODE
         } else if std::ptr::eq(cmd_get_entry(self_), &CMD_CUSTOMIZE_MODE_ENTRY) {
             &raw const WINDOW_CUSTOMIZE_MODE
         } else {
@@ -0,0 +1,57 @@
+use crate::*;
+
+pub static CMD_CONSTRAIN_PANE_ENTRY: cmd_entry = cmd_entry {
+    name: SyncCharPtr::new(c"constrain-pane"),
+    alias: SyncCharPtr::new(c"constrainp"),
+
+    args: args_parse::new(c"t:ux:y:", 0, 0, None),
+    usage: SyncCharPtr::new(c"[-u] [-x width] [-y height] [-t target-pane]"),
+
+    target: cmd_entry_flag::new(b't', cmd_find_type::CMD_FIND_PANE, 0),
+
+    flags: cmd_flag::CMD_AFTERHOOK,
+    exec: cmd_constrain_pane_exec,
+    source: cmd_entry_flag::zeroed(),
+};
+
+unsafe fn cmd_constrain_pane_exec(self_: *mut cmd, item: *mut cmdq_item) -> cmd_retval {
+    unsafe {
+        let args = cmd_get_args(self_);
+        let target = cmdq_get_target(item);
+        let wp = (*target).wp;
+        let w = (*(*target).wl).window;
+        let lc = (*wp).layout_cell;
+
+        let mut constraint_x = (*lc).constraint_x;
+        let mut constraint_y = (*lc).constraint_y;
+        if args_has_(args, 'u') {
+            constraint_x = layout_constraint::default();
+            constraint_y = layout_constraint::default();
+        }
+        for (flag, name, constraint) in [
+            (b'x', "width", &mut constraint_x),
+            (b'y', "height", &mut constraint_y),
+        ] {
+            let value = args_get(args, flag);
+            if value.is_null() {
+                continue;
+            }
+            match layout_constraint_parse(cstr_to_str(value)) {
+                Ok(parsed) => *constraint = parsed,
+                Err(err) => {
+                    cmdq_error!(item, "{} {}", name, err);
+                    return cmd_retval::CMD_RETURN_ERROR;
+                }
+            }
+        }
+        (*lc).constraint_x = constraint_x;
+        (*lc).constraint_y = constraint_y;
+
+        // Resize the pane now if it is outside its new limits.
+        server_unzoom_window(w);
+        layout_constraints_fit(wp);
+        server_redraw_window(w);
+
+        cmd_retval::CMD_RETURN_NORMAL
+    }
+}
@@ -0,0 +1,66 @@
+use crate::*;
+
+use crate::compat::tree::rb_foreach;
+
+pub static CMD_JOIN_GROUP_ENTRY: cmd_entry = cmd_entry {
+    name: SyncCharPtr::new(c"join-group"),
+    alias: SyncCharPtr::new(c"joing"),
+
+    args: args_parse::new(c"t:", 1, 1, None),
+    usage: SyncCharPtr::new(c"[-t target-pane] name"),
+
+    target: cmd_entry_flag::new(b't', cmd_find_type::CMD_FIND_PANE, 0),
+
+    flags: cmd_flag::CMD_AFTERHOOK,
+    exec: cmd_join_group_exec,
+    source: cmd_entry_flag::zeroed(),
+};
+
+pub static CMD_LEAVE_GROUP_ENTRY: cmd_entry = cmd_entry {
+    name: SyncCharPtr::new(c"leave-group"),
+    alias: SyncCharPtr::new(c"leaveg"),
+
+    args: args_parse::new(c"at:", 0, 0, None),
+    usage: SyncCharPtr::new(c"[-a] [-t target-pane]"),
+
+    target: cmd_entry_flag::new(b't', cmd_find_type::CMD_FIND_PANE, 0),
+
+    flags: cmd_flag::CMD_AFTERHOOK,
+    exec: cmd_join_group_exec,
+    source: cmd_entry_flag::zeroed(),
+};
+
+unsafe fn cmd_join_group_exec(self_: *mut cmd, item: *mut cmdq_item) -> cmd_retval {
+    unsafe {
+        let args = cmd_get_args(self_);
+        let wp = (*cmdq_get_target(item)).wp;
+
+        if std::ptr::eq(cmd_get_entry(self_), &CMD_JOIN_GROUP_ENTRY) {
+            let name = args_string(args, 0);
+            if *name == b'\0' {
+                cmdq_error!(item, "empty group name");
+                return cmd_retval::CMD_RETURN_ERROR;
+            }
+            window_pane_set_group(wp, name);
+            return cmd_retval::CMD_RETURN_NORMAL;
+        }
+
+        if (*wp).group.is_null() {
+            cmdq_error!(item, "pane is not in a group");
+            return cmd_retval::CMD_RETURN_ERROR;
+        }
+        if args_has_(args, 'a') {
+            let group = xstrdup((*wp).group).as_ptr();
+            for loop_ in rb_foreach(&raw mut ALL_WINDOW_PANES).map(NonNull::as_ptr) {
+                if window_pane_in_group(loop_, group) {
+                    window_pane_set_group(loop_, null());
+                }
+            }
+            free_(group);
+        } else {
+            window_pane_set_group(wp, null());
+        }
+
+        cmd_retval::CMD_RETURN_NORMAL
+    }
+}
@@ -19,7 +19,7 @@ pub static CMD_KILL_PANE_ENTRY: cmd_entry = cmd_entry {
     alias: SyncCharPtr::new(c"killp"),
 
     args: args_parse::new(c"at:", 0, 0, None),
-    usage: SyncCharPtr::new(c"[-a] [-t target-client]"),
+    usage: SyncCharPtr::new(c"[-a] [-t target-pane]"),
 
     target: cmd_entry_flag::new(b't', cmd_find_type::CMD_FIND_PANE, 0),
 
@@ -36,22 +36,11 @@ unsafe fn cmd_kill_pane_exec(self_: *mut cmd, item: *mut cmdq_item) -> cmd_retva
         let wp = (*target).wp;
 
         if args_has(args, b'a') != 0 {
-            server_unzoom_window((*wl).window);
-            for loopwp in
-                tailq_foreach::<_, discr_entry>(&raw mut (*(*wl).window).panes).map(NonNull::as_ptr)
-            {
-                if loopwp == wp {
-                    continue;
-                }
-                server_client_remove_pane(loopwp);
-                layout_close_pane(loopwp);
-                window_remove_pane((*wl).window, loopwp);
-            }
-            server_redraw_window((*wl).window);
+            trash_kill_other_panes(wp, (*target).s);
             return cmd_retval::CMD_RETURN_NORMAL;
         }
 
-        server_kill_pane(wp);
+        trash_kill_pane(wp, (*target).s);
         cmd_retval::CMD_RETURN_NORMAL
     }
 }
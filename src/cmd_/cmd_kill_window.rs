@@ -74,7 +74,7 @@ unsafe fn cmd_kill_window_exec(self_: *mut cmd, item: *mut cmdq_item) -> cmd_ret
                 found = 0;
                 for loop_ in rb_foreach(&raw mut (*s).windows).map(NonNull::as_ptr) {
                     if (*loop_).window != (*wl).window {
-                        server_kill_window((*loop_).window, 0);
+                        trash_kill_window((*loop_).window, s, 0);
                         found += 1;
                         break;
                     }
@@ -97,7 +97,7 @@ unsafe fn cmd_kill_window_exec(self_: *mut cmd, item: *mut cmdq_item) -> cmd_ret
             }
             if found > 1 {
                 {
-                    server_kill_window((*wl).window, 0);
+                    trash_kill_window((*wl).window, s, 0);
                 }
             }
 
@@ -105,7 +105,7 @@ unsafe fn cmd_kill_window_exec(self_: *mut cmd, item: *mut cmdq_item) -> cmd_ret
             return cmd_retval::CMD_RETURN_NORMAL;
         }
 
-        server_kill_window((*wl).window, 1);
+        trash_kill_window((*wl).window, s, 1);
         cmd_retval::CMD_RETURN_NORMAL
     }
 }
@@ -0,0 +1,72 @@
+use crate::*;
+
+pub static CMD_LIST_MARKS_ENTRY: cmd_entry = cmd_entry {
+    name: SyncCharPtr::new(c"list-marks"),
+    alias: SyncCharPtr::new(c"lsm"),
+
+    args: args_parse::new(c"F:t:", 0, 0, None),
+    usage: SyncCharPtr::new(c"[-F format] [-t target-pane]"),
+
+    target: cmd_entry_flag::new(b't', cmd_find_type::CMD_FIND_PANE, 0),
+
+    flags: cmd_flag::CMD_AFTERHOOK,
+    exec: cmd_list_marks_exec,
+    source: cmd_entry_flag::zeroed(),
+};
+
+unsafe fn cmd_list_marks_exec(self_: *mut cmd, item: *mut cmdq_item) -> cmd_retval {
+    unsafe {
+        let args = cmd_get_args(self_);
+        let target = cmdq_get_target(item);
+        let wp = (*target).wp;
+        let gd = (*wp).base.grid;
+
+        let mut template: *const u8 = args_get(args, b'F');
+        if template.is_null() {
+            template = c!("#{mark_name}: #{mark_line},#{mark_column}: \"#{mark_sample}\"");
+        }
+
+        for mark in 0..GRID_MARKS as u32 {
+            let Some((px, py)) = grid_get_mark(gd, mark) else {
+                continue;
+            };
+
+            let ft = format_create(
+                cmdq_get_client(item),
+                item,
+                FORMAT_NONE,
+                format_flags::empty(),
+            );
+            format_defaults(
+                ft,
+                null_mut(),
+                NonNull::new((*target).s),
+                NonNull::new((*target).wl),
+                NonNull::new(wp),
+            );
+            format_add!(ft, c!("mark_name"), "{}", (b'a' + mark as u8) as char);
+            format_add!(ft, c!("mark_line"), "{}", (*gd).htrimmed + py as u64);
+            format_add!(ft, c!("mark_column"), "{}", px);
+
+            let sample = grid_string_cells(
+                gd,
+                0,
+                py,
+                (*gd).sx,
+                null_mut(),
+                grid_string_flags::GRID_STRING_TRIM_SPACES,
+                null_mut(),
+            );
+            format_add!(ft, c!("mark_sample"), "{}", _s(sample));
+            free_(sample);
+
+            let line = format_expand(ft, template);
+            cmdq_print!(item, "{}", _s(line));
+            free_(line);
+
+            format_free(ft);
+        }
+
+        cmd_retval::CMD_RETURN_NORMAL
+    }
+}
@@ -17,8 +17,8 @@ pub static CMD_MOVE_WINDOW_ENTRY: cmd_entry = cmd_entry {
     name: SyncCharPtr::new(c"move-window"),
     alias: SyncCharPtr::new(c"movew"),
 
-    args: args_parse::new(c"abdkrs:t:", 0, 0, None),
-    usage: SyncCharPtr::new(c"[-abdkr] [-s src-window] [-t dst-window]"),
+    args: args_parse::new(c"abdkMrs:t:", 0, 0, None),
+    usage: SyncCharPtr::new(c"[-abdkMr] [-s src-window] [-t dst-window]"),
 
     source: cmd_entry_flag::new(b's', cmd_find_type::CMD_FIND_WINDOW, 0),
 
@@ -51,6 +51,11 @@ unsafe fn cmd_move_window_exec(self_: *mut cmd, item: *mut cmdq_item) -> cmd_ret
         let wl = (*source).wl;
         let mut cause = null_mut();
 
+        if args_has_(args, 'M') {
+            cmd_move_window_mouse_start(cmdq_get_client(item), &raw mut (*cmdq_get_event(item)).m);
+            return cmd_retval::CMD_RETURN_NORMAL;
+        }
+
         if args_has_(args, 'r') {
             if cmd_find_target(
                 &raw mut target,
@@ -121,3 +126,139 @@ unsafe fn cmd_move_window_exec(self_: *mut cmd, item: *mut cmdq_item) -> cmd_ret
         cmd_retval::CMD_RETURN_NORMAL
     }
 }
+
+/// Get the window range on the status line at a client position.
+unsafe fn cmd_move_window_mouse_range(
+    c: *mut client,
+    m: *mut mouse_event,
+    x: u32,
+    y: u32,
+) -> *mut style_range {
+    unsafe {
+        if (*m).statusat == -1
+            || y < (*m).statusat as u32
+            || y >= (*m).statusat as u32 + (*m).statuslines
+        {
+            return null_mut();
+        }
+        let sr = status_get_range(c, x, y - (*m).statusat as u32);
+        if sr.is_null() || (*sr).type_ != style_range_type::STYLE_RANGE_WINDOW {
+            return null_mut();
+        }
+        sr
+    }
+}
+
+unsafe fn cmd_move_window_mouse_start(c: *mut client, m: *mut mouse_event) {
+    unsafe {
+        let mut s = null_mut();
+
+        if (*m).valid == 0 || c.is_null() || cmd_mouse_window(m, &raw mut s).is_none() {
+            return;
+        }
+        if (*c).session != s {
+            return;
+        }
+        let sr = cmd_move_window_mouse_range(c, m, (*m).lx, (*m).ly);
+        if sr.is_null() {
+            return;
+        }
+        let wl = winlink_find_by_index(&raw mut (*s).windows, (*sr).argument as i32);
+        if wl.is_null() {
+            return;
+        }
+
+        (*c).status_drag = status_drag {
+            session: (*s).id,
+            window: (*(*wl).window).id,
+            dropping: false,
+            target: -1,
+            before: false,
+            x: 0,
+            line: 0,
+        };
+        (*c).tty.mouse_drag_update = Some(cmd_move_window_mouse_update);
+        (*c).tty.mouse_drag_release = Some(cmd_move_window_mouse_release);
+        cmd_move_window_mouse_update(c, m);
+    }
+}
+
+unsafe fn cmd_move_window_mouse_update(c: *mut client, m: *mut mouse_event) {
+    unsafe {
+        let drag = &raw mut (*c).status_drag;
+        let old = *drag;
+
+        (*drag).dropping = false;
+        let sr = cmd_move_window_mouse_range(c, m, (*m).x, (*m).y);
+        let s = (*c).session;
+        if !sr.is_null() && !s.is_null() && (*s).id == (*drag).session {
+            let wl = winlink_find_by_index(&raw mut (*s).windows, (*sr).argument as i32);
+            if !wl.is_null() && (*(*wl).window).id != (*drag).window {
+                (*drag).dropping = true;
+                (*drag).target = (*wl).idx;
+                (*drag).before = (*m).x < ((*sr).start + (*sr).end) / 2;
+                (*drag).x = if (*drag).before {
+                    (*sr).start.saturating_sub(1)
+                } else {
+                    (*sr).end
+                };
+                (*drag).line = (*m).y - (*m).statusat as u32;
+            }
+        }
+
+        if (*drag).dropping != old.dropping
+            || (*drag).target != old.target
+            || (*drag).before != old.before
+        {
+            (*c).flags |= client_flag::REDRAWSTATUSALWAYS;
+        }
+    }
+}
+
+unsafe fn cmd_move_window_mouse_release(c: *mut client, _m: *mut mouse_event) {
+    unsafe {
+        let drag = (*c).status_drag;
+        if !drag.dropping {
+            return;
+        }
+        (*c).status_drag.dropping = false;
+        (*c).flags |= client_flag::REDRAWSTATUSALWAYS;
+
+        let Some(s) = session_find_by_id(drag.session).map(NonNull::as_ptr) else {
+            return;
+        };
+        let w = window_find_by_id(drag.window);
+        if w.is_null() {
+            return;
+        }
+        let Some(wl) = winlink_find_by_window(&raw mut (*s).windows, w).map(NonNull::as_ptr) else {
+            return;
+        };
+        let dst = winlink_find_by_index(&raw mut (*s).windows, drag.target);
+        if dst.is_null() || dst == wl {
+            return;
+        }
+        if drag.before && winlink_previous(dst) == wl {
+            return;
+        }
+        if !drag.before && winlink_next(dst) == wl {
+            return;
+        }
+
+        let idx = winlink_shuffle_up(s, dst, drag.before as i32);
+        if idx == -1 {
+            return;
+        }
+        let mut cause = null_mut();
+        if server_link_window(s, wl, s, idx, 0, ((*s).curw == wl) as i32, &raw mut cause) != 0 {
+            free_(cause);
+            return;
+        }
+        server_unlink_window(s, wl);
+        if options_get_number_((*s).options, c"renumber-windows") != 0 {
+            session_renumber_windows(s);
+        }
+        recalculate_sizes();
+        server_status_session(s);
+    }
+}
@@ -13,7 +13,7 @@
 // OUT OF OR IN CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
 use crate::*;
 
-use crate::compat::tree::rb_foreach;
+use crate::compat::{queue::tailq_first, tree::rb_foreach};
 
 const NEW_WINDOW_TEMPLATE: *const u8 = c!("#{session_name}:#{window_index}.#{pane_index}");
 
@@ -21,8 +21,8 @@ pub static CMD_NEW_WINDOW_ENTRY: cmd_entry = cmd_entry {
     name: SyncCharPtr::new(c"new-window"),
     alias: SyncCharPtr::new(c"neww"),
 
-    args: args_parse::new(c"abc:de:F:kn:PSt:", 0, -1, None),
-    usage: SyncCharPtr::new(c"[-abdkPS] [-c start-directory] [-e environment] [-F format] [-n window-name] [-t target-window] [shell-command]"),
+    args: args_parse::new(c"abc:de:F:kL:n:PSt:", 0, -1, None),
+    usage: SyncCharPtr::new(c"[-abdkPS] [-c start-directory] [-e environment] [-F format] [-L layout] [-n window-name] [-t target-window] [shell-command]"),
 
     target: cmd_entry_flag::new(b't', cmd_find_type::CMD_FIND_WINDOW, CMD_FIND_WINDOW_INDEX),
 
@@ -90,12 +90,35 @@ unsafe fn cmd_new_window_exec(self_: *mut cmd, item: *mut cmdq_item) -> cmd_retv
             }
         }
 
+        let layout = args_get_(args, 'L');
+        let mut spec = None;
+        if !layout.is_null() {
+            match layout_spec_parse(CStr::from_ptr(layout.cast()).to_bytes()) {
+                Ok(parsed) => spec = Some(parsed),
+                Err(error) => {
+                    cmdq_error!(item, "{}: {}", error, _s(layout));
+                    return cmd_retval::CMD_RETURN_ERROR;
+                }
+            }
+        }
+        let mut leaves = Vec::new();
+        if let Some(spec) = &spec {
+            layout_spec_leaves(spec, &mut leaves);
+        }
+
         sc.item = item;
         sc.s = s;
         sc.tc = tc;
 
         sc.name = args_get(args, b'n');
         args_to_vector(args, &raw mut sc.argc, &raw mut sc.argv);
+        if sc.argc == 0
+            && let Some(command) = leaves.first().and_then(|leaf| leaf.command.as_ref())
+        {
+            let mut argv = command.as_ptr() as *mut u8;
+            sc.argc = 1;
+            sc.argv = cmd_copy_argv(1, &raw mut argv);
+        }
         sc.environ = environ_create().as_ptr();
 
         let mut av = args_first_value(args, b'e');
@@ -125,6 +148,19 @@ unsafe fn cmd_new_window_exec(self_: *mut cmd, item: *mut cmdq_item) -> cmd_retv
             environ_free(sc.environ);
             return cmd_retval::CMD_RETURN_ERROR;
         }
+        if let Some(spec) = &spec
+            && cmd_new_window_layout(&raw mut sc, new_wl, spec, &leaves, &raw mut cause) != 0
+        {
+            cmdq_error!(item, "create pane failed: {}", _s(cause));
+            free_(cause);
+            server_kill_window((*new_wl).window, 1);
+            if !sc.argv.is_null() {
+                cmd_free_argv(sc.argc, sc.argv);
+            }
+            environ_free(sc.environ);
+            return cmd_retval::CMD_RETURN_ERROR;
+        }
+
         if !args_has_(args, 'd') || new_wl == (*s).curw {
             cmd_find_from_winlink(current, new_wl, 0);
             server_redraw_session_group(s);
@@ -153,3 +189,65 @@ unsafe fn cmd_new_window_exec(self_: *mut cmd, item: *mut cmdq_item) -> cmd_retv
         cmd_retval::CMD_RETURN_NORMAL
     }
 }
+
+/// Build the layout for a new window and create a pane for each cell after
+/// the first, which holds the window's existing pane.
+unsafe fn cmd_new_window_layout(
+    sc: *mut spawn_context,
+    wl: *mut winlink,
+    spec: &layout_spec,
+    leaves: &[&layout_spec],
+    cause: *mut *mut u8,
+) -> i32 {
+    unsafe {
+        let w = (*wl).window;
+        let (argc0, argv0) = ((*sc).argc, (*sc).argv);
+
+        let cells = layout_spec_build(w, spec);
+        let mut wp = tailq_first(&raw mut (*w).panes);
+        layout_make_leaf(cells[0], wp);
+        layout_fix_offsets(w);
+        layout_fix_panes(w, null_mut());
+
+        for (i, leaf) in leaves.iter().enumerate().skip(1) {
+            (*sc).wl = wl;
+            (*sc).wp0 = wp;
+            (*sc).lc = cells[i];
+            (*sc).name = null_mut();
+            (*sc).idx = -1;
+            (*sc).flags = SPAWN_DETACHED;
+
+            let mut argc = 0;
+            let mut argv = null_mut();
+            if let Some(command) = &leaf.command {
+                let mut cp = command.as_ptr() as *mut u8;
+                argc = 1;
+                argv = cmd_copy_argv(1, &raw mut cp);
+            }
+            (*sc).argc = argc;
+            (*sc).argv = argv;
+
+            let new_wp = spawn_pane(sc, cause);
+            cmd_free_argv(argc, argv);
+            (*sc).argc = argc0;
+            (*sc).argv = argv0;
+            if new_wp.is_null() {
+                // The failed pane's cell is already gone, remove the rest.
+                for lc in &cells[i + 1..] {
+                    layout_destroy_cell(w, *lc, &raw mut (*w).layout_root);
+                }
+                layout_fix_offsets(w);
+                layout_fix_panes(w, null_mut());
+                notify_window(c"window-layout-changed", w);
+                return -1;
+            }
+            wp = new_wp;
+        }
+
+        layout_fix_offsets(w);
+        layout_fix_panes(w, null_mut());
+        notify_window(c"window-layout-changed", w);
+        server_redraw_window(w);
+        0
+    }
+}
@@ -32,6 +32,7 @@ pub(crate) use cmdq_get_callback;
 /* Command queue flags. */
 pub const CMDQ_FIRED: i32 = 0x1;
 pub const CMDQ_WAITING: i32 = 0x2;
+pub const CMDQ_MARKED: i32 = 0x4;
 
 /* Command queue item type. */
 #[repr(i32)]
@@ -64,6 +65,9 @@ pub struct cmdq_item {
     pub source: cmd_find_state,
     pub target: cmd_find_state,
 
+    /// Marked pane to use for {marked} if CMDQ_MARKED is set.
+    pub mark: cmd_find_state,
+
     pub cmdlist: *mut cmd_list,
     pub cmd: *mut cmd,
 
@@ -565,6 +569,11 @@ pub unsafe fn cmdq_find_flag(
             return cmd_retval::CMD_RETURN_NORMAL;
         }
 
+        if (*item).flags & CMDQ_MARKED != 0 && cmdq_flag_is_marked(item, flag) {
+            cmd_find_copy_state(fs, &raw mut (*item).mark);
+            return cmd_retval::CMD_RETURN_NORMAL;
+        }
+
         let value = args_get(cmd_get_args((*item).cmd), (*flag).flag);
         if cmd_find_target(fs, item, value, (*flag).type_, (*flag).flags) != 0 {
             cmd_find_clear_state(fs, 0);
@@ -575,6 +584,104 @@ pub unsafe fn cmdq_find_flag(
     }
 }
 
+/// Is this flag given as the marked pane?
+unsafe fn cmdq_flag_is_marked(item: *mut cmdq_item, flag: *const cmd_entry_flag) -> bool {
+    unsafe {
+        if (*flag).flag == 0 {
+            return false;
+        }
+        let value = args_get(cmd_get_args((*item).cmd), (*flag).flag);
+        !value.is_null() && matches!(cstr_to_str(value), "~" | "{marked}")
+    }
+}
+
+/// Insert the after hook for a command which has run with the given target.
+unsafe fn cmdq_insert_after_hook(item: *mut cmdq_item, target: *mut cmd_find_state) {
+    unsafe {
+        let entry = cmd_get_entry((*item).cmd);
+        if !entry.flags.intersects(cmd_flag::CMD_AFTERHOOK) {
+            return;
+        }
+
+        let mut fs: cmd_find_state = zeroed();
+        let fsp = if cmd_find_valid_state(target) {
+            target
+        } else if cmd_find_valid_state(&raw mut (*(*item).state).current) {
+            &raw mut (*(*item).state).current
+        } else if cmd_find_from_client(&raw mut fs, (*item).client, 0) == 0 {
+            &raw mut fs
+        } else {
+            return;
+        };
+        cmdq_insert_hook!((*fsp).s, item, fsp, "after-{}", _s(entry.name.as_ptr()));
+    }
+}
+
+/// Get a copy of a command item for each marked pane, with that pane as its
+/// {marked} target.
+pub unsafe fn cmdq_get_marked<'a>(
+    item: *mut cmdq_item,
+    marks: impl Iterator<Item = &'a cmd_find_state>,
+) -> *mut cmdq_item {
+    unsafe {
+        let entry = cmd_get_entry((*item).cmd);
+        let mut first: *mut cmdq_item = null_mut();
+        let mut last: *mut cmdq_item = null_mut();
+
+        for mark in marks {
+            let new_item = xcalloc1::<cmdq_item>() as *mut cmdq_item;
+            (*new_item).name = format_nul!("[{}/{:p}]", _s(entry.name.as_ptr()), new_item);
+            (*new_item).type_ = cmdq_type::CMDQ_COMMAND;
+
+            (*new_item).group = (*item).group;
+            (*new_item).state = cmdq_link_state((*item).state);
+
+            (*new_item).cmdlist = (*item).cmdlist;
+            (*new_item).cmd = (*item).cmd;
+            (*(*item).cmdlist).references += 1;
+
+            (*new_item).flags = CMDQ_MARKED;
+            (*new_item).mark = *mark;
+
+            if first.is_null() {
+                first = new_item;
+            }
+            if !last.is_null() {
+                (*last).next = new_item;
+            }
+            last = new_item;
+        }
+        first
+    }
+}
+
+/// Run a command and insert its after hook. If the target (or failing that
+/// the source) is the marked pane and several panes are marked, instead queue
+/// the command once for each of them, so each can wait on its own.
+unsafe fn cmdq_fire_exec(item: *mut cmdq_item) -> cmd_retval {
+    unsafe {
+        let cmd = (*item).cmd;
+        let entry = cmd_get_entry(cmd);
+
+        if (*item).flags & CMDQ_MARKED == 0
+            && (cmdq_flag_is_marked(item, &entry.target)
+                || cmdq_flag_is_marked(item, &entry.source))
+        {
+            server_check_marked_panes();
+            if server_marked_panes().len() > 1 {
+                cmdq_insert_after(item, cmdq_get_marked(item, server_marked_panes()));
+                return cmd_retval::CMD_RETURN_NORMAL;
+            }
+        }
+
+        let retval = (entry.exec)(cmd, item);
+        if retval != cmd_retval::CMD_RETURN_ERROR {
+            cmdq_insert_after_hook(item, &raw mut (*item).target);
+        }
+        retval
+    }
+}
+
 pub unsafe fn cmdq_add_message(item: *mut cmdq_item) {
     unsafe {
         let c = (*item).client;
@@ -664,6 +771,13 @@ pub unsafe fn cmdq_fire_command(item: *mut cmdq_item) -> cmd_retval {
             }
             (*item).target_client = tc;
 
+            // A marked pane may have gone since the command was queued for
+            // it, in which case there is nothing to do.
+            if (*item).flags & CMDQ_MARKED != 0 && !cmd_find_valid_state(&raw mut (*item).mark) {
+                retval = cmd_retval::CMD_RETURN_NORMAL;
+                break 'out;
+            }
+
             retval = cmdq_find_flag(item, &raw mut (*item).source, &entry.source);
             if retval == cmd_retval::CMD_RETURN_ERROR {
                 break 'out;
@@ -675,23 +789,7 @@ pub unsafe fn cmdq_fire_command(item: *mut cmdq_item) -> cmd_retval {
 
             // log_debug_!("entry_name: {}", PercentS((*entry).name));
 
-            retval = (entry.exec)(cmd, item);
-            if retval == cmd_retval::CMD_RETURN_ERROR {
-                break 'out;
-            }
-
-            if entry.flags.intersects(cmd_flag::CMD_AFTERHOOK) {
-                fsp = if cmd_find_valid_state(&raw mut (*item).target) {
-                    &raw mut (*item).target
-                } else if cmd_find_valid_state(&raw mut (*(*item).state).current) {
-                    &raw mut (*(*item).state).current
-                } else if cmd_find_from_client(&raw mut fs, (*item).client, 0) == 0 {
-                    &raw mut fs
-                } else {
-                    break 'out;
-                };
-                cmdq_insert_hook!((*fsp).s, item, fsp, "after-{}", _s(entry.name.as_ptr()));
-            }
+            retval = cmdq_fire_exec(item);
         }
 
         (*item).client = saved;
@@ -927,3 +1025,56 @@ pub unsafe fn cmdq_error_(item: *mut cmdq_item, args: std::fmt::Arguments) {
         free_(msg);
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_get_marked() {
+        unsafe {
+            let input = cmd_parse_input {
+                flags: cmd_parse_input_flags::CMD_PARSE_NOALIAS.into(),
+                ..zeroed()
+            };
+            let cmdlist =
+                match cmd_parse_from_string("run-shell -t '{marked}' 'sleep 1'", Some(&input)) {
+                    Ok(cmdlist) => cmdlist,
+                    Err(cause) => panic!("{}", _s(cause)),
+                };
+
+            let queue = cmdq_new().as_ptr();
+            let item = cmdq_get_command(cmdlist, null_mut());
+            (*item).queue = queue;
+            tailq_insert_tail(&raw mut (*queue).list, item);
+
+            let marks = [
+                cmd_find_state { idx: 1, ..zeroed() },
+                cmd_find_state { idx: 2, ..zeroed() },
+            ];
+            cmdq_insert_after(item, cmdq_get_marked(item, marks.iter()));
+
+            // Each mark is its own item after the original, in order, so a
+            // waiting command such as run-shell waits separately for each.
+            let mut this = tailq_next(item);
+            for idx in [1, 2] {
+                assert!(!this.is_null());
+                assert!(matches!((*this).type_, cmdq_type::CMDQ_COMMAND));
+                assert_eq!((*this).flags, CMDQ_MARKED);
+                assert_eq!((*this).mark.idx, idx);
+                assert_eq!((*this).cmd, (*item).cmd);
+                assert_eq!((*this).group, (*item).group);
+                assert_eq!((*this).state, (*item).state);
+                this = tailq_next(this);
+            }
+            assert!(this.is_null());
+            assert_eq!((*cmdlist).references, 4);
+
+            while !tailq_empty(&raw mut (*queue).list) {
+                cmdq_remove(tailq_first(&raw mut (*queue).list));
+            }
+            cmdq_free(queue);
+            cmd_list_free(cmdlist);
+        }
+    }
+}
@@ -13,14 +13,14 @@
 // OUT OF OR IN CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
 use crate::*;
 
-use crate::compat::queue::tailq_empty;
+use crate::compat::queue::{tailq_empty, tailq_foreach};
 
 pub static CMD_RESIZE_PANE_ENTRY: cmd_entry = cmd_entry {
     name: SyncCharPtr::new(c"resize-pane"),
     alias: SyncCharPtr::new(c"resizep"),
 
-    args: args_parse::new(c"DLMRTt:Ux:y:Z", 0, 1, None),
-    usage: SyncCharPtr::new(c"[-DLMRTUZ] [-x width] [-y height] [-t target-pane] [adjustment]"),
+    args: args_parse::new(c"DILMRTt:Ux:y:Z", 0, 1, None),
+    usage: SyncCharPtr::new(c"[-DILMRTUZ] [-x width] [-y height] [-t target-pane] [adjustment]"),
 
     target: cmd_entry_flag::new(b't', cmd_find_type::CMD_FIND_PANE, 0),
 
@@ -68,11 +68,20 @@ unsafe fn cmd_resize_pane_exec(self_: *mut cmd, item: *mut cmdq_item) -> cmd_ret
             if c.is_null() || (*c).session != s {
                 return cmd_retval::CMD_RETURN_NORMAL;
             }
+            if layout_drag_start(c, &raw mut (*event).m) {
+                return cmd_retval::CMD_RETURN_NORMAL;
+            }
             (*c).tty.mouse_drag_update = Some(cmd_resize_pane_mouse_update);
             cmd_resize_pane_mouse_update(c, &raw mut (*event).m);
             return cmd_retval::CMD_RETURN_NORMAL;
         }
 
+        if args_has_(args, 'I') {
+            server_unzoom_window(w);
+            window_pane_set_mode(wp, null_mut(), &WINDOW_RESIZE_MODE, null_mut(), null_mut());
+            return cmd_retval::CMD_RETURN_NORMAL;
+        }
+
         if args_has_(args, 'Z') {
             if (*w).flags.intersects(window_flag::ZOOMED) {
                 window_unzoom(w, 1);
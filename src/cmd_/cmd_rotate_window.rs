@@ -48,6 +48,7 @@ unsafe fn cmd_rotate_window_exec(self_: *mut cmd, item: *mut cmdq_item) -> cmd_r
         let yoff: u32;
 
         window_push_zoom(w, 0, args_has(args, b'Z'));
+        let saved = layout_stack_detach(w);
 
         if args_has_(args, 'D') {
             wp = tailq_last(&raw mut (*w).panes);
@@ -127,6 +128,7 @@ unsafe fn cmd_rotate_window_exec(self_: *mut cmd, item: *mut cmdq_item) -> cmd_r
             }
         }
 
+        layout_stack_attach(w, saved);
         window_set_active_pane(w, wp, 1);
         cmd_find_from_winlink_pane(current, wl, wp, 0);
         window_pop_zoom(w);
@@ -22,9 +22,9 @@ pub static CMD_RUN_SHELL_ENTRY: cmd_entry = cmd_entry {
     name: SyncCharPtr::new(c"run-shell"),
     alias: SyncCharPtr::new(c"run"),
 
-    args: args_parse::new(c"bd:Ct:c:", 0, 2, Some(cmd_run_shell_args_parse)),
+    args: args_parse::new(c"bd:CE:O:T:t:c:", 0, 2, Some(cmd_run_shell_args_parse)),
     usage: SyncCharPtr::new(
-        c"[-bC] [-c start-directory] [-d delay] [-t target-pane] [shell-command]",
+        c"[-bC] [-c start-directory] [-d delay] [-E stderr-target] [-O stdout-target] [-T timeout] [-t target-pane] [shell-command]",
     ),
 
     target: cmd_entry_flag::new(b't', cmd_find_type::CMD_FIND_PANE, CMD_FIND_CANFAIL),
@@ -45,8 +45,19 @@ pub struct cmd_run_shell_data<'a> {
     pub wp_id: i32,
     pub timer: event,
     pub flags: job_flag,
+
+    pub out: *mut u8,
+    pub err: *mut u8,
+
+    pub job: *mut job,
+    pub timeout: f64,
+    pub timed_out: i32,
+    pub killtimer: event,
 }
 
+/// Exit status reported for a job killed by a timeout.
+const RUN_SHELL_TIMEOUT_STATUS: i32 = 124;
+
 pub unsafe fn cmd_run_shell_args_parse(
     args: *mut args,
     _idx: u32,
@@ -110,6 +121,7 @@ pub unsafe fn cmd_run_shell_exec(self_: *mut cmd, item: *mut cmdq_item) -> cmd_r
         let s = (*target).s;
         let wp = (*target).wp;
         let mut d: f64 = 0.0;
+        let mut timeout: f64 = 0.0;
         let mut end: *mut u8 = null_mut();
         let wait = !args_has(args, b'b') as i32;
 
@@ -123,6 +135,14 @@ pub unsafe fn cmd_run_shell_exec(self_: *mut cmd, item: *mut cmdq_item) -> cmd_r
         } else if args_count(args) == 0 {
             return cmd_retval::CMD_RETURN_NORMAL;
         }
+        if args_has_(args, 'T') {
+            let value = args_get(args, b'T');
+            timeout = strtod(value, &raw mut end);
+            if *end != b'\0' || timeout <= 0.0 {
+                cmdq_error!(item, "invalid timeout: {}", _s(value));
+                return cmd_retval::CMD_RETURN_ERROR;
+            }
+        }
 
         let cdata = xcalloc1::<cmd_run_shell_data>() as *mut cmd_run_shell_data;
         if !args_has_(args, 'C') {
@@ -150,6 +170,18 @@ pub unsafe fn cmd_run_shell_exec(self_: *mut cmd, item: *mut cmdq_item) -> cmd_r
         if !(*cdata).client.is_null() {
             (*(*cdata).client).references += 1;
         }
+        if args_has_(args, 'O') {
+            (*cdata).out = xstrdup(args_get_(args, 'O')).as_ptr();
+        }
+        if args_has_(args, 'E') {
+            (*cdata).err = xstrdup(args_get_(args, 'E')).as_ptr();
+            (*cdata).flags |= job_flag::JOB_STDERR;
+        }
+        (*cdata).timeout = timeout;
+        if timeout != 0.0 {
+            (*cdata).flags |= job_flag::JOB_PGROUP;
+        }
+
         if args_has_(args, 'c') {
             (*cdata).cwd = xstrdup(args_get_(args, 'c')).as_ptr();
         } else {
@@ -166,10 +198,15 @@ pub unsafe fn cmd_run_shell_exec(self_: *mut cmd, item: *mut cmdq_item) -> cmd_r
             Some(cmd_run_shell_timer),
             cdata.cast(),
         );
+        evtimer_set(
+            &raw mut (*cdata).killtimer,
+            Some(cmd_run_shell_kill_timer),
+            cdata.cast(),
+        );
         if !delay.is_null() {
             let mut tv: timeval = timeval {
                 tv_sec: d as time_t,
-                tv_usec: (d - (d as time_t as f64)) as libc::suseconds_t * 1000000,
+                tv_usec: ((d - (d as time_t as f64)) * 1000000.0) as libc::suseconds_t,
             };
             evtimer_add(&raw mut (*cdata).timer, &raw mut tv);
         } else {
@@ -199,7 +236,7 @@ pub unsafe extern "C" fn cmd_run_shell_timer(_fd: i32, _events: i16, arg: *mut c
                 cmd_run_shell_free(cdata.cast());
                 return;
             }
-            if job_run(
+            (*cdata).job = job_run(
                 cmd,
                 0,
                 null_mut(),
@@ -213,10 +250,16 @@ pub unsafe extern "C" fn cmd_run_shell_timer(_fd: i32, _events: i16, arg: *mut c
                 (*cdata).flags,
                 -1,
                 -1,
-            )
-            .is_null()
-            {
+            );
+            if (*cdata).job.is_null() {
                 cmd_run_shell_free(cdata.cast());
+            } else if (*cdata).timeout != 0.0 {
+                let d = (*cdata).timeout;
+                let mut tv: timeval = timeval {
+                    tv_sec: d as time_t,
+                    tv_usec: ((d - (d as time_t as f64)) * 1000000.0) as libc::suseconds_t,
+                };
+                evtimer_add(&raw mut (*cdata).killtimer, &raw mut tv);
             }
             return;
         }
@@ -245,6 +288,55 @@ pub unsafe extern "C" fn cmd_run_shell_timer(_fd: i32, _events: i16, arg: *mut c
     }
 }
 
+/// Kill a job which has run for too long and finish it with what it has
+/// written so far.
+unsafe extern "C" fn cmd_run_shell_kill_timer(_fd: i32, _events: i16, arg: *mut c_void) {
+    unsafe {
+        let cdata = arg as *mut cmd_run_shell_data;
+        let job = (*cdata).job;
+
+        log_debug!("run-shell timed out: {}", _s((*cdata).cmd));
+        job_kill_group(job);
+        (*cdata).timed_out = 1;
+
+        cmd_run_shell_callback(job);
+        job_free(job);
+    }
+}
+
+/// Store job output in a paste buffer, or a user option if the name starts
+/// with @.
+unsafe fn cmd_run_shell_store(job: *mut job, name: *const u8, evb: *mut evbuffer) {
+    unsafe {
+        let mut size = EVBUFFER_LENGTH(evb);
+        let mut cause = null_mut();
+
+        if *name == b'@' {
+            let data = EVBUFFER_DATA(evb);
+            if size != 0 && *data.add(size - 1) == b'\n' {
+                size -= 1;
+            }
+            let value = std::slice::from_raw_parts(data, size);
+            options_set_string!(
+                GLOBAL_S_OPTIONS,
+                name,
+                0,
+                "{}",
+                String::from_utf8_lossy(value)
+            );
+            return;
+        }
+
+        let data: *mut u8 = xmalloc(size.max(1)).cast().as_ptr();
+        memcpy(data.cast(), EVBUFFER_DATA(evb).cast(), size);
+        if paste_set(data, size, name, &raw mut cause) != 0 {
+            cmd_run_shell_print(job, cause);
+            free_(cause);
+            free_(data);
+        }
+    }
+}
+
 pub unsafe fn cmd_run_shell_callback(job: *mut job) {
     unsafe {
         let cdata = job_get_data(job) as *mut cmd_run_shell_data;
@@ -258,8 +350,15 @@ pub unsafe fn cmd_run_shell_callback(job: *mut job) {
         let status: i32 = 0;
         // int retcode, status;
 
+        if !(*cdata).err.is_null() {
+            let errevent = job_get_error_event(job);
+            if !errevent.is_null() {
+                cmd_run_shell_store(job, (*cdata).err, (*errevent).input);
+            }
+        }
+
         let mut line = null_mut::<u8>();
-        loop {
+        while (*cdata).out.is_null() {
             line = evbuffer_readln(
                 (*event).input,
                 null_mut(),
@@ -275,7 +374,9 @@ pub unsafe fn cmd_run_shell_callback(job: *mut job) {
         }
 
         let size = EVBUFFER_LENGTH((*event).input);
-        if size != 0 {
+        if !(*cdata).out.is_null() {
+            cmd_run_shell_store(job, (*cdata).out, (*event).input);
+        } else if size != 0 {
             line = xmalloc(size + 1).cast().as_ptr();
             memcpy(line.cast(), EVBUFFER_DATA((*event).input).cast(), size);
             *line.add(size) = b'\0';
@@ -286,7 +387,10 @@ pub unsafe fn cmd_run_shell_callback(job: *mut job) {
         }
 
         let status = job_get_status(job);
-        if WIFEXITED(status) {
+        if (*cdata).timed_out != 0 {
+            retcode = RUN_SHELL_TIMEOUT_STATUS;
+            msg = format_nul!("'{}' timed out", _s(cmd));
+        } else if WIFEXITED(status) {
             retcode = WEXITSTATUS(status);
             if retcode != 0 {
                 msg = format_nul!("'{}' returned {}", _s(cmd), retcode);
@@ -304,6 +408,7 @@ pub unsafe fn cmd_run_shell_callback(job: *mut job) {
         free_(msg);
 
         if !item.is_null() {
+            cmdq_add_format!(cmdq_get_state(item), c!("run_status"), "{}", retcode);
             if !cmdq_get_client(item).is_null() && (*cmdq_get_client(item)).session.is_null() {
                 (*cmdq_get_client(item)).retval = retcode;
             }
@@ -318,6 +423,7 @@ pub unsafe fn cmd_run_shell_free(data: *mut c_void) {
         let cdata = data as *mut cmd_run_shell_data;
 
         evtimer_del(&raw mut (*cdata).timer);
+        evtimer_del(&raw mut (*cdata).killtimer);
         if !(*cdata).s.is_null() {
             session_remove_ref((*cdata).s, __func__);
         }
@@ -327,6 +433,8 @@ pub unsafe fn cmd_run_shell_free(data: *mut c_void) {
         if !(*cdata).state.is_null() {
             args_make_commands_free((*cdata).state);
         }
+        free_((*cdata).out);
+        free_((*cdata).err);
         free_((*cdata).cwd);
         free_((*cdata).cmd);
         free_(cdata);
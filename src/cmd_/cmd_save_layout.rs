@@ -0,0 +1,131 @@
+use crate::*;
+
+pub static CMD_SAVE_LAYOUT_ENTRY: cmd_entry = cmd_entry {
+    name: SyncCharPtr::new(c"save-layout"),
+    alias: SyncCharPtr::new(c"savel"),
+
+    args: args_parse::new(c"l:t:", 1, 1, None),
+    usage: SyncCharPtr::new(c"[-l layout] [-t target-window] name"),
+
+    target: cmd_entry_flag::new(b't', cmd_find_type::CMD_FIND_WINDOW, CMD_FIND_CANFAIL),
+
+    flags: cmd_flag::CMD_AFTERHOOK,
+    exec: cmd_save_layout_exec,
+    source: cmd_entry_flag::zeroed(),
+};
+
+pub static CMD_DELETE_LAYOUT_ENTRY: cmd_entry = cmd_entry {
+    name: SyncCharPtr::new(c"delete-layout"),
+    alias: SyncCharPtr::new(c"deletel"),
+
+    args: args_parse::new(c"", 1, 1, None),
+    usage: SyncCharPtr::new(c"name"),
+
+    target: cmd_entry_flag::zeroed(),
+
+    flags: cmd_flag::CMD_AFTERHOOK,
+    exec: cmd_save_layout_exec,
+    source: cmd_entry_flag::zeroed(),
+};
+
+pub static CMD_LIST_LAYOUTS_ENTRY: cmd_entry = cmd_entry {
+    name: SyncCharPtr::new(c"list-layouts"),
+    alias: SyncCharPtr::new(c"lsl"),
+
+    args: args_parse::new(c"eF:f:", 0, 0, None),
+    usage: SyncCharPtr::new(c"[-e] [-F format] [-f filter]"),
+
+    target: cmd_entry_flag::zeroed(),
+
+    flags: cmd_flag::CMD_AFTERHOOK,
+    exec: cmd_list_layouts_exec,
+    source: cmd_entry_flag::zeroed(),
+};
+
+unsafe fn cmd_save_layout_exec(self_: *mut cmd, item: *mut cmdq_item) -> cmd_retval {
+    unsafe {
+        let args = cmd_get_args(self_);
+        let name = args_string(args, 0);
+
+        if std::ptr::eq(cmd_get_entry(self_), &CMD_DELETE_LAYOUT_ENTRY) {
+            if !layout_saved_remove(name) {
+                cmdq_error!(item, "unknown layout: {}", _s(name));
+                return cmd_retval::CMD_RETURN_ERROR;
+            }
+            return cmd_retval::CMD_RETURN_NORMAL;
+        }
+
+        let layout = if args_has_(args, 'l') {
+            xstrdup(args_get_(args, 'l')).as_ptr()
+        } else {
+            let wl = (*cmdq_get_target(item)).wl;
+            if wl.is_null() {
+                cmdq_error!(item, "no window to save");
+                return cmd_retval::CMD_RETURN_ERROR;
+            }
+            layout_dump((*(*wl).window).layout_root)
+        };
+        if layout.is_null() {
+            cmdq_error!(item, "layout too large to save");
+            return cmd_retval::CMD_RETURN_ERROR;
+        }
+
+        let mut cause = null_mut();
+        if layout_saved_set(name, layout, &raw mut cause) != 0 {
+            cmdq_error!(item, "{}", _s(cause));
+            free_(cause);
+            free_(layout);
+            return cmd_retval::CMD_RETURN_ERROR;
+        }
+        free_(layout);
+        cmd_retval::CMD_RETURN_NORMAL
+    }
+}
+
+unsafe fn cmd_list_layouts_exec(self_: *mut cmd, item: *mut cmdq_item) -> cmd_retval {
+    unsafe {
+        let args = cmd_get_args(self_);
+
+        let mut template: *const u8 = args_get(args, b'F');
+        if template.is_null() {
+            if args_has_(args, 'e') {
+                template = c!("save-layout -l '#{layout_string}' #{q:layout_name}");
+            } else {
+                template =
+                    c!("#{layout_name}: #{layout_panes} panes [#{layout_width}x#{layout_height}]");
+            }
+        }
+        let filter = args_get(args, b'f');
+
+        for name in layout_saved_names() {
+            let Some(ls) = layout_saved_get(name.as_ptr().cast()) else {
+                continue;
+            };
+            let ft = format_create(
+                cmdq_get_client(item),
+                item,
+                FORMAT_NONE,
+                format_flags::empty(),
+            );
+            format_defaults_layout_saved(ft, ls);
+
+            let flag = if !filter.is_null() {
+                let expanded = format_expand(ft, filter);
+                let flag = format_true(expanded);
+                free_(expanded);
+                flag
+            } else {
+                1
+            };
+            if flag != 0 {
+                let line = format_expand(ft, template);
+                cmdq_print!(item, "{}", _s(line));
+                free_(line);
+            }
+
+            format_free(ft);
+        }
+
+        cmd_retval::CMD_RETURN_NORMAL
+    }
+}
@@ -0,0 +1,352 @@
+use crate::*;
+
+use crate::compat::queue::{tailq_foreach, tailq_insert_tail, tailq_remove};
+use crate::libc::{localtime_r, mktime, strtod, tm};
+
+pub static CMD_SCHEDULE_COMMAND_ENTRY: cmd_entry = cmd_entry {
+    name: SyncCharPtr::new(c"schedule-command"),
+    alias: SyncCharPtr::new(c"sched"),
+
+    args: args_parse::new(c"a:d:i:n:t:", 1, 1, None),
+    usage: SyncCharPtr::new(
+        c"[-a time] [-d delay] [-i interval] [-n name] [-t target-pane] command",
+    ),
+
+    target: cmd_entry_flag::new(b't', cmd_find_type::CMD_FIND_PANE, CMD_FIND_CANFAIL),
+
+    flags: cmd_flag::CMD_AFTERHOOK,
+    exec: cmd_schedule_command_exec,
+    source: cmd_entry_flag::zeroed(),
+};
+
+pub static CMD_LIST_SCHEDULES_ENTRY: cmd_entry = cmd_entry {
+    name: SyncCharPtr::new(c"list-schedules"),
+    alias: SyncCharPtr::new(c"lssched"),
+
+    args: args_parse::new(c"F:f:", 0, 0, None),
+    usage: SyncCharPtr::new(c"[-F format] [-f filter]"),
+
+    flags: cmd_flag::CMD_AFTERHOOK,
+    exec: cmd_list_schedules_exec,
+    source: cmd_entry_flag::zeroed(),
+    target: cmd_entry_flag::zeroed(),
+};
+
+pub static CMD_CANCEL_SCHEDULE_ENTRY: cmd_entry = cmd_entry {
+    name: SyncCharPtr::new(c"cancel-schedule"),
+    alias: SyncCharPtr::new(c"unsched"),
+
+    args: args_parse::new(c"a", 0, 1, None),
+    usage: SyncCharPtr::new(c"[-a] [schedule]"),
+
+    flags: cmd_flag::CMD_AFTERHOOK,
+    exec: cmd_cancel_schedule_exec,
+    source: cmd_entry_flag::zeroed(),
+    target: cmd_entry_flag::zeroed(),
+};
+
+crate::compat::impl_tailq_entry!(schedule, entry, tailq_entry<schedule>);
+#[repr(C)]
+pub struct schedule {
+    id: u32,
+    name: *mut u8,
+
+    cmd: *mut u8,
+    cmdlist: *mut cmd_list,
+    wp_id: i32,
+
+    interval: f64,
+    next: time_t,
+    count: u32,
+
+    timer: event,
+
+    entry: tailq_entry<schedule>,
+}
+
+static mut SCHEDULES: tailq_head<schedule> = compat::TAILQ_HEAD_INITIALIZER!(SCHEDULES);
+static mut SCHEDULE_NEXT_ID: u32 = 0;
+
+/// Parse a number of seconds, which may be fractional.
+unsafe fn cmd_schedule_seconds(value: *const u8) -> Option<f64> {
+    unsafe {
+        let mut end: *mut u8 = null_mut();
+        let d = strtod(value, &raw mut end);
+        if *end != b'\0' || d < 0.0 {
+            return None;
+        }
+        Some(d)
+    }
+}
+
+/// Work out the seconds until the next occurrence of a HH:MM[:SS] time.
+unsafe fn cmd_schedule_at(value: *const u8) -> Option<f64> {
+    unsafe {
+        let mut fields = [0i32; 3];
+        let mut n = 0;
+        for part in cstr_to_str(value).split(':') {
+            if n == fields.len() || part.is_empty() || part.len() > 2 {
+                return None;
+            }
+            fields[n] = part.parse().ok()?;
+            n += 1;
+        }
+        if n < 2 || fields[0] > 23 || fields[1] > 59 || fields[2] > 59 {
+            return None;
+        }
+
+        let now = libc::time(null_mut());
+        let mut tm: tm = zeroed();
+        localtime_r(&raw const now, &raw mut tm);
+        tm.tm_hour = fields[0];
+        tm.tm_min = fields[1];
+        tm.tm_sec = fields[2];
+        tm.tm_isdst = -1;
+        let mut t = mktime(&raw mut tm);
+        if t <= now {
+            tm.tm_mday += 1;
+            tm.tm_isdst = -1;
+            t = mktime(&raw mut tm);
+        }
+        Some((t - now) as f64)
+    }
+}
+
+unsafe fn cmd_schedule_arm(sc: *mut schedule, d: f64) {
+    unsafe {
+        let mut tv = timeval {
+            tv_sec: d as time_t,
+            tv_usec: ((d - (d as time_t) as f64) * 1000000.0) as libc::suseconds_t,
+        };
+        (*sc).next = libc::time(null_mut()) + d.ceil() as time_t;
+        evtimer_add(&raw mut (*sc).timer, &raw mut tv);
+    }
+}
+
+unsafe fn cmd_schedule_free(sc: *mut schedule) {
+    unsafe {
+        log_debug!("remove schedule {}", (*sc).id);
+
+        tailq_remove(&raw mut SCHEDULES, sc);
+        evtimer_del(&raw mut (*sc).timer);
+        cmd_list_free((*sc).cmdlist);
+        free_((*sc).cmd);
+        free_((*sc).name);
+        free_(sc);
+    }
+}
+
+unsafe extern "C" fn cmd_schedule_timer(_fd: i32, _events: i16, arg: *mut c_void) {
+    unsafe {
+        let sc = arg as *mut schedule;
+        let mut fs: cmd_find_state = zeroed();
+
+        if (*sc).wp_id != -1 {
+            let wp = window_pane_find_by_id((*sc).wp_id as u32);
+            if wp.is_null() {
+                log_debug!("schedule {} target pane gone", (*sc).id);
+                cmd_schedule_free(sc);
+                return;
+            }
+            cmd_find_from_pane(&raw mut fs, wp, 0);
+        } else {
+            cmd_find_from_nothing(&raw mut fs, 0);
+        }
+        log_debug!("fire schedule {}: {}", (*sc).id, _s((*sc).cmd));
+
+        let state = cmdq_new_state(&raw mut fs, null_mut(), cmdq_state_flags::empty());
+        cmdq_append(null_mut(), cmdq_get_command((*sc).cmdlist, state));
+        cmdq_free_state(state);
+        (*sc).count += 1;
+
+        if (*sc).interval != 0.0 {
+            cmd_schedule_arm(sc, (*sc).interval);
+        } else {
+            cmd_schedule_free(sc);
+        }
+    }
+}
+
+unsafe fn cmd_schedule_find(name: *const u8) -> *mut schedule {
+    unsafe {
+        for sc in tailq_foreach::<_, ()>(&raw mut SCHEDULES).map(NonNull::as_ptr) {
+            if !(*sc).name.is_null() && libc::strcmp((*sc).name, name) == 0 {
+                return sc;
+            }
+        }
+        if let Ok(id) = strtonum(name, 0, u32::MAX as i64) {
+            for sc in tailq_foreach::<_, ()>(&raw mut SCHEDULES).map(NonNull::as_ptr) {
+                if (*sc).id as i64 == id {
+                    return sc;
+                }
+            }
+        }
+        null_mut()
+    }
+}
+
+unsafe fn cmd_schedule_command_exec(self_: *mut cmd, item: *mut cmdq_item) -> cmd_retval {
+    unsafe {
+        let args = cmd_get_args(self_);
+        let target = cmdq_get_target(item);
+        let name = args_get(args, b'n');
+        let mut d = None;
+        let mut interval = 0.0;
+
+        if args_has_(args, 'a') && args_has_(args, 'd') {
+            cmdq_error!(item, "only one of -a or -d may be given");
+            return cmd_retval::CMD_RETURN_ERROR;
+        }
+        if args_has_(args, 'a') {
+            d = cmd_schedule_at(args_get(args, b'a'));
+            if d.is_none() {
+                cmdq_error!(item, "invalid time: {}", _s(args_get(args, b'a')));
+                return cmd_retval::CMD_RETURN_ERROR;
+            }
+        }
+        if args_has_(args, 'd') {
+            d = cmd_schedule_seconds(args_get(args, b'd'));
+            if d.is_none() {
+                cmdq_error!(item, "invalid delay: {}", _s(args_get(args, b'd')));
+                return cmd_retval::CMD_RETURN_ERROR;
+            }
+        }
+        if args_has_(args, 'i') {
+            match cmd_schedule_seconds(args_get(args, b'i')) {
+                Some(i) if i > 0.0 => interval = i,
+                _ => {
+                    cmdq_error!(item, "invalid interval: {}", _s(args_get(args, b'i')));
+                    return cmd_retval::CMD_RETURN_ERROR;
+                }
+            }
+        }
+        let d = match d {
+            Some(d) => d,
+            None if interval != 0.0 => interval,
+            None => {
+                cmdq_error!(item, "one of -a, -d or -i must be given");
+                return cmd_retval::CMD_RETURN_ERROR;
+            }
+        };
+
+        if !name.is_null() && (*name == b'\0' || !cmd_schedule_find(name).is_null()) {
+            cmdq_error!(item, "invalid or duplicate name: {}", _s(name));
+            return cmd_retval::CMD_RETURN_ERROR;
+        }
+
+        let cmd = args_string(args, 0);
+        let cmdlist = match cmd_parse_from_string(cstr_to_str(cmd), None) {
+            Ok(cmdlist) => cmdlist,
+            Err(error) => {
+                cmdq_error!(item, "{}", _s(error));
+                free_(error);
+                return cmd_retval::CMD_RETURN_ERROR;
+            }
+        };
+
+        let sc = xcalloc1::<schedule>() as *mut schedule;
+        (*sc).id = SCHEDULE_NEXT_ID;
+        SCHEDULE_NEXT_ID += 1;
+        if !name.is_null() {
+            (*sc).name = xstrdup(name).as_ptr();
+        }
+        (*sc).cmd = xstrdup(cmd).as_ptr();
+        (*sc).cmdlist = cmdlist;
+        if args_has_(args, 't') && !(*target).wp.is_null() {
+            (*sc).wp_id = (*(*target).wp).id as i32;
+        } else {
+            (*sc).wp_id = -1;
+        }
+        (*sc).interval = interval;
+        tailq_insert_tail(&raw mut SCHEDULES, sc);
+
+        evtimer_set(&raw mut (*sc).timer, Some(cmd_schedule_timer), sc.cast());
+        cmd_schedule_arm(sc, d);
+
+        log_debug!("add schedule {} in {}s: {}", (*sc).id, d, _s(cmd));
+        cmd_retval::CMD_RETURN_NORMAL
+    }
+}
+
+unsafe fn cmd_list_schedules_exec(self_: *mut cmd, item: *mut cmdq_item) -> cmd_retval {
+    unsafe {
+        let args = cmd_get_args(self_);
+        let now = libc::time(null_mut());
+        let mut flag;
+
+        let mut template: *const u8 = args_get(args, b'F');
+        if template.is_null() {
+            template = c!(
+                "#{schedule_id}#{?schedule_name, (#{schedule_name}),}: next #{t:schedule_next}#{?schedule_interval, every #{schedule_interval}s,}#{?schedule_target, on #{schedule_target},}: #{schedule_command}"
+            );
+        }
+        let filter = args_get(args, b'f');
+
+        for sc in tailq_foreach::<_, ()>(&raw mut SCHEDULES).map(NonNull::as_ptr) {
+            let ft = format_create(
+                cmdq_get_client(item),
+                item,
+                FORMAT_NONE,
+                format_flags::empty(),
+            );
+            format_add!(ft, c!("schedule_id"), "{}", (*sc).id);
+            if !(*sc).name.is_null() {
+                format_add!(ft, c!("schedule_name"), "{}", _s((*sc).name));
+            }
+            format_add!(ft, c!("schedule_command"), "{}", _s((*sc).cmd));
+            format_add!(ft, c!("schedule_next"), "{}", (*sc).next);
+            format_add!(ft, c!("schedule_next_in"), "{}", ((*sc).next - now).max(0));
+            if (*sc).interval != 0.0 {
+                format_add!(ft, c!("schedule_interval"), "{}", (*sc).interval);
+            }
+            format_add!(ft, c!("schedule_count"), "{}", (*sc).count);
+            if (*sc).wp_id != -1 {
+                format_add!(ft, c!("schedule_target"), "%{}", (*sc).wp_id);
+            }
+
+            if !filter.is_null() {
+                let expanded = format_expand(ft, filter);
+                flag = format_true(expanded);
+                free_(expanded);
+            } else {
+                flag = 1;
+            }
+            if flag != 0 {
+                let line = format_expand(ft, template);
+                cmdq_print!(item, "{}", _s(line));
+                free_(line);
+            }
+
+            format_free(ft);
+        }
+
+        cmd_retval::CMD_RETURN_NORMAL
+    }
+}
+
+unsafe fn cmd_cancel_schedule_exec(self_: *mut cmd, item: *mut cmdq_item) -> cmd_retval {
+    unsafe {
+        let args = cmd_get_args(self_);
+
+        if args_has_(args, 'a') {
+            for sc in tailq_foreach::<_, ()>(&raw mut SCHEDULES).map(NonNull::as_ptr) {
+                cmd_schedule_free(sc);
+            }
+            return cmd_retval::CMD_RETURN_NORMAL;
+        }
+        if args_count(args) == 0 {
+            cmdq_error!(item, "no schedule given");
+            return cmd_retval::CMD_RETURN_ERROR;
+        }
+
+        let name = args_string(args, 0);
+        let sc = cmd_schedule_find(name);
+        if sc.is_null() {
+            cmdq_error!(item, "no schedule: {}", _s(name));
+            return cmd_retval::CMD_RETURN_ERROR;
+        }
+        cmd_schedule_free(sc);
+
+        cmd_retval::CMD_RETURN_NORMAL
+    }
+}
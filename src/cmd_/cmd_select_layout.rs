@@ -55,7 +55,49 @@ pub static CMD_PREVIOUS_LAYOUT_ENTRY: cmd_entry = cmd_entry {
     source: cmd_entry_flag::zeroed(),
 };
 
+pub static CMD_UNDO_LAYOUT_ENTRY: cmd_entry = cmd_entry {
+    name: SyncCharPtr::new(c"undo-layout"),
+    alias: SyncCharPtr::new(c"undol"),
+
+    args: args_parse::new(c"t:", 0, 0, None),
+    usage: SyncCharPtr::new(CMD_TARGET_WINDOW_USAGE),
+
+    target: cmd_entry_flag::new(b't', cmd_find_type::CMD_FIND_WINDOW, 0),
+
+    flags: cmd_flag::CMD_AFTERHOOK,
+    exec: cmd_select_layout_exec,
+    source: cmd_entry_flag::zeroed(),
+};
+
+pub static CMD_REDO_LAYOUT_ENTRY: cmd_entry = cmd_entry {
+    name: SyncCharPtr::new(c"redo-layout"),
+    alias: SyncCharPtr::new(c"redol"),
+
+    args: args_parse::new(c"t:", 0, 0, None),
+    usage: SyncCharPtr::new(CMD_TARGET_WINDOW_USAGE),
+
+    target: cmd_entry_flag::new(b't', cmd_find_type::CMD_FIND_WINDOW, 0),
+
+    flags: cmd_flag::CMD_AFTERHOOK,
+    exec: cmd_select_layout_exec,
+    source: cmd_entry_flag::zeroed(),
+};
+
 unsafe fn cmd_select_layout_exec(self_: *mut cmd, item: *mut cmdq_item) -> cmd_retval {
+    unsafe {
+        let w = (*(*cmdq_get_target(item)).wl).window;
+
+        server_unzoom_window(w);
+
+        // Layouts are built from the visible panes; stacks stay with them.
+        let saved = layout_stack_detach(w);
+        let retval = cmd_select_layout_change(self_, item);
+        layout_stack_attach(w, saved);
+        retval
+    }
+}
+
+unsafe fn cmd_select_layout_change(self_: *mut cmd, item: *mut cmdq_item) -> cmd_retval {
     unsafe {
         let args = cmd_get_args(self_);
         let target = cmdq_get_target(item);
@@ -63,8 +105,6 @@ unsafe fn cmd_select_layout_exec(self_: *mut cmd, item: *mut cmdq_item) -> cmd_r
         let w = (*wl).window;
         let wp = (*target).wp;
 
-        server_unzoom_window(w);
-
         let mut oldlayout = null_mut();
 
         'error: {
@@ -81,6 +121,18 @@ unsafe fn cmd_select_layout_exec(self_: *mut cmd, item: *mut cmdq_item) -> cmd_r
                 oldlayout = (*w).old_layout;
                 (*w).old_layout = layout_dump((*w).layout_root);
 
+                let undo = std::ptr::eq(cmd_get_entry(self_), &CMD_UNDO_LAYOUT_ENTRY);
+                let redo = std::ptr::eq(cmd_get_entry(self_), &CMD_REDO_LAYOUT_ENTRY);
+                if undo || redo {
+                    let mut cause = null_mut();
+                    if layout_history_step(w, redo, &raw mut cause) != 0 {
+                        cmdq_error!(item, "{}", _s(cause));
+                        free_(cause);
+                        break 'error;
+                    }
+                    break 'changed;
+                }
+
                 if next || previous {
                     if next {
                         layout_set_next(w);
@@ -91,7 +143,9 @@ unsafe fn cmd_select_layout_exec(self_: *mut cmd, item: *mut cmdq_item) -> cmd_r
                 }
 
                 if args_has_(args, 'E') {
-                    layout_spread_out(wp);
+                    if !(*wp).layout_cell.is_null() {
+                        layout_spread_out(wp);
+                    }
                     break 'changed;
                 }
 
@@ -117,7 +171,14 @@ unsafe fn cmd_select_layout_exec(self_: *mut cmd, item: *mut cmdq_item) -> cmd_r
 
                 if !layoutname.is_null() {
                     let mut cause = null_mut();
-                    if layout_parse(w, layoutname, &raw mut cause) == -1 {
+                    let error = if let Some(ls) = layout_saved_get(layoutname) {
+                        layout_parse_adapt(w, ls.layout.as_ptr().cast(), &raw mut cause)
+                    } else if layout_spec_is_description(layoutname) {
+                        layout_spec_apply(w, layoutname, &raw mut cause)
+                    } else {
+                        layout_parse(w, layoutname, &raw mut cause)
+                    };
+                    if error == -1 {
                         cmdq_error!(item, "{}: {}", _s(cause), _s(layoutname));
                         free_(cause);
                         break 'error;
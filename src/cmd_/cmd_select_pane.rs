@@ -19,8 +19,8 @@ pub static CMD_SELECT_PANE_ENTRY: cmd_entry = cmd_entry {
     name: SyncCharPtr::new(c"select-pane"),
     alias: SyncCharPtr::new(c"selectp"),
 
-    args: args_parse::new(c"DdegLlMmP:RT:t:UZ", 0, 0, None), /* -P and -g deprecated */
-    usage: SyncCharPtr::new(c"[-DdeLlMmRUZ] [-T title] [-t target-pane]"),
+    args: args_parse::new(c"aDdegLlMmP:RT:t:UZ", 0, 0, None), /* -P and -g deprecated */
+    usage: SyncCharPtr::new(c"[-aDdeLlMmRUZ] [-T title] [-t target-pane]"),
 
     target: cmd_entry_flag::new(b't', cmd_find_type::CMD_FIND_PANE, 0),
 
@@ -83,7 +83,6 @@ pub unsafe fn cmd_select_pane_exec(self_: *mut cmd, item: *mut cmdq_item) -> cmd
 
         let mut activewp = null_mut();
         let mut lastwp: *mut window_pane = null_mut();
-        let mut markedwp = null_mut();
 
         if std::ptr::eq(entry, &CMD_LAST_PANE_ENTRY) || args_has_(args, 'l') {
             /*
@@ -110,7 +109,8 @@ pub unsafe fn cmd_select_pane_exec(self_: *mut cmd, item: *mut cmdq_item) -> cmd
                 server_redraw_window_borders((*lastwp).window);
                 server_status_window((*lastwp).window);
             } else {
-                if window_push_zoom(w, 0, args_has(args, b'Z')) != 0 {
+                let unzoom = window_pane_visible(lastwp) == 0;
+                if unzoom && window_push_zoom(w, 0, args_has(args, b'Z')) != 0 {
                     server_redraw_window(w);
                 }
                 window_redraw_active_switch(w, lastwp);
@@ -118,41 +118,34 @@ pub unsafe fn cmd_select_pane_exec(self_: *mut cmd, item: *mut cmdq_item) -> cmd
                     cmd_find_from_winlink(current, wl, 0);
                     cmd_select_pane_redraw(w);
                 }
-                if window_pop_zoom(w) != 0 {
+                if unzoom && window_pop_zoom(w) != 0 {
                     server_redraw_window(w);
                 }
             }
             return cmd_retval::CMD_RETURN_NORMAL;
         }
 
-        if args_has_(args, 'm') || args_has_(args, 'M') {
-            if args_has_(args, 'm') && window_pane_visible(wp) == 0 {
+        if args_has_(args, 'm') || args_has_(args, 'M') || args_has_(args, 'a') {
+            if !args_has_(args, 'M') && window_pane_visible(wp) == 0 {
                 return cmd_retval::CMD_RETURN_NORMAL;
             }
-            if server_check_marked() {
-                lastwp = MARKED_PANE.wp;
-            } else {
-                lastwp = null_mut();
-            }
+            server_check_marked_panes();
+            let mut changed: Vec<cmd_find_state> = server_marked_panes().copied().collect();
 
-            if args_has_(args, 'M') || server_is_marked(s, wl, wp) {
+            if args_has_(args, 'M') || (args_has_(args, 'm') && server_is_marked(s, wl, wp)) {
                 server_clear_marked();
+            } else if args_has_(args, 'a') {
+                server_toggle_marked(s, wl, wp);
             } else {
                 server_set_marked(s, wl, wp);
             }
-            markedwp = MARKED_PANE.wp;
+            changed.extend(server_marked_panes());
 
-            if !lastwp.is_null() {
-                (*lastwp).flags |=
-                    window_pane_flags::PANE_REDRAW | window_pane_flags::PANE_STYLECHANGED;
-                server_redraw_window_borders((*lastwp).window);
-                server_status_window((*lastwp).window);
-            }
-            if !markedwp.is_null() {
-                (*markedwp).flags |=
+            for fs in changed {
+                (*fs.wp).flags |=
                     window_pane_flags::PANE_REDRAW | window_pane_flags::PANE_STYLECHANGED;
-                server_redraw_window_borders((*markedwp).window);
-                server_status_window((*markedwp).window);
+                server_redraw_window_borders(fs.w);
+                server_status_window(fs.w);
             }
             return cmd_retval::CMD_RETURN_NORMAL;
         }
@@ -172,22 +165,29 @@ pub unsafe fn cmd_select_pane_exec(self_: *mut cmd, item: *mut cmdq_item) -> cmd
             return cmd_retval::CMD_RETURN_NORMAL;
         }
 
-        if args_has_(args, 'L') {
-            window_push_zoom(w, 0, 1);
-            wp = window_pane_find_left(wp);
-            window_pop_zoom(w);
+        /* Move within a zoom of several panes without unzooming. */
+        let multizoom = (*w).flags.intersects(window_flag::ZOOMED)
+            && (*wp).flags.intersects(window_pane_flags::PANE_ZOOMED);
+        let find: Option<unsafe fn(*mut window_pane) -> *mut window_pane> = if args_has_(args, 'L')
+        {
+            Some(window_pane_find_left)
         } else if args_has_(args, 'R') {
-            window_push_zoom(w, 0, 1);
-            wp = window_pane_find_right(wp);
-            window_pop_zoom(w);
+            Some(window_pane_find_right)
         } else if args_has_(args, 'U') {
-            window_push_zoom(w, 0, 1);
-            wp = window_pane_find_up(wp);
-            window_pop_zoom(w);
+            Some(window_pane_find_up)
         } else if args_has_(args, 'D') {
-            window_push_zoom(w, 0, 1);
-            wp = window_pane_find_down(wp);
-            window_pop_zoom(w);
+            Some(window_pane_find_down)
+        } else {
+            None
+        };
+        if let Some(find) = find {
+            if !multizoom {
+                window_push_zoom(w, 0, 1);
+            }
+            wp = find(wp);
+            if !multizoom {
+                window_pop_zoom(w);
+            }
         }
         if wp.is_null() {
             return cmd_retval::CMD_RETURN_NORMAL;
@@ -228,7 +228,8 @@ pub unsafe fn cmd_select_pane_exec(self_: *mut cmd, item: *mut cmdq_item) -> cmd
         if wp == activewp {
             return cmd_retval::CMD_RETURN_NORMAL;
         }
-        if window_push_zoom(w, 0, args_has(args, b'Z')) != 0 {
+        let unzoom = window_pane_visible(wp) == 0;
+        if unzoom && window_push_zoom(w, 0, args_has(args, b'Z')) != 0 {
             server_redraw_window(w);
         }
         window_redraw_active_switch(w, wp);
@@ -242,7 +243,7 @@ pub unsafe fn cmd_select_pane_exec(self_: *mut cmd, item: *mut cmdq_item) -> cmd
         }
         cmdq_insert_hook!(s, item, current, "after-select-pane");
         cmd_select_pane_redraw(w);
-        if window_pop_zoom(w) != 0 {
+        if unzoom && window_pop_zoom(w) != 0 {
             server_redraw_window(w);
         }
 
@@ -30,6 +30,26 @@ pub static CMD_SHOW_MESSAGES_ENTRY: cmd_entry = cmd_entry {
     target: cmd_entry_flag::zeroed(),
 };
 
+pub static CMD_LIST_JOBS_ENTRY: cmd_entry = cmd_entry {
+    name: SyncCharPtr::new(c"list-jobs"),
+    alias: SyncCharPtr::new(c"lsj"),
+
+    args: args_parse::new(c"", 0, 0, None),
+    usage: SyncCharPtr::new(c""),
+
+    flags: cmd_flag::CMD_AFTERHOOK,
+    exec: cmd_list_jobs_exec,
+    source: cmd_entry_flag::zeroed(),
+    target: cmd_entry_flag::zeroed(),
+};
+
+unsafe fn cmd_list_jobs_exec(_self_: *mut cmd, item: *mut cmdq_item) -> cmd_retval {
+    unsafe {
+        job_print_summary(item, 0);
+    }
+    cmd_retval::CMD_RETURN_NORMAL
+}
+
 unsafe fn cmd_show_messages_terminals(
     self_: *mut cmd,
     item: *mut cmdq_item,
@@ -0,0 +1,143 @@
+use crate::*;
+
+pub static CMD_STACK_PANE_ENTRY: cmd_entry = cmd_entry {
+    name: SyncCharPtr::new(c"stack-pane"),
+    alias: SyncCharPtr::new(c"stackp"),
+
+    args: args_parse::new(c"ds:t:", 0, 0, None),
+    usage: SyncCharPtr::new(c"[-d] [-s src-pane] [-t dst-pane]"),
+
+    source: cmd_entry_flag::new(b's', cmd_find_type::CMD_FIND_PANE, CMD_FIND_DEFAULT_MARKED),
+    target: cmd_entry_flag::new(b't', cmd_find_type::CMD_FIND_PANE, 0),
+
+    flags: cmd_flag::CMD_AFTERHOOK,
+    exec: cmd_stack_pane_exec,
+};
+
+pub static CMD_UNSTACK_PANE_ENTRY: cmd_entry = cmd_entry {
+    name: SyncCharPtr::new(c"unstack-pane"),
+    alias: SyncCharPtr::new(c"unstackp"),
+
+    args: args_parse::new(c"bdht:v", 0, 0, None),
+    usage: SyncCharPtr::new(c"[-bdhv] [-t target-pane]"),
+
+    target: cmd_entry_flag::new(b't', cmd_find_type::CMD_FIND_PANE, 0),
+
+    flags: cmd_flag::CMD_AFTERHOOK,
+    exec: cmd_unstack_pane_exec,
+    source: cmd_entry_flag::zeroed(),
+};
+
+pub static CMD_NEXT_TAB_ENTRY: cmd_entry = cmd_entry {
+    name: SyncCharPtr::new(c"next-tab"),
+    alias: SyncCharPtr::new(c"nextt"),
+
+    args: args_parse::new(c"t:", 0, 0, None),
+    usage: SyncCharPtr::new(CMD_TARGET_PANE_USAGE),
+
+    target: cmd_entry_flag::new(b't', cmd_find_type::CMD_FIND_PANE, 0),
+
+    flags: cmd_flag::CMD_AFTERHOOK,
+    exec: cmd_next_tab_exec,
+    source: cmd_entry_flag::zeroed(),
+};
+
+pub static CMD_PREVIOUS_TAB_ENTRY: cmd_entry = cmd_entry {
+    name: SyncCharPtr::new(c"previous-tab"),
+    alias: SyncCharPtr::new(c"prevt"),
+
+    args: args_parse::new(c"t:", 0, 0, None),
+    usage: SyncCharPtr::new(CMD_TARGET_PANE_USAGE),
+
+    target: cmd_entry_flag::new(b't', cmd_find_type::CMD_FIND_PANE, 0),
+
+    flags: cmd_flag::CMD_AFTERHOOK,
+    exec: cmd_next_tab_exec,
+    source: cmd_entry_flag::zeroed(),
+};
+
+unsafe fn cmd_stack_pane_exec(self_: *mut cmd, item: *mut cmdq_item) -> cmd_retval {
+    unsafe {
+        let args = cmd_get_args(self_);
+        let source = cmdq_get_source(item);
+        let target = cmdq_get_target(item);
+        let src_wp = (*source).wp;
+        let dst_wp = (*target).wp;
+        let w = (*(*target).wl).window;
+
+        if src_wp == dst_wp {
+            cmdq_error!(item, "source and target panes must be different");
+            return cmd_retval::CMD_RETURN_ERROR;
+        }
+        if (*src_wp).window != w {
+            cmdq_error!(item, "source and target panes must be in the same window");
+            return cmd_retval::CMD_RETURN_ERROR;
+        }
+
+        server_unzoom_window(w);
+        layout_stack_add(dst_wp, src_wp, !args_has_(args, 'd'));
+        if !args_has_(args, 'd') {
+            window_set_active_pane(w, src_wp, 1);
+        }
+        server_redraw_window(w);
+
+        cmd_retval::CMD_RETURN_NORMAL
+    }
+}
+
+unsafe fn cmd_unstack_pane_exec(self_: *mut cmd, item: *mut cmdq_item) -> cmd_retval {
+    unsafe {
+        let args = cmd_get_args(self_);
+        let target = cmdq_get_target(item);
+        let wp = (*target).wp;
+        let w = (*(*target).wl).window;
+
+        if layout_stack_tabs(wp).is_empty() {
+            cmdq_error!(item, "pane is not in a stack");
+            return cmd_retval::CMD_RETURN_ERROR;
+        }
+        server_unzoom_window(w);
+
+        let type_ = if args_has_(args, 'h') {
+            layout_type::LAYOUT_LEFTRIGHT
+        } else {
+            layout_type::LAYOUT_TOPBOTTOM
+        };
+        let flags = if args_has_(args, 'b') {
+            SPAWN_BEFORE
+        } else {
+            0
+        };
+
+        let lc = layout_split_pane(wp, type_, -1, flags);
+        if lc.is_null() {
+            cmdq_error!(item, "create pane failed: pane too small");
+            return cmd_retval::CMD_RETURN_ERROR;
+        }
+        layout_stack_remove(wp);
+        layout_assign_pane(lc, wp, 0);
+
+        if !args_has_(args, 'd') {
+            window_set_active_pane(w, wp, 1);
+        }
+        server_redraw_window(w);
+        notify_window(c"window-layout-changed", w);
+
+        cmd_retval::CMD_RETURN_NORMAL
+    }
+}
+
+unsafe fn cmd_next_tab_exec(self_: *mut cmd, item: *mut cmdq_item) -> cmd_retval {
+    unsafe {
+        let wp = (*cmdq_get_target(item)).wp;
+        let forward = std::ptr::eq(cmd_get_entry(self_), &CMD_NEXT_TAB_ENTRY);
+
+        if layout_stack_tabs(wp).is_empty() {
+            cmdq_error!(item, "pane is not in a stack");
+            return cmd_retval::CMD_RETURN_ERROR;
+        }
+        layout_stack_cycle(wp, forward);
+
+        cmd_retval::CMD_RETURN_NORMAL
+    }
+}
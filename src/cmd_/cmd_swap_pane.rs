@@ -86,12 +86,7 @@ unsafe fn cmd_swap_pane_exec(self_: *mut cmd, item: *mut cmdq_item) -> cmd_retva
                 tailq_insert_after::<_, discr_entry>(&raw mut (*dst_w).panes, tmp_wp, src_wp);
             }
 
-            let src_lc = (*src_wp).layout_cell;
-            let dst_lc = (*dst_wp).layout_cell;
-            (*src_lc).wp = dst_wp;
-            (*dst_wp).layout_cell = src_lc;
-            (*dst_lc).wp = src_wp;
-            (*src_wp).layout_cell = dst_lc;
+            layout_stack_swap(src_wp, dst_wp);
 
             (*src_wp).window = dst_w;
             options_set_parent((*src_wp).options, (*dst_w).options);
@@ -0,0 +1,120 @@
+use crate::*;
+
+pub static CMD_LIST_TRASH_ENTRY: cmd_entry = cmd_entry {
+    name: SyncCharPtr::new(c"list-trash"),
+    alias: SyncCharPtr::new(c"lstrash"),
+
+    args: args_parse::new(c"F:f:", 0, 0, None),
+    usage: SyncCharPtr::new(c"[-F format] [-f filter]"),
+
+    target: cmd_entry_flag::zeroed(),
+
+    flags: cmd_flag::CMD_AFTERHOOK,
+    exec: cmd_list_trash_exec,
+    source: cmd_entry_flag::zeroed(),
+};
+
+pub static CMD_RESTORE_TRASH_ENTRY: cmd_entry = cmd_entry {
+    name: SyncCharPtr::new(c"restore-trash"),
+    alias: SyncCharPtr::new(c"restoret"),
+
+    args: args_parse::new(c"dNt:", 0, 1, None),
+    usage: SyncCharPtr::new(c"[-dN] [-t target-session] [id]"),
+
+    target: cmd_entry_flag::new(b't', cmd_find_type::CMD_FIND_SESSION, CMD_FIND_CANFAIL),
+
+    flags: cmd_flag::CMD_AFTERHOOK,
+    exec: cmd_restore_trash_exec,
+    source: cmd_entry_flag::zeroed(),
+};
+
+unsafe fn cmd_list_trash_exec(self_: *mut cmd, item: *mut cmdq_item) -> cmd_retval {
+    unsafe {
+        let args = cmd_get_args(self_);
+
+        let mut template: *const u8 = args_get(args, b'F');
+        if template.is_null() {
+            template = c!(
+                "#{trash_id}: #{trash_type} #{trash_name}#{?trash_session, from #{trash_session},} (#{t:trash_time})"
+            );
+        }
+        let filter = args_get(args, b'f');
+
+        let mut te = trash_walk(null_mut());
+        while !te.is_null() {
+            let ft = format_create(
+                cmdq_get_client(item),
+                item,
+                FORMAT_NONE,
+                format_flags::empty(),
+            );
+            format_defaults_trash(ft, te);
+
+            let flag = if !filter.is_null() {
+                let expanded = format_expand(ft, filter);
+                let flag = format_true(expanded);
+                free_(expanded);
+                flag
+            } else {
+                1
+            };
+            if flag != 0 {
+                let line = format_expand(ft, template);
+                cmdq_print!(item, "{}", _s(line));
+                free_(line);
+            }
+
+            format_free(ft);
+            te = trash_walk(te);
+        }
+
+        cmd_retval::CMD_RETURN_NORMAL
+    }
+}
+
+unsafe fn cmd_restore_trash_exec(self_: *mut cmd, item: *mut cmdq_item) -> cmd_retval {
+    unsafe {
+        let args = cmd_get_args(self_);
+        let mut cause = null_mut();
+
+        let te = if args_count(args) == 0 {
+            trash_walk(null_mut())
+        } else {
+            match strtonum(args_string(args, 0), 0, u32::MAX) {
+                Ok(id) => trash_find(id),
+                Err(_) => null_mut(),
+            }
+        };
+        if te.is_null() {
+            if args_count(args) == 0 {
+                cmdq_error!(item, "trash is empty");
+            } else {
+                cmdq_error!(item, "no trash entry: {}", _s(args_string(args, 0)));
+            }
+            return cmd_retval::CMD_RETURN_ERROR;
+        }
+
+        let mut s = null_mut();
+        if args_has_(args, 't') {
+            s = (*cmdq_get_target(item)).s;
+            if s.is_null() {
+                cmdq_error!(item, "no target session");
+                return cmd_retval::CMD_RETURN_ERROR;
+            }
+        }
+
+        let wl = trash_restore(
+            te,
+            s,
+            args_has_(args, 'N'),
+            !args_has_(args, 'd'),
+            &raw mut cause,
+        );
+        if wl.is_null() {
+            cmdq_error!(item, "restore failed: {}", _s(cause));
+            free_(cause);
+            return cmd_retval::CMD_RETURN_ERROR;
+        }
+        cmd_retval::CMD_RETURN_NORMAL
+    }
+}
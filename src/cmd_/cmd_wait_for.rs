@@ -19,13 +19,14 @@ use crate::compat::{
     queue::{tailq_empty, tailq_first, tailq_foreach, tailq_init, tailq_insert_tail, tailq_remove},
     tree::{rb_find, rb_foreach, rb_initializer, rb_insert, rb_remove},
 };
+use crate::libc::strtod;
 
 pub static CMD_WAIT_FOR_ENTRY: cmd_entry = cmd_entry {
     name: SyncCharPtr::new(c"wait-for"),
     alias: SyncCharPtr::new(c"wait"),
 
-    args: args_parse::new(c"LSU", 1, 1, None),
-    usage: SyncCharPtr::new(c"[-L|-S|-U] channel"),
+    args: args_parse::new(c"LSUn:p:PT:", 1, 1, None),
+    usage: SyncCharPtr::new(c"[-LPSU] [-n count] [-p payload] [-T timeout] channel"),
 
     flags: cmd_flag::empty(),
     exec: cmd_wait_for_exec,
@@ -33,10 +34,29 @@ pub static CMD_WAIT_FOR_ENTRY: cmd_entry = cmd_entry {
     target: cmd_entry_flag::zeroed(),
 };
 
+pub static CMD_LIST_CHANNELS_ENTRY: cmd_entry = cmd_entry {
+    name: SyncCharPtr::new(c"list-channels"),
+    alias: SyncCharPtr::new(c"lsch"),
+
+    args: args_parse::new(c"F:f:", 0, 0, None),
+    usage: SyncCharPtr::new(c"[-F format] [-f filter]"),
+
+    flags: cmd_flag::CMD_AFTERHOOK,
+    exec: cmd_list_channels_exec,
+    source: cmd_entry_flag::zeroed(),
+    target: cmd_entry_flag::zeroed(),
+};
+
 crate::compat::impl_tailq_entry!(wait_item, entry, tailq_entry<wait_item>);
 #[repr(C)]
 pub struct wait_item {
     item: *mut cmdq_item,
+    client: *mut u8,
+    print: i32,
+
+    wc: *mut wait_channel,
+    timer: event,
+
     // #[entry]
     entry: tailq_entry<wait_item>,
 }
@@ -44,11 +64,14 @@ pub struct wait_item {
 #[repr(C)]
 pub struct wait_channel {
     pub name: *mut u8,
-    pub locked: i32,
+    pub locked: u32,
+    pub limit: u32,
     pub woken: i32,
+    pub payload: *mut u8,
 
     pub waiters: tailq_head<wait_item>,
     pub lockers: tailq_head<wait_item>,
+    pub holders: tailq_head<wait_item>,
 
     pub entry: rb_entry<wait_channel>,
 }
@@ -75,10 +98,13 @@ pub unsafe fn cmd_wait_for_add(name: *const u8) -> *mut wait_channel {
         (*wc).name = xstrdup(name).as_ptr();
 
         (*wc).locked = 0;
+        (*wc).limit = 1;
         (*wc).woken = 0;
+        (*wc).payload = null_mut();
 
         tailq_init(&raw mut (*wc).waiters);
         tailq_init(&raw mut (*wc).lockers);
+        tailq_init(&raw mut (*wc).holders);
 
         rb_insert(&raw mut WAIT_CHANNELS, wc);
 
@@ -87,12 +113,14 @@ pub unsafe fn cmd_wait_for_add(name: *const u8) -> *mut wait_channel {
     wc
 }
 
+/// Remove a channel once it is neither locked nor waited for. Any signal not
+/// yet waited for goes with it.
 pub unsafe fn cmd_wait_for_remove(wc: *mut wait_channel) {
     unsafe {
         if (*wc).locked != 0 {
             return;
         }
-        if !tailq_empty(&raw mut (*wc).waiters) || (*wc).woken == 0 {
+        if !tailq_empty(&raw mut (*wc).waiters) {
             return;
         }
 
@@ -100,32 +128,159 @@ pub unsafe fn cmd_wait_for_remove(wc: *mut wait_channel) {
 
         rb_remove(&raw mut WAIT_CHANNELS, wc);
 
+        free_((*wc).payload);
         free_((*wc).name);
         free_(wc);
     }
 }
 
+/// Queue a command on a channel, failing it if the timeout expires first.
+unsafe fn cmd_wait_for_queue(
+    wc: *mut wait_channel,
+    head: *mut tailq_head<wait_item>,
+    item: *mut cmdq_item,
+    print: i32,
+    tv: Option<timeval>,
+) {
+    unsafe {
+        let wi: *mut wait_item = xcalloc1();
+        (*wi).item = item;
+        (*wi).client = xstrdup((*cmdq_get_client(item)).name).as_ptr();
+        (*wi).print = print;
+        (*wi).wc = wc;
+        tailq_insert_tail(head, wi);
+
+        evtimer_set(&raw mut (*wi).timer, Some(cmd_wait_for_timer), wi.cast());
+        if let Some(mut tv) = tv {
+            evtimer_add(&raw mut (*wi).timer, &raw mut tv);
+        }
+    }
+}
+
+unsafe fn cmd_wait_for_free(head: *mut tailq_head<wait_item>, wi: *mut wait_item) {
+    unsafe {
+        tailq_remove(head, wi);
+        evtimer_del(&raw mut (*wi).timer);
+        free_((*wi).client);
+        free_(wi);
+    }
+}
+
+unsafe extern "C" fn cmd_wait_for_timer(_fd: i32, _events: i16, arg: *mut c_void) {
+    unsafe {
+        let wi = arg as *mut wait_item;
+        let wc = (*wi).wc;
+        let item = (*wi).item;
+
+        let mut head = &raw mut (*wc).waiters;
+        for wj in tailq_foreach::<_, ()>(&raw mut (*wc).lockers).map(NonNull::as_ptr) {
+            if wj == wi {
+                head = &raw mut (*wc).lockers;
+            }
+        }
+        log_debug!("wait channel {} timed out", _s((*wc).name));
+
+        if head == &raw mut (*wc).lockers {
+            cmdq_error!(item, "timed out waiting for lock on {}", _s((*wc).name));
+        } else {
+            cmdq_error!(item, "timed out waiting for {}", _s((*wc).name));
+        }
+        cmdq_remove_group(item);
+        cmdq_continue(item);
+
+        cmd_wait_for_free(head, wi);
+        cmd_wait_for_remove(wc);
+    }
+}
+
+/// Pass the channel payload to a woken command.
+unsafe fn cmd_wait_for_payload(item: *mut cmdq_item, wc: *mut wait_channel, print: i32) {
+    unsafe {
+        if (*wc).payload.is_null() {
+            return;
+        }
+        cmdq_add_format!(
+            cmdq_get_state(item),
+            c!("wait_payload"),
+            "{}",
+            _s((*wc).payload)
+        );
+        if print != 0 {
+            cmdq_print!(item, "{}", _s((*wc).payload));
+        }
+    }
+}
+
+/// Hand the lock to queued lockers while there are free places.
+unsafe fn cmd_wait_for_grant(wc: *mut wait_channel) {
+    unsafe {
+        while (*wc).locked < (*wc).limit {
+            let wi = tailq_first(&raw mut (*wc).lockers);
+            if wi.is_null() {
+                break;
+            }
+            log_debug!(
+                "wait channel {} locked by {}",
+                _s((*wc).name),
+                _s((*wi).client)
+            );
+
+            tailq_remove(&raw mut (*wc).lockers, wi);
+            evtimer_del(&raw mut (*wi).timer);
+            cmdq_continue((*wi).item);
+            (*wi).item = null_mut();
+            tailq_insert_tail(&raw mut (*wc).holders, wi);
+            (*wc).locked += 1;
+        }
+    }
+}
+
 pub unsafe fn cmd_wait_for_exec(self_: *mut cmd, item: *mut cmdq_item) -> cmd_retval {
     unsafe {
         let args = cmd_get_args(self_);
         let name = args_string(args, 0);
+        let mut tv = None;
         // struct wait_channel *wc, find;
 
+        let timeout = args_get(args, b'T');
+        if !timeout.is_null() {
+            let mut end: *mut u8 = null_mut();
+            let d = strtod(timeout, &raw mut end);
+            if *end != b'\0' || d <= 0.0 {
+                cmdq_error!(item, "invalid timeout: {}", _s(timeout));
+                return cmd_retval::CMD_RETURN_ERROR;
+            }
+            tv = Some(timeval {
+                tv_sec: d as time_t,
+                tv_usec: ((d - (d as time_t) as f64) * 1000000.0) as libc::suseconds_t,
+            });
+        }
+
         let mut find: wait_channel = zeroed();
         find.name = name as *mut u8; // TODO casting away const
         let wc = rb_find(&raw mut WAIT_CHANNELS, &raw mut find);
 
         if args_has_(args, 'S') {
-            return cmd_wait_for_signal(item, name, wc);
+            return cmd_wait_for_signal(item, name, wc, args_get(args, b'p'));
         }
         if args_has_(args, 'L') {
-            return cmd_wait_for_lock(item, name, wc);
+            let mut limit = 0;
+            if args_has_(args, 'n') {
+                let mut cause = null_mut();
+                limit = args_strtonum(args, b'n', 1, i32::MAX as i64, &raw mut cause) as u32;
+                if !cause.is_null() {
+                    cmdq_error!(item, "count {}", _s(cause));
+                    free_(cause);
+                    return cmd_retval::CMD_RETURN_ERROR;
+                }
+            }
+            return cmd_wait_for_lock(item, name, wc, limit, tv);
         }
         if args_has_(args, 'U') {
             return cmd_wait_for_unlock(item, name, wc);
         }
 
-        cmd_wait_for_wait(item, name, wc)
+        cmd_wait_for_wait(item, name, wc, args_has(args, b'P'), tv)
     }
 }
 
@@ -133,12 +288,18 @@ pub unsafe fn cmd_wait_for_signal(
     _item: *mut cmdq_item,
     name: *const u8,
     mut wc: *mut wait_channel,
+    payload: *const u8,
 ) -> cmd_retval {
     unsafe {
         if wc.is_null() {
             wc = cmd_wait_for_add(name);
         }
 
+        if !payload.is_null() {
+            free_((*wc).payload);
+            (*wc).payload = xstrdup(payload).as_ptr();
+        }
+
         if tailq_empty(&raw mut (*wc).waiters) && (*wc).woken == 0 {
             log_debug!("signal wait channel {}, no waiters", _s((*wc).name));
             (*wc).woken = 1;
@@ -146,11 +307,15 @@ pub unsafe fn cmd_wait_for_signal(
         }
         log_debug!("signal wait channel {}, with waiters", _s((*wc).name));
 
-        for wi in tailq_foreach::<_, ()>(&raw mut (*wc).waiters).map(NonNull::as_ptr) {
-            cmdq_continue((*wi).item);
+        if !tailq_empty(&raw mut (*wc).waiters) {
+            for wi in tailq_foreach::<_, ()>(&raw mut (*wc).waiters).map(NonNull::as_ptr) {
+                cmd_wait_for_payload((*wi).item, wc, (*wi).print);
+                cmdq_continue((*wi).item);
 
-            tailq_remove::<_, ()>(&raw mut (*wc).waiters, wi);
-            free_(wi);
+                cmd_wait_for_free(&raw mut (*wc).waiters, wi);
+            }
+            free_((*wc).payload);
+            (*wc).payload = null_mut();
         }
 
         cmd_wait_for_remove(wc);
@@ -163,6 +328,8 @@ pub unsafe fn cmd_wait_for_wait(
     item: *mut cmdq_item,
     name: *const u8,
     mut wc: *mut wait_channel,
+    print: i32,
+    tv: Option<timeval>,
 ) -> cmd_retval {
     unsafe {
         let c = cmdq_get_client(item);
@@ -178,14 +345,13 @@ pub unsafe fn cmd_wait_for_wait(
 
         if (*wc).woken != 0 {
             log_debug!("wait channel {} already woken ({:p})", _s((*wc).name), c);
+            cmd_wait_for_payload(item, wc, print);
             cmd_wait_for_remove(wc);
             return cmd_retval::CMD_RETURN_NORMAL;
         }
         log_debug!("wait channel {} not woken ({:p})", _s((*wc).name), c);
 
-        let wi: *mut wait_item = xcalloc1();
-        (*wi).item = item;
-        tailq_insert_tail(&raw mut (*wc).waiters, wi);
+        cmd_wait_for_queue(wc, &raw mut (*wc).waiters, item, print, tv);
     }
     cmd_retval::CMD_RETURN_WAIT
 }
@@ -194,9 +360,12 @@ pub unsafe fn cmd_wait_for_lock(
     item: *mut cmdq_item,
     name: *const u8,
     mut wc: *mut wait_channel,
+    limit: u32,
+    tv: Option<timeval>,
 ) -> cmd_retval {
     unsafe {
-        if cmdq_get_client(item).is_null() {
+        let c = cmdq_get_client(item);
+        if c.is_null() {
             cmdq_error!(item, "not able to lock");
             return cmd_retval::CMD_RETURN_ERROR;
         }
@@ -204,14 +373,22 @@ pub unsafe fn cmd_wait_for_lock(
         if wc.is_null() {
             wc = cmd_wait_for_add(name);
         }
+        if limit != 0 {
+            (*wc).limit = limit;
+            cmd_wait_for_grant(wc);
+        }
 
-        if (*wc).locked != 0 {
-            let wi = xcalloc1::<wait_item>();
-            wi.item = item;
-            tailq_insert_tail(&raw mut (*wc).lockers, wi);
+        if (*wc).locked >= (*wc).limit || !tailq_empty(&raw mut (*wc).lockers) {
+            cmd_wait_for_queue(wc, &raw mut (*wc).lockers, item, 0, tv);
             return cmd_retval::CMD_RETURN_WAIT;
         }
-        (*wc).locked = 1;
+
+        let wi: *mut wait_item = xcalloc1();
+        (*wi).client = xstrdup((*c).name).as_ptr();
+        (*wi).wc = wc;
+        evtimer_set(&raw mut (*wi).timer, Some(cmd_wait_for_timer), wi.cast());
+        tailq_insert_tail(&raw mut (*wc).holders, wi);
+        (*wc).locked += 1;
     }
     cmd_retval::CMD_RETURN_NORMAL
 }
@@ -227,15 +404,22 @@ pub unsafe fn cmd_wait_for_unlock(
             return cmd_retval::CMD_RETURN_ERROR;
         }
 
-        let wi = tailq_first(&raw mut (*wc).lockers);
-        if !wi.is_null() {
-            cmdq_continue((*wi).item);
-            tailq_remove(&raw mut (*wc).lockers, wi);
-            free_(wi);
-        } else {
-            (*wc).locked = 0;
-            cmd_wait_for_remove(wc);
+        // Release the place held by this client if it has one.
+        let c = cmdq_get_client(item);
+        let mut wi = tailq_first(&raw mut (*wc).holders);
+        if !c.is_null() {
+            for wj in tailq_foreach::<_, ()>(&raw mut (*wc).holders).map(NonNull::as_ptr) {
+                if libc::strcmp((*wj).client, (*c).name) == 0 {
+                    wi = wj;
+                    break;
+                }
+            }
         }
+        cmd_wait_for_free(&raw mut (*wc).holders, wi);
+        (*wc).locked -= 1;
+
+        cmd_wait_for_grant(wc);
+        cmd_wait_for_remove(wc);
     }
     cmd_retval::CMD_RETURN_NORMAL
 }
@@ -245,17 +429,385 @@ pub unsafe fn cmd_wait_for_flush() {
         for wc in rb_foreach(&raw mut WAIT_CHANNELS).map(NonNull::as_ptr) {
             for wi in tailq_foreach(&raw mut (*wc).waiters).map(NonNull::as_ptr) {
                 cmdq_continue((*wi).item);
-                tailq_remove(&raw mut (*wc).waiters, wi);
-                free_(wi);
+                cmd_wait_for_free(&raw mut (*wc).waiters, wi);
             }
-            (*wc).woken = 1;
+            (*wc).woken = 0;
             for wi in tailq_foreach(&raw mut (*wc).lockers).map(NonNull::as_ptr) {
                 cmdq_continue((*wi).item);
-                tailq_remove(&raw mut (*wc).lockers, wi);
-                free_(wi);
+                cmd_wait_for_free(&raw mut (*wc).lockers, wi);
+            }
+            for wi in tailq_foreach(&raw mut (*wc).holders).map(NonNull::as_ptr) {
+                cmd_wait_for_free(&raw mut (*wc).holders, wi);
             }
             (*wc).locked = 0;
             cmd_wait_for_remove(wc);
         }
     }
 }
+
+/// Join the client names of a list of waiters or holders.
+unsafe fn cmd_list_channels_clients(head: *mut tailq_head<wait_item>) -> (u32, String) {
+    unsafe {
+        let mut n = 0;
+        let mut s = String::new();
+        for wi in tailq_foreach::<_, ()>(head).map(NonNull::as_ptr) {
+            if n != 0 {
+                s.push(',');
+            }
+            s.push_str(_s((*wi).client).to_string().as_str());
+            n += 1;
+        }
+        (n, s)
+    }
+}
+
+unsafe fn cmd_list_channels_exec(self_: *mut cmd, item: *mut cmdq_item) -> cmd_retval {
+    unsafe {
+        let args = cmd_get_args(self_);
+        let mut flag;
+
+        let mut template: *const u8 = args_get(args, b'F');
+        if template.is_null() {
+            template = c!(
+                "#{channel_name}: #{channel_holders}/#{channel_limit} held#{?channel_holder_clients, by #{channel_holder_clients},}, #{channel_waiters} waiting, #{channel_lockers} locking#{?channel_woken, (signalled),}"
+            );
+        }
+        let filter = args_get(args, b'f');
+
+        for wc in rb_foreach(&raw mut WAIT_CHANNELS).map(NonNull::as_ptr) {
+            let ft = format_create(
+                cmdq_get_client(item),
+                item,
+                FORMAT_NONE,
+                format_flags::empty(),
+            );
+            format_add!(ft, c!("channel_name"), "{}", _s((*wc).name));
+            format_add!(ft, c!("channel_limit"), "{}", (*wc).limit);
+            format_add!(ft, c!("channel_woken"), "{}", (*wc).woken);
+            if !(*wc).payload.is_null() {
+                format_add!(ft, c!("channel_payload"), "{}", _s((*wc).payload));
+            }
+
+            let (n, clients) = cmd_list_channels_clients(&raw mut (*wc).holders);
+            format_add!(ft, c!("channel_holders"), "{}", n);
+            format_add!(ft, c!("channel_holder_clients"), "{}", clients);
+            let (n, clients) = cmd_list_channels_clients(&raw mut (*wc).waiters);
+            format_add!(ft, c!("channel_waiters"), "{}", n);
+            format_add!(ft, c!("channel_waiter_clients"), "{}", clients);
+            let (n, clients) = cmd_list_channels_clients(&raw mut (*wc).lockers);
+            format_add!(ft, c!("channel_lockers"), "{}", n);
+            format_add!(ft, c!("channel_locker_clients"), "{}", clients);
+
+            if !filter.is_null() {
+                let expanded = format_expand(ft, filter);
+                flag = format_true(expanded);
+                free_(expanded);
+            } else {
+                flag = 1;
+            }
+            if flag != 0 {
+                let line = format_expand(ft, template);
+                cmdq_print!(item, "{}", _s(line));
+                free_(line);
+            }
+
+            format_free(ft);
+        }
+
+        cmd_retval::CMD_RETURN_NORMAL
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::cmd_::cmd_queue::{CMDQ_WAITING, cmdq_remove};
+    use std::sync::{Mutex, Once};
+
+    // The channels are global so only one test may use them at a time.
+    static CHANNELS: Mutex<()> = Mutex::new(());
+
+    struct test_queue {
+        queue: *mut cmdq_list,
+        cmdlist: *mut cmd_list,
+        clients: Vec<*mut client>,
+    }
+
+    impl test_queue {
+        unsafe fn new() -> Self {
+            static INIT: Once = Once::new();
+            INIT.call_once(|| unsafe {
+                event_init();
+            });
+            unsafe {
+                let input = cmd_parse_input {
+                    flags: cmd_parse_input_flags::CMD_PARSE_NOALIAS.into(),
+                    ..zeroed()
+                };
+                let cmdlist = match cmd_parse_from_string("wait-for test", Some(&input)) {
+                    Ok(cmdlist) => cmdlist,
+                    Err(cause) => panic!("{}", _s(cause)),
+                };
+                Self {
+                    queue: cmdq_new().as_ptr(),
+                    cmdlist,
+                    clients: Vec::new(),
+                }
+            }
+        }
+
+        /// Make a waiting command item for a new client with a name.
+        unsafe fn item(&mut self, name: &str) -> *mut cmdq_item {
+            unsafe {
+                let c: *mut client = xcalloc1();
+                (*c).name = format_nul!("{}", name);
+                self.clients.push(c);
+
+                let item = cmdq_get_command(self.cmdlist, null_mut());
+                (*item).queue = self.queue;
+                (*item).client = c;
+                (*item).flags |= CMDQ_WAITING;
+                tailq_insert_tail(&raw mut (*self.queue).list, item);
+                item
+            }
+        }
+    }
+
+    impl Drop for test_queue {
+        fn drop(&mut self) {
+            unsafe {
+                while !tailq_empty(&raw mut (*self.queue).list) {
+                    let item = tailq_first(&raw mut (*self.queue).list);
+                    (*item).client = null_mut();
+                    cmdq_remove(item);
+                }
+                cmdq_free(self.queue);
+                cmd_list_free(self.cmdlist);
+                for c in self.clients.drain(..) {
+                    free_((*c).name as *mut u8);
+                    free_(c);
+                }
+            }
+        }
+    }
+
+    unsafe fn channel(name: &str) -> *mut wait_channel {
+        unsafe {
+            let name = format_nul!("{}", name);
+            let mut find: wait_channel = zeroed();
+            find.name = name;
+            let wc = rb_find(&raw mut WAIT_CHANNELS, &raw mut find);
+            free_(name);
+            wc
+        }
+    }
+
+    unsafe fn waiting(item: *mut cmdq_item) -> bool {
+        unsafe { (*item).flags & CMDQ_WAITING != 0 }
+    }
+
+    unsafe fn holders(wc: *mut wait_channel) -> String {
+        unsafe { cmd_list_channels_clients(&raw mut (*wc).holders).1 }
+    }
+
+    #[test]
+    fn test_wait_signal() {
+        let _lock = CHANNELS.lock().unwrap();
+        unsafe {
+            let mut q = test_queue::new();
+
+            // A signal with nobody waiting wakes the next wait at once.
+            let item = q.item("a");
+            let r = cmd_wait_for_signal(item, c!("signal"), channel("signal"), null());
+            assert!(matches!(r, cmd_retval::CMD_RETURN_NORMAL));
+            assert_eq!((*channel("signal")).woken, 1);
+            let r = cmd_wait_for_wait(item, c!("signal"), channel("signal"), 0, None);
+            assert!(matches!(r, cmd_retval::CMD_RETURN_NORMAL));
+            assert!(channel("signal").is_null());
+
+            // Otherwise every waiter waits until the signal.
+            let (a, b) = (q.item("a"), q.item("b"));
+            for item in [a, b] {
+                let r = cmd_wait_for_wait(item, c!("signal"), channel("signal"), 0, None);
+                assert!(matches!(r, cmd_retval::CMD_RETURN_WAIT));
+            }
+            assert_eq!((*channel("signal")).woken, 0);
+            cmd_wait_for_signal(q.item("c"), c!("signal"), channel("signal"), null());
+            assert!(!waiting(a) && !waiting(b));
+            assert!(channel("signal").is_null());
+
+            // As before, a second signal with nobody waiting cancels the first.
+            for _ in 0..2 {
+                cmd_wait_for_signal(item, c!("signal"), channel("signal"), null());
+            }
+            assert!(channel("signal").is_null());
+
+            // And a signal on a locked channel lasts until it is unlocked.
+            cmd_wait_for_lock(a, c!("signal"), channel("signal"), 0, None);
+            cmd_wait_for_signal(item, c!("signal"), channel("signal"), null());
+            for _ in 0..2 {
+                let r = cmd_wait_for_wait(b, c!("signal"), channel("signal"), 0, None);
+                assert!(matches!(r, cmd_retval::CMD_RETURN_NORMAL));
+            }
+            cmd_wait_for_unlock(a, c!("signal"), channel("signal"));
+            assert!(channel("signal").is_null());
+        }
+    }
+
+    #[test]
+    fn test_wait_lock() {
+        let _lock = CHANNELS.lock().unwrap();
+        unsafe {
+            let mut q = test_queue::new();
+            let (a, b) = (q.item("a"), q.item("b"));
+
+            let r = cmd_wait_for_lock(a, c!("lock"), channel("lock"), 0, None);
+            assert!(matches!(r, cmd_retval::CMD_RETURN_NORMAL));
+            let r = cmd_wait_for_lock(b, c!("lock"), channel("lock"), 0, None);
+            assert!(matches!(r, cmd_retval::CMD_RETURN_WAIT));
+            let wc = channel("lock");
+            assert_eq!(((*wc).locked, (*wc).limit), (1, 1));
+            assert_eq!(holders(wc), "a");
+
+            // Unlocking hands the lock to the next locker.
+            let r = cmd_wait_for_unlock(a, c!("lock"), wc);
+            assert!(matches!(r, cmd_retval::CMD_RETURN_NORMAL));
+            assert!(!waiting(b));
+            assert_eq!((*wc).locked, 1);
+            assert_eq!(holders(wc), "b");
+
+            cmd_wait_for_unlock(b, c!("lock"), wc);
+            assert!(channel("lock").is_null());
+
+            (*a).client = null_mut();
+            let r = cmd_wait_for_unlock(a, c!("lock"), channel("lock"));
+            assert!(matches!(r, cmd_retval::CMD_RETURN_ERROR));
+        }
+    }
+
+    #[test]
+    fn test_wait_limit() {
+        let _lock = CHANNELS.lock().unwrap();
+        unsafe {
+            let mut q = test_queue::new();
+            let (a, b, c) = (q.item("a"), q.item("b"), q.item("c"));
+
+            for item in [a, b] {
+                let r = cmd_wait_for_lock(item, c!("limit"), channel("limit"), 2, None);
+                assert!(matches!(r, cmd_retval::CMD_RETURN_NORMAL));
+            }
+            let r = cmd_wait_for_lock(c, c!("limit"), channel("limit"), 2, None);
+            assert!(matches!(r, cmd_retval::CMD_RETURN_WAIT));
+            let wc = channel("limit");
+            assert_eq!(((*wc).locked, (*wc).limit), (2, 2));
+            assert_eq!(holders(wc), "a,b");
+
+            // Each client releases its own place, not the first one.
+            cmd_wait_for_unlock(b, c!("limit"), wc);
+            assert!(!waiting(c));
+            assert_eq!((*wc).locked, 2);
+            assert_eq!(holders(wc), "a,c");
+
+            // Raising the limit grants queued lockers straight away.
+            let d = q.item("d");
+            let r = cmd_wait_for_lock(d, c!("limit"), wc, 0, None);
+            assert!(matches!(r, cmd_retval::CMD_RETURN_WAIT));
+            let e = q.item("e");
+            let r = cmd_wait_for_lock(e, c!("limit"), wc, 3, None);
+            assert!(matches!(r, cmd_retval::CMD_RETURN_WAIT));
+            assert!(!waiting(d) && waiting(e));
+            assert_eq!(holders(wc), "a,c,d");
+
+            for item in [a, c, d] {
+                cmd_wait_for_unlock(item, c!("limit"), wc);
+            }
+            assert!(!waiting(e));
+            assert_eq!(holders(wc), "e");
+            cmd_wait_for_unlock(e, c!("limit"), wc);
+            assert!(channel("limit").is_null());
+        }
+    }
+
+    #[test]
+    fn test_wait_timeout() {
+        let _lock = CHANNELS.lock().unwrap();
+        unsafe {
+            let mut q = test_queue::new();
+            let tv = Some(timeval {
+                tv_sec: 60,
+                tv_usec: 0,
+            });
+
+            let a = q.item("a");
+            let r = cmd_wait_for_wait(a, c!("timeout"), channel("timeout"), 0, tv);
+            assert!(matches!(r, cmd_retval::CMD_RETURN_WAIT));
+            let wi = tailq_first(&raw mut (*channel("timeout")).waiters);
+            assert!(evtimer_pending(&raw const (*wi).timer, null_mut()) != 0);
+
+            // Expiry fails the waiter and forgets the channel.
+            (*a).client = null_mut();
+            cmd_wait_for_timer(-1, 0, wi.cast());
+            assert!(!waiting(a));
+            assert!(channel("timeout").is_null());
+
+            // A locker that times out leaves the holder alone.
+            let (b, c) = (q.item("b"), q.item("c"));
+            cmd_wait_for_lock(b, c!("timeout"), channel("timeout"), 0, tv);
+            let r = cmd_wait_for_lock(c, c!("timeout"), channel("timeout"), 0, tv);
+            assert!(matches!(r, cmd_retval::CMD_RETURN_WAIT));
+            let wc = channel("timeout");
+            let wi = tailq_first(&raw mut (*wc).lockers);
+            (*c).client = null_mut();
+            cmd_wait_for_timer(-1, 0, wi.cast());
+            assert!(!waiting(c));
+            assert!(tailq_empty(&raw mut (*wc).lockers));
+            assert_eq!((*wc).locked, 1);
+            assert_eq!(holders(wc), "b");
+
+            cmd_wait_for_unlock(b, c!("timeout"), wc);
+            assert!(channel("timeout").is_null());
+        }
+    }
+
+    #[test]
+    fn test_wait_payload() {
+        let _lock = CHANNELS.lock().unwrap();
+        unsafe {
+            let mut q = test_queue::new();
+            let payload = |item: *mut cmdq_item| {
+                let formats = (*cmdq_get_state(item)).formats;
+                if formats.is_null() {
+                    return None;
+                }
+                rb_foreach(&raw mut (*formats).tree)
+                    .map(NonNull::as_ptr)
+                    .find(|&fe| _s((*fe).key).to_string() == "wait_payload")
+                    .map(|fe| _s((*fe).value).to_string())
+            };
+
+            // A payload sent before the wait is kept until then.
+            let (a, b) = (q.item("a"), q.item("b"));
+            cmd_wait_for_signal(a, c!("payload"), channel("payload"), c!("one"));
+            assert_eq!(_s((*channel("payload")).payload).to_string(), "one");
+            cmd_wait_for_wait(b, c!("payload"), channel("payload"), 0, None);
+            assert_eq!(payload(b).as_deref(), Some("one"));
+            assert!(channel("payload").is_null());
+
+            // Every waiter gets the payload and it is not kept afterwards.
+            let (c, d) = (q.item("c"), q.item("d"));
+            for item in [c, d] {
+                cmd_wait_for_wait(item, c!("payload"), channel("payload"), 0, None);
+            }
+            cmd_wait_for_signal(a, c!("payload"), channel("payload"), c!("two"));
+            assert_eq!(payload(c).as_deref(), Some("two"));
+            assert_eq!(payload(d).as_deref(), Some("two"));
+            assert!(channel("payload").is_null());
+
+            // A plain signal passes nothing.
+            let e = q.item("e");
+            cmd_wait_for_signal(a, c!("payload"), channel("payload"), null());
+            cmd_wait_for_wait(e, c!("payload"), channel("payload"), 0, None);
+            assert_eq!(payload(e), None);
+        }
+    }
+}
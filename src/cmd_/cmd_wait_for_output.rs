@@ -0,0 +1,232 @@
+use crate::*;
+
+use crate::compat::queue::{tailq_empty, tailq_foreach, tailq_insert_tail, tailq_remove};
+use crate::libc::{
+    REG_EXTENDED, REG_ICASE, regcomp, regex_t, regexec, regfree, regmatch_t, strtod,
+};
+
+pub static CMD_WAIT_FOR_OUTPUT_ENTRY: cmd_entry = cmd_entry {
+    name: SyncCharPtr::new(c"wait-for-output"),
+    alias: SyncCharPtr::new(c"waito"),
+
+    args: args_parse::new(c"iT:t:", 1, 1, None),
+    usage: SyncCharPtr::new(c"[-i] [-T timeout] [-t target-pane] pattern"),
+
+    target: cmd_entry_flag::new(b't', cmd_find_type::CMD_FIND_PANE, 0),
+
+    flags: cmd_flag::empty(),
+    exec: cmd_wait_for_output_exec,
+    source: cmd_entry_flag::zeroed(),
+};
+
+/// Most lines of output searched at once.
+const WAIT_FOR_OUTPUT_LINES: u32 = 1000;
+
+/// Number of capture groups exposed as formats.
+const WAIT_FOR_OUTPUT_GROUPS: usize = 10;
+
+crate::compat::impl_tailq_entry!(wait_output, entry, tailq_entry<wait_output>);
+#[repr(C)]
+pub struct wait_output {
+    item: *mut cmdq_item,
+    wp: *mut window_pane,
+
+    regex: regex_t,
+    line: u64,
+    x: u32,
+
+    timer: event,
+
+    entry: tailq_entry<wait_output>,
+}
+
+static mut WAIT_OUTPUTS: tailq_head<wait_output> = compat::TAILQ_HEAD_INITIALIZER!(WAIT_OUTPUTS);
+
+unsafe fn cmd_wait_for_output_exec(self_: *mut cmd, item: *mut cmdq_item) -> cmd_retval {
+    unsafe {
+        let args = cmd_get_args(self_);
+        let wp = (*cmdq_get_target(item)).wp;
+        let gd = (*wp).base.grid;
+        let pattern = args_string(args, 0);
+        let mut d: f64 = 0.0;
+        let mut end: *mut u8 = null_mut();
+
+        let timeout = args_get(args, b'T');
+        if !timeout.is_null() {
+            d = strtod(timeout, &raw mut end);
+            if *end != b'\0' || d <= 0.0 {
+                cmdq_error!(item, "invalid timeout: {}", _s(timeout));
+                return cmd_retval::CMD_RETURN_ERROR;
+            }
+        }
+
+        let wo = xcalloc1::<wait_output>() as *mut wait_output;
+        let mut cflags = REG_EXTENDED;
+        if args_has(args, b'i') != 0 {
+            cflags |= REG_ICASE;
+        }
+        if regcomp(&raw mut (*wo).regex, pattern, cflags) != 0 {
+            cmdq_error!(item, "invalid pattern: {}", _s(pattern));
+            free_(wo);
+            return cmd_retval::CMD_RETURN_ERROR;
+        }
+
+        // Only output written from the cursor onwards is searched.
+        (*wo).item = item;
+        (*wo).wp = wp;
+        (*wo).line = (*gd).htrimmed + ((*gd).hsize + (*wp).base.cy) as u64;
+        (*wo).x = (*wp).base.cx;
+        tailq_insert_tail(&raw mut WAIT_OUTPUTS, wo);
+
+        evtimer_set(
+            &raw mut (*wo).timer,
+            Some(cmd_wait_for_output_timer),
+            wo.cast(),
+        );
+        if !timeout.is_null() {
+            let mut tv = timeval {
+                tv_sec: d as time_t,
+                tv_usec: ((d - (d as time_t) as f64) * 1000000.0) as libc::suseconds_t,
+            };
+            evtimer_add(&raw mut (*wo).timer, &raw mut tv);
+        }
+
+        log_debug!("wait for output on %{}: {}", (*wp).id, _s(pattern));
+        cmd_retval::CMD_RETURN_WAIT
+    }
+}
+
+unsafe fn cmd_wait_for_output_free(wo: *mut wait_output) {
+    unsafe {
+        tailq_remove(&raw mut WAIT_OUTPUTS, wo);
+        evtimer_del(&raw mut (*wo).timer);
+        regfree(&raw mut (*wo).regex);
+        free_(wo);
+    }
+}
+
+unsafe extern "C" fn cmd_wait_for_output_timer(_fd: i32, _events: i16, arg: *mut c_void) {
+    unsafe {
+        let wo = arg as *mut wait_output;
+        let item = (*wo).item;
+
+        cmdq_error!(item, "timed out waiting for output");
+        cmdq_remove_group(item);
+        cmdq_continue(item);
+        cmd_wait_for_output_free(wo);
+    }
+}
+
+/// Collect the text written to the pane since the wait started.
+unsafe fn cmd_wait_for_output_text(wo: *mut wait_output) -> Vec<u8> {
+    unsafe {
+        let gd = (*(*wo).wp).base.grid;
+        let total = (*gd).hsize + (*gd).sy;
+        let mut text = Vec::new();
+
+        let (mut py, mut px) = if (*wo).line < (*gd).htrimmed {
+            (0, 0)
+        } else {
+            ((*wo).line - (*gd).htrimmed, (*wo).x)
+        };
+        if py >= total as u64 {
+            return text;
+        }
+
+        let mut last = total;
+        while last as u64 > py && grid_line_length(gd, last - 1) == 0 {
+            last -= 1;
+        }
+        if (last as u64) > py + WAIT_FOR_OUTPUT_LINES as u64 {
+            py = (last - WAIT_FOR_OUTPUT_LINES) as u64;
+            px = 0;
+        }
+
+        for yy in py as u32..last {
+            let line = grid_string_cells(
+                gd,
+                if yy as u64 == py { px } else { 0 },
+                yy,
+                (*gd).sx,
+                null_mut(),
+                grid_string_flags::GRID_STRING_TRIM_SPACES,
+                null_mut(),
+            );
+            text.extend_from_slice(std::ffi::CStr::from_ptr(line.cast()).to_bytes());
+            free_(line);
+            if !(*grid_peek_line(gd, yy))
+                .flags
+                .intersects(grid_line_flag::WRAPPED)
+            {
+                text.push(b'\n');
+            }
+        }
+        text.push(b'\0');
+        text
+    }
+}
+
+/// Check waiters on a pane after new output has been parsed.
+pub unsafe fn cmd_wait_for_output_check(wp: *mut window_pane) {
+    unsafe {
+        if tailq_empty(&raw const WAIT_OUTPUTS) {
+            return;
+        }
+
+        for wo in tailq_foreach::<_, ()>(&raw mut WAIT_OUTPUTS).map(NonNull::as_ptr) {
+            if (*wo).wp != wp {
+                continue;
+            }
+
+            let text = cmd_wait_for_output_text(wo);
+            if text.len() <= 1 {
+                continue;
+            }
+            let mut rm: [regmatch_t; WAIT_FOR_OUTPUT_GROUPS] = zeroed();
+            if regexec(
+                &raw const (*wo).regex,
+                text.as_ptr(),
+                WAIT_FOR_OUTPUT_GROUPS,
+                rm.as_mut_ptr(),
+                0,
+            ) != 0
+            {
+                continue;
+            }
+
+            let state = cmdq_get_state((*wo).item);
+            for (i, m) in rm.iter().enumerate() {
+                if m.rm_so == -1 {
+                    continue;
+                }
+                let value = String::from_utf8_lossy(&text[m.rm_so as usize..m.rm_eo as usize]);
+                if i == 0 {
+                    cmdq_add_format!(state, c!("wait_match"), "{}", value);
+                } else {
+                    let key = format_nul!("wait_match_{}", i);
+                    cmdq_add_format!(state, key, "{}", value);
+                    free_(key);
+                }
+            }
+
+            log_debug!("output matched on %{}", (*wp).id);
+            cmdq_continue((*wo).item);
+            cmd_wait_for_output_free(wo);
+        }
+    }
+}
+
+/// Fail any waiters on a pane which is going away.
+pub unsafe fn cmd_wait_for_output_cancel(wp: *mut window_pane) {
+    unsafe {
+        for wo in tailq_foreach::<_, ()>(&raw mut WAIT_OUTPUTS).map(NonNull::as_ptr) {
+            if (*wo).wp != wp {
+                continue;
+            }
+            cmdq_error!((*wo).item, "pane exited before output matched");
+            cmdq_remove_group((*wo).item);
+            cmdq_continue((*wo).item);
+            cmd_wait_for_output_free(wo);
+        }
+    }
+}
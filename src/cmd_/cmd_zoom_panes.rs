@@ -0,0 +1,84 @@
+use crate::*;
+
+use crate::compat::queue::tailq_foreach;
+
+pub static CMD_ZOOM_PANES_ENTRY: cmd_entry = cmd_entry {
+    name: SyncCharPtr::new(c"zoom-panes"),
+    alias: SyncCharPtr::new(c"zoomp"),
+
+    args: args_parse::new(c"t:v", 0, -1, None),
+    usage: SyncCharPtr::new(c"[-v] [-t target-pane] [pane ...]"),
+
+    target: cmd_entry_flag::new(b't', cmd_find_type::CMD_FIND_PANE, 0),
+
+    flags: cmd_flag::CMD_AFTERHOOK,
+    exec: cmd_zoom_panes_exec,
+    source: cmd_entry_flag::zeroed(),
+};
+
+unsafe fn cmd_zoom_panes_exec(self_: *mut cmd, item: *mut cmdq_item) -> cmd_retval {
+    unsafe {
+        let args = cmd_get_args(self_);
+        let target = cmdq_get_target(item);
+        let w = (*(*target).wl).window;
+        let mut selected = vec![(*target).wp];
+
+        if (*w).flags.intersects(window_flag::ZOOMED) {
+            server_unzoom_window(w);
+            return cmd_retval::CMD_RETURN_NORMAL;
+        }
+
+        if args_count(args) == 0 {
+            server_check_marked_panes();
+            for fs in server_marked_panes() {
+                if fs.w == w {
+                    selected.push(fs.wp);
+                }
+            }
+        }
+        for i in 0..args_count(args) {
+            let mut fs: cmd_find_state = zeroed();
+            if cmd_find_target(
+                &raw mut fs,
+                item,
+                args_string(args, i),
+                cmd_find_type::CMD_FIND_PANE,
+                0,
+            ) != 0
+            {
+                return cmd_retval::CMD_RETURN_ERROR;
+            }
+            if fs.w != w {
+                cmdq_error!(item, "panes must be in the same window");
+                return cmd_retval::CMD_RETURN_ERROR;
+            }
+            selected.push(fs.wp);
+        }
+
+        let panes: Vec<*mut window_pane> = tailq_foreach::<_, discr_entry>(&raw mut (*w).panes)
+            .map(NonNull::as_ptr)
+            .filter(|wp| selected.contains(wp))
+            .collect();
+        if panes.iter().any(|&wp| window_pane_visible(wp) == 0) {
+            cmdq_error!(item, "pane is not visible");
+            return cmd_retval::CMD_RETURN_ERROR;
+        }
+        if panes.len() < 2 {
+            cmdq_error!(item, "no other panes to zoom");
+            return cmd_retval::CMD_RETURN_ERROR;
+        }
+
+        let type_ = if args_has_(args, 'v') {
+            layout_type::LAYOUT_TOPBOTTOM
+        } else {
+            layout_type::LAYOUT_LEFTRIGHT
+        };
+        if window_zoom_panes(w, &panes, type_) != 0 {
+            cmdq_error!(item, "window too small to zoom {} panes", panes.len());
+            return cmd_retval::CMD_RETURN_ERROR;
+        }
+        server_redraw_window(w);
+
+        cmd_retval::CMD_RETURN_NORMAL
+    }
+}
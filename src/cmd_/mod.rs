@@ -32,6 +32,7 @@ pub mod cmd_capture_pane;
 pub mod cmd_choose_tree;
 pub mod cmd_command_prompt;
 pub mod cmd_confirm_before;
+pub mod cmd_constrain_pane;
 pub mod cmd_copy_mode;
 pub mod cmd_detach_client;
 pub mod cmd_display_menu;
@@ -40,6 +41,7 @@ pub mod cmd_display_panes;
 pub mod cmd_find;
 pub mod cmd_find_window;
 pub mod cmd_if_shell;
+pub mod cmd_join_group;
 pub mod cmd_join_pane;
 pub mod cmd_kill_pane;
 pub mod cmd_kill_server;
@@ -48,6 +50,7 @@ pub mod cmd_kill_window;
 pub mod cmd_list_buffers;
 pub mod cmd_list_clients;
 pub mod cmd_list_keys;
+pub mod cmd_list_marks;
 pub mod cmd_list_panes;
 pub mod cmd_list_sessions;
 pub mod cmd_list_windows;
@@ -69,6 +72,8 @@ pub mod cmd_respawn_window;
 pub mod cmd_rotate_window;
 pub mod cmd_run_shell;
 pub mod cmd_save_buffer;
+pub mod cmd_save_layout;
+pub mod cmd_schedule;
 pub mod cmd_select_layout;
 pub mod cmd_select_pane;
 pub mod cmd_select_window;
@@ -83,22 +88,28 @@ pub mod cmd_show_options;
 pub mod cmd_show_prompt_history;
 pub mod cmd_source_file;
 pub mod cmd_split_window;
+pub mod cmd_stack_pane;
 pub mod cmd_swap_pane;
 pub mod cmd_swap_window;
 pub mod cmd_switch_client;
+pub mod cmd_trash;
 pub mod cmd_unbind_key;
 pub mod cmd_wait_for;
+pub mod cmd_wait_for_output;
+pub mod cmd_zoom_panes;
 
 use cmd_attach_session::CMD_ATTACH_SESSION_ENTRY;
 use cmd_bind_key::CMD_BIND_KEY_ENTRY;
 use cmd_break_pane::CMD_BREAK_PANE_ENTRY;
 use cmd_capture_pane::{CMD_CAPTURE_PANE_ENTRY, CMD_CLEAR_HISTORY_ENTRY};
 use cmd_choose_tree::{
-    CMD_CHOOSE_BUFFER_ENTRY, CMD_CHOOSE_CLIENT_ENTRY, CMD_CHOOSE_TREE_ENTRY,
-    CMD_CUSTOMIZE_MODE_ENTRY,
+    CMD_CHOOSE_BUFFER_ENTRY, CMD_CHOOSE_CLIENT_ENTRY, CMD_CHOOSE_LAYOUT_ENTRY,
+    CMD_CHOOSE_TRASH_ENTRY, CMD_CHOOSE_TREE_ENTRY, CMD_CUSTOMIZE_MODE_ENTRY,
+    CMD_SEARCH_PANES_ENTRY,
 };
 use cmd_command_prompt::CMD_COMMAND_PROMPT_ENTRY;
 use cmd_confirm_before::CMD_CONFIRM_BEFORE_ENTRY;
+use cmd_constrain_pane::CMD_CONSTRAIN_PANE_ENTRY;
 use cmd_copy_mode::{CMD_CLOCK_MODE_ENTRY, CMD_COPY_MODE_ENTRY};
 use cmd_detach_client::CMD_DETACH_CLIENT_ENTRY;
 use cmd_detach_client::CMD_SUSPEND_CLIENT_ENTRY;
@@ -107,6 +118,7 @@ use cmd_display_message::CMD_DISPLAY_MESSAGE_ENTRY;
 use cmd_display_panes::CMD_DISPLAY_PANES_ENTRY;
 use cmd_find_window::CMD_FIND_WINDOW_ENTRY;
 use cmd_if_shell::CMD_IF_SHELL_ENTRY;
+use cmd_join_group::{CMD_JOIN_GROUP_ENTRY, CMD_LEAVE_GROUP_ENTRY};
 use cmd_join_pane::{CMD_JOIN_PANE_ENTRY, CMD_MOVE_PANE_ENTRY};
 use cmd_kill_pane::CMD_KILL_PANE_ENTRY;
 use cmd_kill_server::CMD_KILL_SERVER_ENTRY;
@@ -117,6 +129,7 @@ use cmd_kill_window::CMD_UNLINK_WINDOW_ENTRY;
 use cmd_list_buffers::CMD_LIST_BUFFERS_ENTRY;
 use cmd_list_clients::CMD_LIST_CLIENTS_ENTRY;
 use cmd_list_keys::{CMD_LIST_COMMANDS_ENTRY, CMD_LIST_KEYS_ENTRY};
+use cmd_list_marks::CMD_LIST_MARKS_ENTRY;
 use cmd_list_panes::CMD_LIST_PANES_ENTRY;
 use cmd_list_sessions::CMD_LIST_SESSIONS_ENTRY;
 use cmd_list_windows::CMD_LIST_WINDOWS_ENTRY;
@@ -140,9 +153,14 @@ use cmd_rotate_window::CMD_ROTATE_WINDOW_ENTRY;
 use cmd_run_shell::CMD_RUN_SHELL_ENTRY;
 use cmd_save_buffer::CMD_SAVE_BUFFER_ENTRY;
 use cmd_save_buffer::CMD_SHOW_BUFFER_ENTRY;
+use cmd_save_layout::{CMD_DELETE_LAYOUT_ENTRY, CMD_LIST_LAYOUTS_ENTRY, CMD_SAVE_LAYOUT_ENTRY};
+use cmd_schedule::{
+    CMD_CANCEL_SCHEDULE_ENTRY, CMD_LIST_SCHEDULES_ENTRY, CMD_SCHEDULE_COMMAND_ENTRY,
+};
 use cmd_select_layout::CMD_NEXT_LAYOUT_ENTRY;
 use cmd_select_layout::CMD_PREVIOUS_LAYOUT_ENTRY;
 use cmd_select_layout::CMD_SELECT_LAYOUT_ENTRY;
+use cmd_select_layout::{CMD_REDO_LAYOUT_ENTRY, CMD_UNDO_LAYOUT_ENTRY};
 use cmd_select_pane::CMD_LAST_PANE_ENTRY;
 use cmd_select_pane::CMD_SELECT_PANE_ENTRY;
 use cmd_select_window::CMD_LAST_WINDOW_ENTRY;
@@ -159,35 +177,46 @@ use cmd_set_option::CMD_SET_HOOK_ENTRY;
 use cmd_set_option::CMD_SET_OPTION_ENTRY;
 use cmd_set_option::CMD_SET_WINDOW_OPTION_ENTRY;
 use cmd_show_environment::CMD_SHOW_ENVIRONMENT_ENTRY;
-use cmd_show_messages::CMD_SHOW_MESSAGES_ENTRY;
+use cmd_show_messages::{CMD_LIST_JOBS_ENTRY, CMD_SHOW_MESSAGES_ENTRY};
 use cmd_show_options::CMD_SHOW_HOOKS_ENTRY;
 use cmd_show_options::CMD_SHOW_OPTIONS_ENTRY;
 use cmd_show_options::CMD_SHOW_WINDOW_OPTIONS_ENTRY;
 use cmd_show_prompt_history::{CMD_CLEAR_PROMPT_HISTORY_ENTRY, CMD_SHOW_PROMPT_HISTORY_ENTRY};
 use cmd_source_file::CMD_SOURCE_FILE_ENTRY;
 use cmd_split_window::CMD_SPLIT_WINDOW_ENTRY;
+use cmd_stack_pane::{
+    CMD_NEXT_TAB_ENTRY, CMD_PREVIOUS_TAB_ENTRY, CMD_STACK_PANE_ENTRY, CMD_UNSTACK_PANE_ENTRY,
+};
 use cmd_swap_pane::CMD_SWAP_PANE_ENTRY;
 use cmd_swap_window::CMD_SWAP_WINDOW_ENTRY;
 use cmd_switch_client::CMD_SWITCH_CLIENT_ENTRY;
+use cmd_trash::{CMD_LIST_TRASH_ENTRY, CMD_RESTORE_TRASH_ENTRY};
 use cmd_unbind_key::CMD_UNBIND_KEY_ENTRY;
-use cmd_wait_for::CMD_WAIT_FOR_ENTRY;
+use cmd_wait_for::{CMD_LIST_CHANNELS_ENTRY, CMD_WAIT_FOR_ENTRY};
+use cmd_wait_for_output::CMD_WAIT_FOR_OUTPUT_ENTRY;
+use cmd_zoom_panes::CMD_ZOOM_PANES_ENTRY;
 
-pub static CMD_TABLE: [&cmd_entry; 90] = [
+pub static CMD_TABLE: [&cmd_entry; 115] = [
     &CMD_ATTACH_SESSION_ENTRY,
     &CMD_BIND_KEY_ENTRY,
     &CMD_BREAK_PANE_ENTRY,
+    &CMD_CANCEL_SCHEDULE_ENTRY,
     &CMD_CAPTURE_PANE_ENTRY,
     &CMD_CHOOSE_BUFFER_ENTRY,
     &CMD_CHOOSE_CLIENT_ENTRY,
+    &CMD_CHOOSE_LAYOUT_ENTRY,
+    &CMD_CHOOSE_TRASH_ENTRY,
     &CMD_CHOOSE_TREE_ENTRY,
     &CMD_CLEAR_HISTORY_ENTRY,
     &CMD_CLEAR_PROMPT_HISTORY_ENTRY,
     &CMD_CLOCK_MODE_ENTRY,
     &CMD_COMMAND_PROMPT_ENTRY,
     &CMD_CONFIRM_BEFORE_ENTRY,
+    &CMD_CONSTRAIN_PANE_ENTRY,
     &CMD_COPY_MODE_ENTRY,
     &CMD_CUSTOMIZE_MODE_ENTRY,
     &CMD_DELETE_BUFFER_ENTRY,
+    &CMD_DELETE_LAYOUT_ENTRY,
     &CMD_DETACH_CLIENT_ENTRY,
     &CMD_DISPLAY_MENU_ENTRY,
     &CMD_DISPLAY_MESSAGE_ENTRY,
@@ -196,6 +225,7 @@ pub static CMD_TABLE: [&cmd_entry; 90] = [
     &CMD_FIND_WINDOW_ENTRY,
     &CMD_HAS_SESSION_ENTRY,
     &CMD_IF_SHELL_ENTRY,
+    &CMD_JOIN_GROUP_ENTRY,
     &CMD_JOIN_PANE_ENTRY,
     &CMD_KILL_PANE_ENTRY,
     &CMD_KILL_SERVER_ENTRY,
@@ -203,13 +233,20 @@ pub static CMD_TABLE: [&cmd_entry; 90] = [
     &CMD_KILL_WINDOW_ENTRY,
     &CMD_LAST_PANE_ENTRY,
     &CMD_LAST_WINDOW_ENTRY,
+    &CMD_LEAVE_GROUP_ENTRY,
     &CMD_LINK_WINDOW_ENTRY,
     &CMD_LIST_BUFFERS_ENTRY,
+    &CMD_LIST_CHANNELS_ENTRY,
     &CMD_LIST_CLIENTS_ENTRY,
     &CMD_LIST_COMMANDS_ENTRY,
+    &CMD_LIST_JOBS_ENTRY,
     &CMD_LIST_KEYS_ENTRY,
+    &CMD_LIST_LAYOUTS_ENTRY,
+    &CMD_LIST_MARKS_ENTRY,
     &CMD_LIST_PANES_ENTRY,
+    &CMD_LIST_SCHEDULES_ENTRY,
     &CMD_LIST_SESSIONS_ENTRY,
+    &CMD_LIST_TRASH_ENTRY,
     &CMD_LIST_WINDOWS_ENTRY,
     &CMD_LOAD_BUFFER_ENTRY,
     &CMD_LOCK_CLIENT_ENTRY,
@@ -220,21 +257,28 @@ pub static CMD_TABLE: [&cmd_entry; 90] = [
     &CMD_NEW_SESSION_ENTRY,
     &CMD_NEW_WINDOW_ENTRY,
     &CMD_NEXT_LAYOUT_ENTRY,
+    &CMD_NEXT_TAB_ENTRY,
     &CMD_NEXT_WINDOW_ENTRY,
     &CMD_PASTE_BUFFER_ENTRY,
     &CMD_PIPE_PANE_ENTRY,
     &CMD_PREVIOUS_LAYOUT_ENTRY,
+    &CMD_PREVIOUS_TAB_ENTRY,
     &CMD_PREVIOUS_WINDOW_ENTRY,
+    &CMD_REDO_LAYOUT_ENTRY,
     &CMD_REFRESH_CLIENT_ENTRY,
     &CMD_RENAME_SESSION_ENTRY,
     &CMD_RENAME_WINDOW_ENTRY,
     &CMD_RESIZE_PANE_ENTRY,
     &CMD_RESIZE_WINDOW_ENTRY,
     &CMD_RESPAWN_PANE_ENTRY,
+    &CMD_RESTORE_TRASH_ENTRY,
     &CMD_RESPAWN_WINDOW_ENTRY,
     &CMD_ROTATE_WINDOW_ENTRY,
     &CMD_RUN_SHELL_ENTRY,
     &CMD_SAVE_BUFFER_ENTRY,
+    &CMD_SAVE_LAYOUT_ENTRY,
+    &CMD_SCHEDULE_COMMAND_ENTRY,
+    &CMD_SEARCH_PANES_ENTRY,
     &CMD_SELECT_LAYOUT_ENTRY,
     &CMD_SELECT_PANE_ENTRY,
     &CMD_SELECT_WINDOW_ENTRY,
@@ -255,14 +299,19 @@ pub static CMD_TABLE: [&cmd_entry; 90] = [
     &CMD_SHOW_WINDOW_OPTIONS_ENTRY,
     &CMD_SOURCE_FILE_ENTRY,
     &CMD_SPLIT_WINDOW_ENTRY,
+    &CMD_STACK_PANE_ENTRY,
     &CMD_START_SERVER_ENTRY,
     &CMD_SUSPEND_CLIENT_ENTRY,
     &CMD_SWAP_PANE_ENTRY,
     &CMD_SWAP_WINDOW_ENTRY,
     &CMD_SWITCH_CLIENT_ENTRY,
     &CMD_UNBIND_KEY_ENTRY,
+    &CMD_UNDO_LAYOUT_ENTRY,
     &CMD_UNLINK_WINDOW_ENTRY,
+    &CMD_UNSTACK_PANE_ENTRY,
     &CMD_WAIT_FOR_ENTRY,
+    &CMD_WAIT_FOR_OUTPUT_ENTRY,
+    &CMD_ZOOM_PANES_ENTRY,
 ];
 
 // Instance of a command.
@@ -881,10 +930,11 @@ pub unsafe fn cmd_mouse_pane(
         if (*m).wp == -1 {
             wp = NonNull::new((*(*wl.as_ptr()).window).active);
         } else {
-            let wp = NonNull::new(window_pane_find_by_id((*m).wp as u32))?;
-            if !window_has_pane((*wl.as_ptr()).window, wp.as_ptr()) {
+            let found = NonNull::new(window_pane_find_by_id((*m).wp as u32))?;
+            if !window_has_pane((*wl.as_ptr()).window, found.as_ptr()) {
                 return None;
             }
+            wp = Some(found);
         }
 
         if !wlp.is_null() {
@@ -911,17 +961,17 @@ pub unsafe fn cmd_template_replace(template: *const u8, s: *const u8, idx: c_int
         while *ptr != b'\0' {
             let ch = *ptr;
             ptr = ptr.add(1);
-            if matches!(ch as c_uchar, b'%') {
-                if *ptr < b'1' || *ptr > b'9' || *ptr as i32 - b'0' as i32 != idx {
-                    if *ptr != b'%' || replaced != 0 {
-                        break;
-                    }
+            if matches!(ch as c_uchar, b'%')
+                && ((*ptr >= b'1' && *ptr <= b'9' && *ptr as i32 - b'0' as i32 == idx)
+                    || (*ptr == b'%' && replaced == 0))
+            {
+                if *ptr == b'%' {
                     replaced = 1;
                 }
                 ptr = ptr.add(1);
 
                 let quoted = *ptr == b'%';
-                if !quoted {
+                if quoted {
                     ptr = ptr.add(1);
                 }
 
@@ -948,3 +998,37 @@ pub unsafe fn cmd_template_replace(template: *const u8, s: *const u8, idx: c_int
         buf
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn replace(template: &CStr, s: &CStr, idx: c_int) -> String {
+        unsafe {
+            let out = cmd_template_replace(template.as_ptr().cast(), s.as_ptr().cast(), idx);
+            let result = cstr_to_str(out).to_string();
+            free_(out);
+            result
+        }
+    }
+
+    #[test]
+    fn test_template_replace() {
+        assert_eq!(
+            replace(c"kill-window -t %%", c"main:1", 1),
+            "kill-window -t main:1"
+        );
+        assert_eq!(replace(c"a %1 b %2", c"x", 1), "a x b %2");
+        assert_eq!(replace(c"a %1 b %2", c"x", 2), "a %1 b x");
+        assert_eq!(replace(c"%% and %%", c"x", 1), "x and %%");
+        assert_eq!(replace(c"50% of %%", c"x", 1), "50% of x");
+        assert_eq!(replace(c"no template", c"x", 1), "no template");
+    }
+
+    #[test]
+    fn test_template_replace_quoted() {
+        assert_eq!(replace(c"echo \"%%%\"", c"a\"b;c", 1), r#"echo "a\"b\;c""#);
+        assert_eq!(replace(c"echo %%", c"a\"b", 1), "echo a\"b");
+        assert_eq!(replace(c"%1%", c"$x", 1), "\\$x");
+    }
+}
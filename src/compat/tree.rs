@@ -419,7 +419,7 @@ where
                             rb_color!(oright) = rb_color::RB_BLACK;
                         }
                         rb_color!(tmp) = rb_color::RB_RED;
-                        rb_rotate_left(head, oright);
+                        rb_rotate_left(head, tmp);
                         tmp = rb_left(parent);
                     }
                     rb_color!(tmp) = rb_color(parent);
@@ -747,3 +747,89 @@ where
         elm
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    struct node {
+        key: u32,
+        entry: rb_entry<node>,
+    }
+
+    impl GetEntry<node> for node {
+        unsafe fn entry_mut(this: *mut Self) -> *mut rb_entry<node> {
+            unsafe { &raw mut (*this).entry }
+        }
+        unsafe fn entry(this: *const Self) -> *const rb_entry<node> {
+            unsafe { &raw const (*this).entry }
+        }
+        fn cmp(this: &Self, other: &Self) -> Ordering {
+            this.key.cmp(&other.key)
+        }
+    }
+
+    /// Check the red-black properties below a node, returning its black height.
+    unsafe fn check(elm: *mut node, parent: *mut node) -> u32 {
+        unsafe {
+            if elm.is_null() {
+                return 1;
+            }
+            assert!(rb_parent::<_, ()>(elm) == parent);
+            let left = rb_left::<_, ()>(elm);
+            let right = rb_right::<_, ()>(elm);
+            if rb_color::<_, ()>(elm) == rb_color::RB_RED {
+                assert!(left.is_null() || rb_color::<_, ()>(left) == rb_color::RB_BLACK);
+                assert!(right.is_null() || rb_color::<_, ()>(right) == rb_color::RB_BLACK);
+            }
+            let height = check(left, elm);
+            assert_eq!(height, check(right, elm));
+            height + (rb_color::<_, ()>(elm) == rb_color::RB_BLACK) as u32
+        }
+    }
+
+    /// Shuffle with a fixed linear congruential generator.
+    fn shuffle(keys: &mut [u32], mut seed: u32) {
+        for i in (1..keys.len()).rev() {
+            seed = seed.wrapping_mul(1103515245).wrapping_add(12345);
+            keys.swap(i, (seed >> 8) as usize % (i + 1));
+        }
+    }
+
+    #[test]
+    fn test_remove_keeps_balance() {
+        const N: u32 = 512;
+        let mut nodes: Vec<node> = (0..N)
+            .map(|key| node {
+                key,
+                entry: rb_entry::default(),
+            })
+            .collect();
+        let base = nodes.as_mut_ptr();
+        let mut head = rb_initializer::<node>();
+
+        let mut keys: Vec<u32> = (0..N).collect();
+        shuffle(&mut keys, 1);
+        unsafe {
+            for &key in &keys {
+                assert!(rb_insert::<_, ()>(&raw mut head, base.add(key as usize)).is_null());
+                check(rb_root(&raw mut head), null_mut());
+            }
+
+            shuffle(&mut keys, 2);
+            for (removed, &key) in keys.iter().enumerate() {
+                rb_remove::<_, ()>(&raw mut head, base.add(key as usize));
+                let root = rb_root(&raw mut head);
+                assert!(root.is_null() || rb_color::<_, ()>(root) == rb_color::RB_BLACK);
+                check(root, null_mut());
+
+                let left: Vec<u32> = rb_foreach::<_, ()>(&raw mut head)
+                    .map(|elm| (*elm.as_ptr()).key)
+                    .collect();
+                assert_eq!(left.len(), N as usize - removed - 1);
+                assert!(left.windows(2).all(|pair| pair[0] < pair[1]));
+            }
+        }
+        assert!(head.rbh_root.is_null());
+    }
+}
@@ -938,6 +938,105 @@ pub unsafe fn format_cb_current_path(ft: *mut format_tree) -> *mut c_void {
     }
 }
 
+/// Get git repository information for the pane's current path, falling back
+/// to the path set by OSC 7.
+unsafe fn format_git_info(ft: *mut format_tree) -> *const git_info {
+    unsafe {
+        let wp = (*ft).wp;
+
+        if wp.is_null() {
+            return null();
+        }
+
+        let mut path = osdep_get_cwd((*wp).fd);
+        if path.is_null() {
+            path = (*wp).base.path;
+            if !path.is_null() && libc::strncmp(path, c!("file://"), 7) == 0 {
+                path = strchr(path.add(7), b'/' as i32);
+            }
+        }
+        git_get(path)
+    }
+}
+
+unsafe fn format_git_string(value: &Option<std::ffi::CString>) -> *mut c_void {
+    match value {
+        Some(value) => unsafe { xstrdup(value.as_ptr().cast()).as_ptr().cast() },
+        None => null_mut(),
+    }
+}
+
+/// Callback for pane_git_ahead.
+pub unsafe fn format_cb_pane_git_ahead(ft: *mut format_tree) -> *mut c_void {
+    unsafe {
+        match format_git_info(ft).as_ref().and_then(|gi| gi.ahead) {
+            Some(ahead) => format_nul!("{ahead}").cast(),
+            None => null_mut(),
+        }
+    }
+}
+
+/// Callback for pane_git_behind.
+pub unsafe fn format_cb_pane_git_behind(ft: *mut format_tree) -> *mut c_void {
+    unsafe {
+        match format_git_info(ft).as_ref().and_then(|gi| gi.behind) {
+            Some(behind) => format_nul!("{behind}").cast(),
+            None => null_mut(),
+        }
+    }
+}
+
+/// Callback for pane_git_branch.
+pub unsafe fn format_cb_pane_git_branch(ft: *mut format_tree) -> *mut c_void {
+    unsafe {
+        match format_git_info(ft).as_ref() {
+            Some(gi) => format_git_string(&gi.branch),
+            None => null_mut(),
+        }
+    }
+}
+
+/// Callback for pane_git_commit.
+pub unsafe fn format_cb_pane_git_commit(ft: *mut format_tree) -> *mut c_void {
+    unsafe {
+        match format_git_info(ft).as_ref() {
+            Some(gi) => format_git_string(&gi.commit),
+            None => null_mut(),
+        }
+    }
+}
+
+/// Callback for pane_git_detached.
+pub unsafe fn format_cb_pane_git_detached(ft: *mut format_tree) -> *mut c_void {
+    unsafe {
+        match format_git_info(ft).as_ref() {
+            Some(gi) if gi.detached => xstrdup(c!("1")).as_ptr().cast(),
+            Some(_) => xstrdup(c!("0")).as_ptr().cast(),
+            None => null_mut(),
+        }
+    }
+}
+
+/// Callback for pane_git_root.
+pub unsafe fn format_cb_pane_git_root(ft: *mut format_tree) -> *mut c_void {
+    unsafe {
+        match format_git_info(ft).as_ref() {
+            Some(gi) => format_git_string(&gi.root),
+            None => null_mut(),
+        }
+    }
+}
+
+/// Callback for pane_git_upstream.
+pub unsafe fn format_cb_pane_git_upstream(ft: *mut format_tree) -> *mut c_void {
+    unsafe {
+        match format_git_info(ft).as_ref() {
+            Some(gi) => format_git_string(&gi.upstream),
+            None => null_mut(),
+        }
+    }
+}
+
 /// Callback for history_bytes.
 pub unsafe fn format_cb_history_bytes(ft: *mut format_tree) -> *mut c_void {
     unsafe {
@@ -1987,6 +2086,31 @@ pub unsafe fn format_cb_pane_bottom(ft: *mut format_tree) -> *mut c_void {
     }
 }
 
+/// Callback for pane_constraint.
+pub unsafe fn format_cb_pane_constraint(ft: *mut format_tree) -> *mut c_void {
+    unsafe {
+        let wp = (*ft).wp;
+        if wp.is_null() || (*wp).layout_cell.is_null() {
+            return null_mut();
+        }
+        let lc = (*wp).layout_cell;
+        let mut out = Vec::new();
+        if (*lc).constraint_x != layout_constraint::default() {
+            out.push(format!(
+                "x={}",
+                layout_constraint_string(&(*lc).constraint_x)
+            ));
+        }
+        if (*lc).constraint_y != layout_constraint::default() {
+            out.push(format!(
+                "y={}",
+                layout_constraint_string(&(*lc).constraint_y)
+            ));
+        }
+        format_printf!("{}", out.join(",")).cast()
+    }
+}
+
 /// Callback for pane_dead.
 pub unsafe fn format_cb_pane_dead(ft: *mut format_tree) -> *mut c_void {
     unsafe {
@@ -2162,7 +2286,7 @@ pub unsafe fn format_cb_pane_left(ft: *mut format_tree) -> *mut c_void {
 pub unsafe fn format_cb_pane_marked(ft: *mut format_tree) -> *mut c_void {
     unsafe {
         if !(*ft).wp.is_null() {
-            if server_check_marked() && MARKED_PANE.wp == (*ft).wp {
+            if server_marked_panes().any(|fs| fs.wp == (*ft).wp) {
                 return xstrdup(c!("1")).as_ptr().cast();
             }
             return xstrdup(c!("0")).as_ptr().cast();
@@ -2257,6 +2381,39 @@ pub unsafe fn format_cb_pane_search_string(ft: *mut format_tree) -> *mut c_void
     }
 }
 
+/// Callback for pane_group.
+pub unsafe fn format_cb_pane_group(ft: *mut format_tree) -> *mut c_void {
+    unsafe {
+        if !(*ft).wp.is_null() && !(*(*ft).wp).group.is_null() {
+            return xstrdup((*(*ft).wp).group).as_ptr().cast();
+        }
+        null_mut()
+    }
+}
+
+/// Callback for pane_stack_size.
+pub unsafe fn format_cb_pane_stack_size(ft: *mut format_tree) -> *mut c_void {
+    unsafe {
+        if !(*ft).wp.is_null() {
+            return format_printf!("{}", layout_stack_tabs((*ft).wp).len()).cast();
+        }
+        null_mut()
+    }
+}
+
+/// Callback for pane_stack_visible.
+pub unsafe fn format_cb_pane_stack_visible(ft: *mut format_tree) -> *mut c_void {
+    unsafe {
+        if !(*ft).wp.is_null() {
+            if !layout_stack_tabs((*ft).wp).is_empty() && !layout_stack_hidden((*ft).wp) {
+                return xstrdup(c!("1")).as_ptr().cast();
+            }
+            return xstrdup(c!("0")).as_ptr().cast();
+        }
+        null_mut()
+    }
+}
+
 /// Callback for pane_synchronized.
 pub unsafe fn format_cb_pane_synchronized(ft: *mut format_tree) -> *mut c_void {
     unsafe {
@@ -2453,7 +2610,7 @@ pub unsafe fn format_cb_session_many_attached(ft: *mut format_tree) -> *mut c_vo
 pub unsafe fn format_cb_session_marked(ft: *mut format_tree) -> *mut c_void {
     unsafe {
         if !(*ft).s.is_null() {
-            if server_check_marked() && MARKED_PANE.s == (*ft).s {
+            if server_marked_panes().any(|fs| fs.s == (*ft).s) {
                 return xstrdup(c!("1")).as_ptr().cast();
             }
             return xstrdup(c!("0")).as_ptr().cast();
@@ -2705,7 +2862,7 @@ pub unsafe fn format_cb_window_linked_sessions(ft: *mut format_tree) -> *mut c_v
 pub unsafe fn format_cb_window_marked_flag(ft: *mut format_tree) -> *mut c_void {
     unsafe {
         if !(*ft).wl.is_null() {
-            if server_check_marked() && MARKED_PANE.wl == (*ft).wl {
+            if server_marked_panes().any(|fs| fs.wl == (*ft).wl) {
                 return xstrdup(c!("1")).as_ptr().cast();
             }
             return xstrdup(c!("0")).as_ptr().cast();
@@ -2986,7 +3143,7 @@ impl format_table_entry {
  * here. Only variables which are added by the caller go into the tree.
  */
 #[rustfmt::skip]
-static FORMAT_TABLE: [format_table_entry ; 171] = [
+static FORMAT_TABLE: [format_table_entry ; 182] = [
     format_table_entry::new(c"active_window_index", format_table_type::FORMAT_TABLE_STRING, format_cb_active_window_index),
      format_table_entry::new(c"alternate_on", format_table_type::FORMAT_TABLE_STRING, format_cb_alternate_on),
      format_table_entry::new(c"alternate_saved_x", format_table_type::FORMAT_TABLE_STRING, format_cb_alternate_saved_x),
@@ -3059,6 +3216,7 @@ static FORMAT_TABLE: [format_table_entry ; 171] = [
      format_table_entry::new(c"pane_at_top", format_table_type::FORMAT_TABLE_STRING, format_cb_pane_at_top),
      format_table_entry::new(c"pane_bg", format_table_type::FORMAT_TABLE_STRING, format_cb_pane_bg),
      format_table_entry::new(c"pane_bottom", format_table_type::FORMAT_TABLE_STRING, format_cb_pane_bottom),
+     format_table_entry::new(c"pane_constraint", format_table_type::FORMAT_TABLE_STRING, format_cb_pane_constraint),
      format_table_entry::new(c"pane_current_command", format_table_type::FORMAT_TABLE_STRING, format_cb_current_command),
      format_table_entry::new(c"pane_current_path", format_table_type::FORMAT_TABLE_STRING, format_cb_current_path),
      format_table_entry::new(c"pane_dead", format_table_type::FORMAT_TABLE_STRING, format_cb_pane_dead),
@@ -3067,6 +3225,14 @@ static FORMAT_TABLE: [format_table_entry ; 171] = [
      format_table_entry::new(c"pane_dead_time", format_table_type::FORMAT_TABLE_TIME, format_cb_pane_dead_time),
      format_table_entry::new(c"pane_fg", format_table_type::FORMAT_TABLE_STRING, format_cb_pane_fg),
      format_table_entry::new(c"pane_format", format_table_type::FORMAT_TABLE_STRING, format_cb_pane_format),
+     format_table_entry::new(c"pane_git_ahead", format_table_type::FORMAT_TABLE_STRING, format_cb_pane_git_ahead),
+     format_table_entry::new(c"pane_git_behind", format_table_type::FORMAT_TABLE_STRING, format_cb_pane_git_behind),
+     format_table_entry::new(c"pane_git_branch", format_table_type::FORMAT_TABLE_STRING, format_cb_pane_git_branch),
+     format_table_entry::new(c"pane_git_commit", format_table_type::FORMAT_TABLE_STRING, format_cb_pane_git_commit),
+     format_table_entry::new(c"pane_git_detached", format_table_type::FORMAT_TABLE_STRING, format_cb_pane_git_detached),
+     format_table_entry::new(c"pane_git_root", format_table_type::FORMAT_TABLE_STRING, format_cb_pane_git_root),
+     format_table_entry::new(c"pane_git_upstream", format_table_type::FORMAT_TABLE_STRING, format_cb_pane_git_upstream),
+     format_table_entry::new(c"pane_group", format_table_type::FORMAT_TABLE_STRING, format_cb_pane_group),
      format_table_entry::new(c"pane_height", format_table_type::FORMAT_TABLE_STRING, format_cb_pane_height),
      format_table_entry::new(c"pane_id", format_table_type::FORMAT_TABLE_STRING, format_cb_pane_id),
      format_table_entry::new(c"pane_in_mode", format_table_type::FORMAT_TABLE_STRING, format_cb_pane_in_mode),
@@ -3083,6 +3249,8 @@ static FORMAT_TABLE: [format_table_entry ; 171] = [
      format_table_entry::new(c"pane_pipe", format_table_type::FORMAT_TABLE_STRING, format_cb_pane_pipe),
      format_table_entry::new(c"pane_right", format_table_type::FORMAT_TABLE_STRING, format_cb_pane_right),
      format_table_entry::new(c"pane_search_string", format_table_type::FORMAT_TABLE_STRING, format_cb_pane_search_string),
+     format_table_entry::new(c"pane_stack_size", format_table_type::FORMAT_TABLE_STRING, format_cb_pane_stack_size),
+     format_table_entry::new(c"pane_stack_visible", format_table_type::FORMAT_TABLE_STRING, format_cb_pane_stack_visible),
      format_table_entry::new(c"pane_start_command", format_table_type::FORMAT_TABLE_STRING, format_cb_start_command),
      format_table_entry::new(c"pane_start_path", format_table_type::FORMAT_TABLE_STRING, format_cb_start_path),
      format_table_entry::new(c"pane_synchronized", format_table_type::FORMAT_TABLE_STRING, format_cb_pane_synchronized),
@@ -3168,6 +3336,11 @@ pub unsafe extern "C" fn format_table_compare(key0: *const c_void, entry0: *cons
     }
 }
 
+/// Names of the default format variables, in order.
+pub fn format_table_names() -> impl Iterator<Item = *const u8> {
+    FORMAT_TABLE.iter().map(|fte| fte.key.as_ptr())
+}
+
 pub unsafe fn format_table_get(key: *const u8) -> *mut format_table_entry {
     unsafe {
         libc::bsearch(
@@ -0,0 +1,228 @@
+//! Git repository information for format variables.
+//!
+//! Everything here is read directly from the files in the `.git` directory
+//! (`HEAD`, loose refs, `packed-refs` and `config`) so no process is spawned.
+//! Results are cached per directory for a short time because format
+//! callbacks are run on every status and border redraw.
+use crate::*;
+
+use std::collections::BTreeMap;
+use std::ffi::{CString, OsStr};
+use std::os::unix::ffi::OsStrExt;
+use std::path::{Path, PathBuf};
+
+/// How long a cached entry is used before the repository is read again, in
+/// milliseconds.
+const GIT_CACHE_TIMEOUT: u64 = 2000;
+
+/// Maximum number of cached directories.
+const GIT_CACHE_LIMIT: usize = 256;
+
+/// Length of an abbreviated commit id.
+const GIT_SHORT_SHA: usize = 7;
+
+/// Repository information for one directory.
+#[derive(Default)]
+pub struct git_info {
+    /// Top level of the working tree.
+    pub root: Option<CString>,
+    /// Branch name, or abbreviated commit id if HEAD is detached.
+    pub branch: Option<CString>,
+    /// Full commit id of HEAD.
+    pub commit: Option<CString>,
+    pub detached: bool,
+    /// Upstream ref of the current branch, for example `origin/main`.
+    pub upstream: Option<CString>,
+    /// Commits ahead of and behind upstream, only known when it is cheap to
+    /// work out (currently when both point at the same commit).
+    pub ahead: Option<u32>,
+    pub behind: Option<u32>,
+}
+
+struct git_cache_entry {
+    time: u64,
+    info: Option<Box<git_info>>,
+}
+
+static mut GIT_CACHE: BTreeMap<Vec<u8>, git_cache_entry> = BTreeMap::new();
+
+/// Find the working tree root and git directory containing `path`.
+fn git_find_dirs(path: &Path) -> Option<(PathBuf, PathBuf)> {
+    let mut dir = Some(path);
+    while let Some(d) = dir {
+        let dot_git = d.join(".git");
+        if dot_git.join("HEAD").is_file() {
+            return Some((d.to_path_buf(), dot_git));
+        }
+        if dot_git.is_file() {
+            // Worktrees and submodules use a file containing "gitdir: <path>".
+            let contents = std::fs::read_to_string(&dot_git).ok()?;
+            let gitdir = contents.strip_prefix("gitdir:")?.trim();
+            return Some((d.to_path_buf(), d.join(gitdir)));
+        }
+        dir = d.parent();
+    }
+    None
+}
+
+/// Find the common directory holding refs and config, which differs from the
+/// git directory for linked worktrees.
+fn git_common_dir(gitdir: &Path) -> PathBuf {
+    match std::fs::read_to_string(gitdir.join("commondir")) {
+        Ok(contents) => gitdir.join(contents.trim()),
+        Err(_) => gitdir.to_path_buf(),
+    }
+}
+
+/// Resolve a ref name to a commit id, from a loose ref or `packed-refs`.
+fn git_resolve_ref(gitdir: &Path, commondir: &Path, name: &str) -> Option<String> {
+    for dir in [gitdir, commondir] {
+        if let Ok(contents) = std::fs::read_to_string(dir.join(name)) {
+            let contents = contents.trim();
+            if let Some(target) = contents.strip_prefix("ref:") {
+                return git_resolve_ref(gitdir, commondir, target.trim());
+            }
+            return Some(contents.to_string());
+        }
+    }
+
+    let packed = std::fs::read_to_string(commondir.join("packed-refs")).ok()?;
+    for line in packed.lines() {
+        if line.starts_with('#') || line.starts_with('^') {
+            continue;
+        }
+        if let Some((sha, refname)) = line.split_once(' ')
+            && refname == name
+        {
+            return Some(sha.to_string());
+        }
+    }
+    None
+}
+
+/// Find the upstream ref of a branch from the `[branch "name"]` section of
+/// the repository config.
+fn git_upstream(commondir: &Path, branch: &str) -> Option<(String, String)> {
+    let config = std::fs::read_to_string(commondir.join("config")).ok()?;
+    let section = format!("[branch \"{branch}\"]");
+
+    let mut in_section = false;
+    let mut remote = None;
+    let mut merge = None;
+    for line in config.lines() {
+        let line = line.trim();
+        if line.starts_with('[') {
+            in_section = line == section;
+            continue;
+        }
+        if !in_section {
+            continue;
+        }
+        if let Some((key, value)) = line.split_once('=') {
+            match key.trim() {
+                "remote" => remote = Some(value.trim().to_string()),
+                "merge" => merge = Some(value.trim().to_string()),
+                _ => (),
+            }
+        }
+    }
+
+    let remote = remote?;
+    let merge = merge?;
+    let short = merge.strip_prefix("refs/heads/").unwrap_or(&merge);
+    if remote == "." {
+        return Some((short.to_string(), merge.clone()));
+    }
+    Some((
+        format!("{remote}/{short}"),
+        format!("refs/remotes/{remote}/{short}"),
+    ))
+}
+
+fn git_cstring(s: &str) -> Option<CString> {
+    CString::new(s).ok()
+}
+
+/// Read repository information for a directory.
+fn git_read(path: &Path) -> Option<git_info> {
+    let (root, gitdir) = git_find_dirs(path)?;
+    let commondir = git_common_dir(&gitdir);
+
+    let head = std::fs::read_to_string(gitdir.join("HEAD")).ok()?;
+    let head = head.trim();
+
+    let mut info = git_info {
+        root: CString::new(root.as_os_str().as_bytes()).ok(),
+        ..Default::default()
+    };
+
+    if let Some(target) = head.strip_prefix("ref:") {
+        let target = target.trim();
+        let name = target.strip_prefix("refs/heads/").unwrap_or(target);
+        info.branch = git_cstring(name);
+
+        let commit = git_resolve_ref(&gitdir, &commondir, target);
+        if let Some((upstream, upstream_ref)) = git_upstream(&commondir, name) {
+            info.upstream = git_cstring(&upstream);
+            let upstream_commit = git_resolve_ref(&gitdir, &commondir, &upstream_ref);
+            if commit.is_some() && commit == upstream_commit {
+                info.ahead = Some(0);
+                info.behind = Some(0);
+            }
+        }
+        info.commit = commit.as_deref().and_then(git_cstring);
+    } else {
+        info.detached = true;
+        info.branch = git_cstring(head.get(..GIT_SHORT_SHA).unwrap_or(head));
+        info.commit = git_cstring(head);
+    }
+
+    Some(info)
+}
+
+/// Get repository information for a directory, using the cache if the entry
+/// is recent enough. The returned pointer is valid until the next call.
+pub unsafe fn git_get(path: *const u8) -> *const git_info {
+    unsafe {
+        if path.is_null() || *path != b'/' {
+            return null();
+        }
+        let key = CStr::from_ptr(path.cast()).to_bytes().to_vec();
+        let cache = &raw mut GIT_CACHE;
+        let now = get_timer();
+
+        if let Some(entry) = (*cache).get(&key)
+            && now.saturating_sub(entry.time) < GIT_CACHE_TIMEOUT
+        {
+            return match &entry.info {
+                Some(info) => &raw const **info,
+                None => null(),
+            };
+        }
+
+        if (*cache).len() >= GIT_CACHE_LIMIT {
+            (*cache).retain(|_, entry| now.saturating_sub(entry.time) < GIT_CACHE_TIMEOUT);
+            if (*cache).len() >= GIT_CACHE_LIMIT {
+                (*cache).clear();
+            }
+        }
+
+        let info = git_read(Path::new(OsStr::from_bytes(&key))).map(Box::new);
+        log_debug!(
+            "{}: {} is {}a repository",
+            "git_get",
+            _s(path),
+            if info.is_some() { "" } else { "not " }
+        );
+        let entry = (*cache).entry(key).or_insert(git_cache_entry {
+            time: 0,
+            info: None,
+        });
+        entry.time = now;
+        entry.info = info;
+        match &entry.info {
+            Some(info) => &raw const **info,
+            None => null(),
+        }
+    }
+}
@@ -323,6 +323,10 @@ pub unsafe fn grid_create(sx: u32, sy: u32, hlimit: u32) -> *mut grid {
         (*gd).hsize = 0;
         (*gd).hlimit = hlimit;
 
+        (*gd).htrimmed = 0;
+        (*gd).markx = [0; GRID_MARKS];
+        (*gd).marky = [0; GRID_MARKS];
+
         if (*gd).sy != 0 {
             (*gd).linedata = xcalloc_::<grid_line>((*gd).sy as usize).as_ptr();
         } else {
@@ -394,6 +398,7 @@ pub unsafe fn grid_compare(ga: *mut grid, gb: *mut grid) -> c_int {
 unsafe fn grid_trim_history(gd: *mut grid, ny: c_uint) {
     unsafe {
         grid_free_lines(gd, 0, ny);
+        (*gd).htrimmed += ny as u64;
         libc::memmove(
             (*gd).linedata as *mut c_void,
             (*gd).linedata.add(ny as usize) as *const c_void,
@@ -468,6 +473,44 @@ pub unsafe fn grid_clear_history(gd: *mut grid) {
     }
 }
 
+/// Set a named mark, moving it off the line which held it before.
+pub unsafe fn grid_set_mark(gd: *mut grid, mark: u32, px: u32, py: u32) {
+    unsafe {
+        if let Some((_, oy)) = grid_get_mark(gd, mark) {
+            (*grid_get_line(gd, oy)).marks &= !(1 << mark);
+        }
+        (*grid_get_line(gd, py)).marks |= 1 << mark;
+        (*gd).markx[mark as usize] = px;
+        (*gd).marky[mark as usize] = (*gd).htrimmed + py as u64;
+    }
+}
+
+/// Find a named mark, if its line is still in the grid. The line it was last
+/// found on is tried first; only if the lines have been rearranged since is
+/// the grid searched for it.
+pub unsafe fn grid_get_mark(gd: *mut grid, mark: u32) -> Option<(u32, u32)> {
+    unsafe {
+        let px = (*gd).markx[mark as usize];
+        let ay = (*gd).marky[mark as usize];
+        if ay >= (*gd).htrimmed {
+            let yy = ay - (*gd).htrimmed;
+            if yy < ((*gd).hsize + (*gd).sy) as u64
+                && (*grid_peek_line(gd, yy as u32)).marks & (1 << mark) != 0
+            {
+                return Some((px, yy as u32));
+            }
+        }
+
+        for yy in 0..(*gd).hsize + (*gd).sy {
+            if (*grid_peek_line(gd, yy)).marks & (1 << mark) != 0 {
+                (*gd).marky[mark as usize] = (*gd).htrimmed + yy as u64;
+                return Some((px, yy));
+            }
+        }
+        None
+    }
+}
+
 /// Scroll a region up, moving the top line into the history.
 pub unsafe fn grid_scroll_history_region(
     gd: *mut grid,
@@ -1497,6 +1540,7 @@ unsafe fn grid_reflow_join(
 
         // Remove lines that were completely consumed
         for i in (yy + 1)..(yy + 1 + lines) {
+            (*gl).marks |= (*(*gd).linedata.add(i as usize)).marks;
             free((*(*gd).linedata.add(i as usize)).celldata.cast());
             free((*(*gd).linedata.add(i as usize)).extddata.cast());
             grid_reflow_dead((*gd).linedata.add(i as usize));
@@ -0,0 +1,541 @@
+use crate::*;
+
+use std::io::Write as _;
+
+/// Names of export formats, in the same order as grid_export_format.
+static GRID_EXPORT_NAMES: [&str; 6] = ["text", "ansi", "html", "rtf", "svg", "json"];
+
+/// Size of a cell in SVG output, for a font size of 15 pixels.
+const GRID_EXPORT_SVG_WIDTH: u32 = 9;
+const GRID_EXPORT_SVG_HEIGHT: u32 = 18;
+
+/// Serialize grid cells with their colours and attributes.
+///
+/// Cells are added one at a time; newlines are held back until the next cell
+/// so that a trailing newline can still be dropped before the output is
+/// finished.
+pub struct grid_export {
+    format: grid_export_format,
+    palette: *const colour_palette,
+    sc: *mut screen,
+
+    buf: Vec<u8>,
+    newlines: u32,
+
+    x: u32,
+    y: u32,
+    columns: u32,
+    open: bool,
+
+    last: grid_cell,
+    has_link: c_int,
+
+    style: String,
+    colours: Vec<i32>,
+}
+
+pub fn grid_export_find(name: *const u8) -> Option<grid_export_format> {
+    if name.is_null() {
+        return None;
+    }
+    let name = unsafe { cstr_to_str(name) };
+    GRID_EXPORT_NAMES
+        .iter()
+        .position(|n| *n == name)
+        .and_then(|i| grid_export_format::try_from(i as i32).ok())
+}
+
+/// Start exporting. The palette and screen are optional and are used for
+/// colour overrides and hyperlinks.
+pub fn grid_export_create(
+    format: grid_export_format,
+    palette: *const colour_palette,
+    sc: *mut screen,
+) -> grid_export {
+    grid_export {
+        format,
+        palette,
+        sc,
+        buf: Vec::new(),
+        newlines: 0,
+        x: 0,
+        y: 0,
+        columns: 0,
+        open: false,
+        last: GRID_DEFAULT_CELL,
+        has_link: 0,
+        style: String::new(),
+        colours: Vec::new(),
+    }
+}
+
+/// Resolve a cell colour to RGB, or None for the default colour.
+unsafe fn grid_export_rgb(ge: &grid_export, mut c: i32) -> Option<i32> {
+    unsafe {
+        if COLOUR_DEFAULT(c) {
+            return None;
+        }
+        let p = colour_palette_get(ge.palette, c);
+        if p != -1 {
+            c = p;
+        }
+        match colour_force_rgb(c) {
+            -1 => None,
+            rgb => Some(rgb),
+        }
+    }
+}
+
+/// Work out the foreground and background, swapping them for reverse.
+unsafe fn grid_export_colours(ge: &grid_export, gc: &grid_cell) -> (Option<i32>, Option<i32>) {
+    unsafe {
+        let fg = grid_export_rgb(ge, gc.fg);
+        let bg = grid_export_rgb(ge, gc.bg);
+        if gc.attr.intersects(grid_attr::GRID_ATTR_REVERSE) {
+            (bg, fg)
+        } else {
+            (fg, bg)
+        }
+    }
+}
+
+fn grid_export_html_style(ge: &grid_export, gc: &grid_cell) -> String {
+    let mut style = String::new();
+    let attr = gc.attr;
+
+    let (fg, bg) = unsafe { grid_export_colours(ge, gc) };
+    if let Some(fg) = fg {
+        let (r, g, b) = colour_split_rgb(fg);
+        style.push_str(&format!("color:#{r:02x}{g:02x}{b:02x};"));
+    } else if attr.intersects(grid_attr::GRID_ATTR_REVERSE) {
+        style.push_str("color:Canvas;");
+    }
+    if let Some(bg) = bg {
+        let (r, g, b) = colour_split_rgb(bg);
+        style.push_str(&format!("background-color:#{r:02x}{g:02x}{b:02x};"));
+    } else if attr.intersects(grid_attr::GRID_ATTR_REVERSE) {
+        style.push_str("background-color:CanvasText;");
+    }
+
+    if attr.intersects(grid_attr::GRID_ATTR_BRIGHT) {
+        style.push_str("font-weight:bold;");
+    }
+    if attr.intersects(grid_attr::GRID_ATTR_DIM) {
+        style.push_str("opacity:0.5;");
+    }
+    if attr.intersects(grid_attr::GRID_ATTR_ITALICS) {
+        style.push_str("font-style:italic;");
+    }
+    if attr.intersects(grid_attr::GRID_ATTR_HIDDEN) {
+        style.push_str("visibility:hidden;");
+    }
+
+    let mut decoration = Vec::new();
+    if attr.intersects(GRID_ATTR_ALL_UNDERSCORE) {
+        decoration.push("underline");
+    }
+    if attr.intersects(grid_attr::GRID_ATTR_STRIKETHROUGH) {
+        decoration.push("line-through");
+    }
+    if attr.intersects(grid_attr::GRID_ATTR_OVERLINE) {
+        decoration.push("overline");
+    }
+    if !decoration.is_empty() {
+        style.push_str(&format!("text-decoration:{};", decoration.join(" ")));
+        if attr.intersects(grid_attr::GRID_ATTR_UNDERSCORE_2) {
+            style.push_str("text-decoration-style:double;");
+        } else if attr.intersects(grid_attr::GRID_ATTR_UNDERSCORE_3) {
+            style.push_str("text-decoration-style:wavy;");
+        } else if attr.intersects(grid_attr::GRID_ATTR_UNDERSCORE_4) {
+            style.push_str("text-decoration-style:dotted;");
+        } else if attr.intersects(grid_attr::GRID_ATTR_UNDERSCORE_5) {
+            style.push_str("text-decoration-style:dashed;");
+        }
+        if let Some(us) = unsafe { grid_export_rgb(ge, gc.us) } {
+            let (r, g, b) = colour_split_rgb(us);
+            style.push_str(&format!("text-decoration-color:#{r:02x}{g:02x}{b:02x};"));
+        }
+    }
+
+    style
+}
+
+/// Find or add an RTF colour table entry. Entry zero is the default colour.
+fn grid_export_rtf_colour(ge: &mut grid_export, c: i32) -> usize {
+    match ge.colours.iter().position(|&x| x == c) {
+        Some(i) => i + 1,
+        None => {
+            ge.colours.push(c);
+            ge.colours.len()
+        }
+    }
+}
+
+fn grid_export_rtf_style(ge: &mut grid_export, gc: &grid_cell) -> String {
+    let mut style = String::from("\\plain\\f0\\fs20");
+    let attr = gc.attr;
+
+    let (mut fg, mut bg) = unsafe { grid_export_colours(ge, gc) };
+    if attr.intersects(grid_attr::GRID_ATTR_REVERSE) {
+        fg = fg.or(Some(colour_join_rgb(0xff, 0xff, 0xff)));
+        bg = bg.or(Some(colour_join_rgb(0, 0, 0)));
+    }
+    if let Some(fg) = fg {
+        style.push_str(&format!("\\cf{}", grid_export_rtf_colour(ge, fg)));
+    }
+    if let Some(bg) = bg {
+        let n = grid_export_rtf_colour(ge, bg);
+        style.push_str(&format!("\\highlight{n}\\chcbpat{n}"));
+    }
+
+    if attr.intersects(grid_attr::GRID_ATTR_BRIGHT) {
+        style.push_str("\\b");
+    }
+    if attr.intersects(grid_attr::GRID_ATTR_ITALICS) {
+        style.push_str("\\i");
+    }
+    if attr.intersects(grid_attr::GRID_ATTR_UNDERSCORE_2) {
+        style.push_str("\\uldb");
+    } else if attr.intersects(grid_attr::GRID_ATTR_UNDERSCORE_3) {
+        style.push_str("\\ulwave");
+    } else if attr.intersects(grid_attr::GRID_ATTR_UNDERSCORE_4) {
+        style.push_str("\\uld");
+    } else if attr.intersects(grid_attr::GRID_ATTR_UNDERSCORE_5) {
+        style.push_str("\\uldash");
+    } else if attr.intersects(grid_attr::GRID_ATTR_UNDERSCORE) {
+        style.push_str("\\ul");
+    }
+    if attr.intersects(grid_attr::GRID_ATTR_STRIKETHROUGH) {
+        style.push_str("\\strike");
+    }
+    if attr.intersects(grid_attr::GRID_ATTR_HIDDEN) {
+        style.push_str("\\v");
+    }
+
+    style
+}
+
+fn grid_export_escape_xml(buf: &mut Vec<u8>, data: &[u8]) {
+    for &ch in data {
+        match ch {
+            b'&' => buf.extend_from_slice(b"&amp;"),
+            b'<' => buf.extend_from_slice(b"&lt;"),
+            b'>' => buf.extend_from_slice(b"&gt;"),
+            b'"' => buf.extend_from_slice(b"&quot;"),
+            _ => buf.push(ch),
+        }
+    }
+}
+
+fn grid_export_escape_json(buf: &mut Vec<u8>, data: &[u8]) {
+    buf.push(b'"');
+    for &ch in data {
+        match ch {
+            b'"' => buf.extend_from_slice(b"\\\""),
+            b'\\' => buf.extend_from_slice(b"\\\\"),
+            0..0x20 | 0x7f => {
+                let _ = write!(buf, "\\u{ch:04x}");
+            }
+            _ => buf.push(ch),
+        }
+    }
+    buf.push(b'"');
+}
+
+/// Colour for SVG output. Default colours are drawn as white on black.
+unsafe fn grid_export_svg_colour(ge: &grid_export, c: i32, default: i32) -> String {
+    unsafe {
+        let (r, g, b) =
+            colour_split_rgb(grid_export_rgb(ge, c).unwrap_or_else(|| colour_force_rgb(default)));
+        format!("#{r:02x}{g:02x}{b:02x}")
+    }
+}
+
+unsafe fn grid_export_svg_cell(ge: &mut grid_export, gc: &grid_cell, data: &[u8]) {
+    unsafe {
+        let attr = gc.attr;
+        let px = ge.x * GRID_EXPORT_SVG_WIDTH;
+        let py = ge.y * GRID_EXPORT_SVG_HEIGHT;
+        let width = gc.data.width.max(1) as u32 * GRID_EXPORT_SVG_WIDTH;
+
+        let (fg, bg) = if attr.intersects(grid_attr::GRID_ATTR_REVERSE) {
+            (
+                grid_export_svg_colour(ge, gc.bg, 0),
+                Some(grid_export_svg_colour(ge, gc.fg, 7)),
+            )
+        } else {
+            (
+                grid_export_svg_colour(ge, gc.fg, 7),
+                grid_export_rgb(ge, gc.bg).map(|_| grid_export_svg_colour(ge, gc.bg, 0)),
+            )
+        };
+        if let Some(bg) = bg {
+            let _ = writeln!(
+                ge.buf,
+                "<rect x=\"{px}\" y=\"{py}\" width=\"{width}\" height=\"{GRID_EXPORT_SVG_HEIGHT}\" fill=\"{bg}\"/>",
+            );
+        }
+
+        if data == b" " || attr.intersects(grid_attr::GRID_ATTR_HIDDEN) {
+            return;
+        }
+        let _ = write!(
+            ge.buf,
+            "<text x=\"{px}\" y=\"{}\" fill=\"{fg}\"",
+            py + GRID_EXPORT_SVG_HEIGHT - 4
+        );
+        if attr.intersects(grid_attr::GRID_ATTR_BRIGHT) {
+            ge.buf.extend_from_slice(b" font-weight=\"bold\"");
+        }
+        if attr.intersects(grid_attr::GRID_ATTR_ITALICS) {
+            ge.buf.extend_from_slice(b" font-style=\"italic\"");
+        }
+        if attr.intersects(grid_attr::GRID_ATTR_DIM) {
+            ge.buf.extend_from_slice(b" opacity=\"0.5\"");
+        }
+        let mut decoration = Vec::new();
+        if attr.intersects(GRID_ATTR_ALL_UNDERSCORE) {
+            decoration.push("underline");
+        }
+        if attr.intersects(grid_attr::GRID_ATTR_STRIKETHROUGH) {
+            decoration.push("line-through");
+        }
+        if attr.intersects(grid_attr::GRID_ATTR_OVERLINE) {
+            decoration.push("overline");
+        }
+        if !decoration.is_empty() {
+            let _ = write!(ge.buf, " text-decoration=\"{}\"", decoration.join(" "));
+        }
+        ge.buf.push(b'>');
+        grid_export_escape_xml(&mut ge.buf, data);
+        ge.buf.extend_from_slice(b"</text>\n");
+    }
+}
+
+unsafe fn grid_export_json_cell(ge: &mut grid_export, gc: &grid_cell, data: &[u8]) {
+    unsafe {
+        let mut uri: *const u8 = null();
+
+        if ge.open {
+            ge.buf.push(b',');
+        } else {
+            grid_export_json_row(ge);
+        }
+
+        let _ = write!(ge.buf, "{{\"x\":{},\"text\":", ge.x);
+        grid_export_escape_json(&mut ge.buf, data);
+        let _ = write!(
+            ge.buf,
+            ",\"width\":{},\"fg\":\"{}\",\"bg\":\"{}\",\"us\":\"{}\",\"attributes\":\"{}\"",
+            gc.data.width,
+            _s(colour_tostring(gc.fg)),
+            _s(colour_tostring(gc.bg)),
+            _s(colour_tostring(gc.us)),
+            _s(attributes_tostring(gc.attr)),
+        );
+        if gc.link != 0
+            && !ge.sc.is_null()
+            && !(*ge.sc).hyperlinks.is_null()
+            && hyperlinks_get(
+                (*ge.sc).hyperlinks,
+                gc.link,
+                &raw mut uri,
+                null_mut(),
+                null_mut(),
+            )
+        {
+            ge.buf.extend_from_slice(b",\"link\":");
+            grid_export_escape_json(&mut ge.buf, std::ffi::CStr::from_ptr(uri.cast()).to_bytes());
+        }
+        ge.buf.push(b'}');
+    }
+}
+
+/// Start a row of JSON cells.
+fn grid_export_json_row(ge: &mut grid_export) {
+    if ge.y != 0 {
+        ge.buf.extend_from_slice(b",\n");
+    }
+    ge.buf.push(b'[');
+    ge.open = true;
+}
+
+/// Write any held back newlines.
+fn grid_export_flush(ge: &mut grid_export) {
+    while ge.newlines != 0 {
+        match ge.format {
+            grid_export_format::GRID_EXPORT_RTF => ge.buf.extend_from_slice(b"\\line\n"),
+            grid_export_format::GRID_EXPORT_SVG => {}
+            grid_export_format::GRID_EXPORT_JSON => {
+                if !ge.open {
+                    grid_export_json_row(ge);
+                }
+                ge.buf.push(b']');
+                ge.open = false;
+            }
+            _ => ge.buf.push(b'\n'),
+        }
+        ge.x = 0;
+        ge.y += 1;
+        ge.newlines -= 1;
+    }
+}
+
+/// Add a cell. Padding cells should be skipped by the caller.
+pub unsafe fn grid_export_cell(ge: &mut grid_export, gc: &grid_cell) {
+    unsafe {
+        let data = &gc.data.data[..gc.data.size as usize];
+
+        grid_export_flush(ge);
+        match ge.format {
+            grid_export_format::GRID_EXPORT_TEXT => ge.buf.extend_from_slice(data),
+            grid_export_format::GRID_EXPORT_ANSI => {
+                let mut code: [u8; 8192] = [0; 8192];
+                grid_string_cells_code(
+                    &ge.last,
+                    gc,
+                    code.as_mut_ptr(),
+                    code.len(),
+                    grid_string_flags::empty(),
+                    ge.sc,
+                    &raw mut ge.has_link,
+                );
+                ge.buf.extend_from_slice(&code[..strlen(code.as_ptr())]);
+                ge.last = *gc;
+                ge.buf.extend_from_slice(data);
+            }
+            grid_export_format::GRID_EXPORT_HTML => {
+                let style = grid_export_html_style(ge, gc);
+                if style != ge.style {
+                    if !ge.style.is_empty() {
+                        ge.buf.extend_from_slice(b"</span>");
+                    }
+                    if !style.is_empty() {
+                        let _ = write!(ge.buf, "<span style=\"{style}\">");
+                    }
+                    ge.style = style;
+                }
+                grid_export_escape_xml(&mut ge.buf, data);
+            }
+            grid_export_format::GRID_EXPORT_RTF => {
+                let style = grid_export_rtf_style(ge, gc);
+                if style != ge.style {
+                    let _ = write!(ge.buf, "{style} ");
+                    ge.style = style;
+                }
+                for ch in String::from_utf8_lossy(data).chars() {
+                    match ch {
+                        '\\' | '{' | '}' => {
+                            let _ = write!(ge.buf, "\\{ch}");
+                        }
+                        ' '..='~' => ge.buf.push(ch as u8),
+                        _ => {
+                            let mut units = [0u16; 2];
+                            for unit in ch.encode_utf16(&mut units) {
+                                let _ = write!(ge.buf, "\\u{}?", *unit as i16);
+                            }
+                        }
+                    }
+                }
+            }
+            grid_export_format::GRID_EXPORT_SVG => grid_export_svg_cell(ge, gc, data),
+            grid_export_format::GRID_EXPORT_JSON => grid_export_json_cell(ge, gc, data),
+        }
+        ge.x += gc.data.width.max(1) as u32;
+        ge.columns = ge.columns.max(ge.x);
+    }
+}
+
+/// End a line.
+pub fn grid_export_newline(ge: &mut grid_export) {
+    ge.newlines += 1;
+}
+
+/// Drop the last newline if it has not been written yet.
+pub fn grid_export_trim_newline(ge: &mut grid_export) {
+    if ge.newlines != 0 {
+        ge.newlines -= 1;
+    }
+}
+
+/// Finish exporting and return the output as an allocated buffer.
+pub unsafe fn grid_export_finish(mut ge: grid_export, len: *mut usize) -> *mut u8 {
+    unsafe {
+        let mut out: Vec<u8> = Vec::new();
+
+        match ge.format {
+            grid_export_format::GRID_EXPORT_ANSI => {
+                let mut code: [u8; 8192] = [0; 8192];
+                grid_string_cells_code(
+                    &ge.last,
+                    &GRID_DEFAULT_CELL,
+                    code.as_mut_ptr(),
+                    code.len(),
+                    grid_string_flags::empty(),
+                    ge.sc,
+                    &raw mut ge.has_link,
+                );
+                ge.buf.extend_from_slice(&code[..strlen(code.as_ptr())]);
+            }
+            grid_export_format::GRID_EXPORT_HTML if !ge.style.is_empty() => {
+                ge.buf.extend_from_slice(b"</span>");
+            }
+            _ => {}
+        }
+        grid_export_flush(&mut ge);
+
+        match ge.format {
+            grid_export_format::GRID_EXPORT_TEXT | grid_export_format::GRID_EXPORT_ANSI => {}
+            grid_export_format::GRID_EXPORT_HTML => {
+                out.extend_from_slice(
+                    b"<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n</head>\n<body>\n<pre style=\"font-family:monospace\">",
+                );
+            }
+            grid_export_format::GRID_EXPORT_RTF => {
+                out.extend_from_slice(
+                    b"{\\rtf1\\ansi\\deff0{\\fonttbl{\\f0\\fmodern Courier New;}}\n{\\colortbl;",
+                );
+                for &c in &ge.colours {
+                    let (r, g, b) = colour_split_rgb(c);
+                    let _ = write!(out, "\\red{r}\\green{g}\\blue{b};");
+                }
+                out.extend_from_slice(b"}\n");
+            }
+            grid_export_format::GRID_EXPORT_SVG => {
+                let _ = writeln!(
+                    out,
+                    "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{}\" height=\"{}\" font-family=\"monospace\" font-size=\"15\" xml:space=\"preserve\">",
+                    ge.columns * GRID_EXPORT_SVG_WIDTH,
+                    (ge.y + u32::from(ge.x != 0)) * GRID_EXPORT_SVG_HEIGHT,
+                );
+                let _ = writeln!(
+                    out,
+                    "<rect width=\"100%\" height=\"100%\" fill=\"{}\"/>",
+                    grid_export_svg_colour(&ge, 8, 0),
+                );
+            }
+            grid_export_format::GRID_EXPORT_JSON => {
+                if ge.open {
+                    ge.buf.push(b']');
+                }
+                out.extend_from_slice(b"[\n");
+            }
+        }
+        out.append(&mut ge.buf);
+        match ge.format {
+            grid_export_format::GRID_EXPORT_HTML => {
+                out.extend_from_slice(b"</pre>\n</body>\n</html>\n")
+            }
+            grid_export_format::GRID_EXPORT_RTF => out.extend_from_slice(b"}\n"),
+            grid_export_format::GRID_EXPORT_SVG => out.extend_from_slice(b"</svg>\n"),
+            grid_export_format::GRID_EXPORT_JSON => out.extend_from_slice(b"\n]\n"),
+            _ => {}
+        }
+
+        *len = out.len();
+        let buf: *mut u8 = xmalloc(out.len() + 1).as_ptr().cast();
+        std::ptr::copy_nonoverlapping(out.as_ptr(), buf, out.len());
+        *buf.add(out.len()) = b'\0';
+        buf
+    }
+}
@@ -14,10 +14,10 @@
 use crate::*;
 
 use crate::libc::{
-    AF_UNIX, O_RDWR, PF_UNSPEC, SHUT_WR, SIG_BLOCK, SIG_SETMASK, SIGCONT, SIGTERM, SIGTTIN,
-    SIGTTOU, SOCK_STREAM, STDERR_FILENO, STDIN_FILENO, STDOUT_FILENO, TIOCSWINSZ, WIFSTOPPED,
-    WSTOPSIG, chdir, close, dup2, execl, execvp, fork, ioctl, kill, killpg, memset, open, setenv,
-    shutdown, sigfillset, sigprocmask, sigset_t, socketpair, winsize,
+    AF_UNIX, O_RDWR, PF_UNSPEC, SHUT_WR, SIG_BLOCK, SIG_SETMASK, SIGCONT, SIGKILL, SIGTERM,
+    SIGTTIN, SIGTTOU, SOCK_STREAM, STDERR_FILENO, STDIN_FILENO, STDOUT_FILENO, TIOCSWINSZ,
+    WIFSTOPPED, WSTOPSIG, chdir, close, dup2, execl, execvp, fork, ioctl, kill, killpg, memset,
+    open, pipe, setenv, setpgid, shutdown, sigfillset, sigprocmask, sigset_t, socketpair, winsize,
 };
 
 use crate::compat::{
@@ -56,6 +56,9 @@ pub struct job {
     pub fd: c_int,
     pub event: *mut bufferevent,
 
+    pub errfd: c_int,
+    pub errevent: *mut bufferevent,
+
     pub updatecb: job_update_cb,
     pub completecb: job_complete_cb,
     pub freecb: job_free_cb,
@@ -94,6 +97,7 @@ pub unsafe fn job_run(
         let pid: pid_t;
         let nullfd: i32;
         let mut out: [i32; 2] = [0; 2];
+        let mut err: [i32; 2] = [-1; 2];
         let mut master: i32 = 0;
         let mut home: *mut u8 = null_mut();
         let mut shell: *const u8 = null_mut();
@@ -145,6 +149,11 @@ pub unsafe fn job_run(
                 if socketpair(AF_UNIX, SOCK_STREAM, PF_UNSPEC, &raw mut out as *mut c_int) != 0 {
                     break 'fail;
                 }
+                if flags.intersects(job_flag::JOB_STDERR) && pipe(&raw mut err as *mut c_int) != 0 {
+                    close(out[0]);
+                    close(out[1]);
+                    break 'fail;
+                }
                 pid = fork();
             }
 
@@ -172,6 +181,10 @@ pub unsafe fn job_run(
                         close(out[0]);
                         close(out[1]);
                     }
+                    if err[0] != -1 {
+                        close(err[0]);
+                        close(err[1]);
+                    }
                     break 'fail;
                 }
                 0 => {
@@ -192,6 +205,10 @@ pub unsafe fn job_run(
                     environ_free(env);
 
                     if !flags.intersects(job_flag::JOB_PTY) {
+                        if flags.intersects(job_flag::JOB_PGROUP) {
+                            // Own process group so the whole job can be killed.
+                            setpgid(0, 0);
+                        }
                         if dup2(out[1], STDIN_FILENO) == -1 {
                             fatal("dup2 failed");
                         }
@@ -203,15 +220,21 @@ pub unsafe fn job_run(
                         }
                         close(out[0]);
 
-                        nullfd = open(_PATH_DEVNULL, O_RDWR, 0);
-                        if nullfd == -1 {
-                            fatal("open failed");
-                        }
-                        if dup2(nullfd, STDERR_FILENO) == -1 {
-                            fatal("dup2 failed");
-                        }
-                        if nullfd != STDERR_FILENO {
-                            close(nullfd);
+                        if err[1] != -1 {
+                            if dup2(err[1], STDERR_FILENO) == -1 {
+                                fatal("dup2 failed");
+                            }
+                        } else {
+                            nullfd = open(_PATH_DEVNULL, O_RDWR, 0);
+                            if nullfd == -1 {
+                                fatal("open failed");
+                            }
+                            if dup2(nullfd, STDERR_FILENO) == -1 {
+                                fatal("dup2 failed");
+                            }
+                            if nullfd != STDERR_FILENO {
+                                close(nullfd);
+                            }
                         }
                     }
                     closefrom(STDERR_FILENO + 1);
@@ -279,6 +302,26 @@ pub unsafe fn job_run(
             }
             bufferevent_enable((*job).event, EV_READ | EV_WRITE);
 
+            (*job).errfd = -1;
+            (*job).errevent = null_mut();
+            if err[0] != -1 {
+                close(err[1]);
+                (*job).errfd = err[0];
+                setblocking((*job).errfd, 0);
+
+                (*job).errevent = bufferevent_new(
+                    (*job).errfd,
+                    None,
+                    None,
+                    Some(job_stderr_error_callback),
+                    job as *mut c_void,
+                );
+                if (*job).errevent.is_null() {
+                    fatalx("out of memory");
+                }
+                bufferevent_enable((*job).errevent, EV_READ);
+            }
+
             log_debug!("run job {:p}: {} pid {}", job, _s((*job).cmd), (*job).pid);
             return job;
         }
@@ -315,6 +358,7 @@ pub unsafe fn job_transfer(job: *mut job, pid: *mut pid_t, tty: *mut u8, ttylen:
         if !(*job).event.is_null() {
             bufferevent_free((*job).event);
         }
+        job_free_error(job);
 
         free_(job);
         fd
@@ -342,6 +386,7 @@ pub unsafe fn job_free(job: *mut job) {
         if (*job).fd != -1 {
             close((*job).fd);
         }
+        job_free_error(job);
         free_(job);
     }
 }
@@ -364,6 +409,17 @@ pub unsafe fn job_resize(job: *mut job, sx: c_uint, sy: c_uint) {
     }
 }
 
+unsafe fn job_free_error(job: *mut job) {
+    unsafe {
+        if !(*job).errevent.is_null() {
+            bufferevent_free((*job).errevent);
+        }
+        if (*job).errfd != -1 {
+            close((*job).errfd);
+        }
+    }
+}
+
 unsafe extern "C" fn job_read_callback(bufev: *mut bufferevent, data: *mut c_void) {
     let job = data as *mut job;
 
@@ -419,6 +475,18 @@ unsafe extern "C" fn job_error_callback(
     }
 }
 
+unsafe extern "C" fn job_stderr_error_callback(
+    _bufev: *mut bufferevent,
+    _events: libc::c_short,
+    data: *mut c_void,
+) {
+    let job: *mut job = data.cast();
+
+    unsafe {
+        bufferevent_disable((*job).errevent, EV_READ);
+    }
+}
+
 pub unsafe fn job_check_died(pid: pid_t, status: i32) {
     unsafe {
         let mut job: *mut job = null_mut();
@@ -473,6 +541,64 @@ pub unsafe fn job_get_event(job: *mut job) -> *mut bufferevent {
     unsafe { (*job).event }
 }
 
+/// Get the buffered standard error of a job started with `JOB_STDERR`,
+/// including anything still waiting in the pipe.
+pub unsafe fn job_get_error_event(job: *mut job) -> *mut bufferevent {
+    unsafe {
+        if (*job).errevent.is_null() {
+            return null_mut();
+        }
+        while evbuffer_read((*(*job).errevent).input, (*job).errfd, -1) > 0 {}
+        (*job).errevent
+    }
+}
+
+pub unsafe fn job_get_pid(job: *mut job) -> pid_t {
+    unsafe { (*job).pid }
+}
+
+/// Time a job's process group has to exit after SIGTERM before SIGKILL.
+const JOB_KILL_GRACE: time_t = 5;
+
+struct job_kill {
+    timer: event,
+    pgid: pid_t,
+}
+
+unsafe extern "C" fn job_kill_timer(_fd: i32, _events: i16, arg: *mut c_void) {
+    unsafe {
+        let jk = arg as *mut job_kill;
+
+        if killpg((*jk).pgid, 0) == 0 {
+            log_debug!("job group {} still running, killing", (*jk).pgid);
+            killpg((*jk).pgid, SIGKILL);
+        }
+        drop(Box::from_raw(jk));
+    }
+}
+
+/// Send SIGTERM to the process group of a job started with JOB_PGROUP, and
+/// SIGKILL if anything in it is still running after a grace period.
+pub unsafe fn job_kill_group(job: *mut job) {
+    unsafe {
+        if (*job).pid == -1 || !(*job).flags.intersects(job_flag::JOB_PGROUP) {
+            return;
+        }
+        killpg((*job).pid, SIGTERM);
+
+        let jk = Box::into_raw(Box::new(job_kill {
+            timer: zeroed(),
+            pgid: (*job).pid,
+        }));
+        evtimer_set(&raw mut (*jk).timer, Some(job_kill_timer), jk.cast());
+        let tv = timeval {
+            tv_sec: JOB_KILL_GRACE,
+            tv_usec: 0,
+        };
+        evtimer_add(&raw mut (*jk).timer, &raw const tv);
+    }
+}
+
 pub unsafe fn job_kill_all() {
     unsafe {
         for job in list_foreach(&raw mut ALL_JOBS).map(NonNull::as_ptr) {
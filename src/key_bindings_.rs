@@ -361,7 +361,7 @@ pub unsafe fn key_bindings_init_done(_item: *mut cmdq_item, data: *mut c_void) -
 
 pub unsafe fn key_bindings_init() {
     #[rustfmt::skip]
-    static DEFAULTS: [&str; 262] = [
+    static DEFAULTS: [&str; 300] = [
         // Prefix keys.
         "bind -N 'Send the prefix key' C-b { send-prefix }",
         "bind -N 'Rotate through the panes' C-o { rotate-window }",
@@ -467,6 +467,8 @@ pub unsafe fn key_bindings_init() {
         "bind -n MouseDrag1Border { resize-pane -M }",
         /* Mouse button 1 down on status line. */
         "bind -n MouseDown1Status { select-window -t= }",
+        /* Mouse button 1 drag on status line. */
+        "bind -n MouseDrag1Status { move-window -M }",
         /* Mouse wheel down on status line. */
         "bind -n WheelDownStatus { next-window }",
         /* Mouse wheel up on status line. */
@@ -567,9 +569,12 @@ pub unsafe fn key_bindings_init() {
         "bind -Tcopy-mode-vi C-u { send -X halfpage-up }",
         "bind -Tcopy-mode-vi C-v { send -X rectangle-toggle }",
         "bind -Tcopy-mode-vi C-y { send -X scroll-up }",
+        "bind -Tcopy-mode-vi C-o { send -X previous-jump }",
+        "bind -Tcopy-mode-vi Tab { send -X next-jump }",
         "bind -Tcopy-mode-vi Escape { send -X clear-selection }",
         "bind -Tcopy-mode-vi Space { send -X begin-selection }",
         "bind -Tcopy-mode-vi '$' { send -X end-of-line }",
+        "bind -Tcopy-mode-vi \"'\" { command-prompt -1p'(jump to mark)' { send -X jump-to-mark '%%' } }",
         "bind -Tcopy-mode-vi , { send -X jump-reverse }",
         "bind -Tcopy-mode-vi / { command-prompt -T search -p'(search down)' { send -X search-forward '%%' } }",
         "bind -Tcopy-mode-vi 0 { send -X start-of-line }",
@@ -603,15 +608,19 @@ pub unsafe fn key_bindings_init() {
         "bind -Tcopy-mode-vi W { send -X next-space }",
         "bind -Tcopy-mode-vi X { send -X set-mark }",
         "bind -Tcopy-mode-vi ^ { send -X back-to-indentation }",
+        "bind -Tcopy-mode-vi \"`\" { command-prompt -1p'(jump to mark)' { send -X jump-to-mark '%%' } }",
+        "bind -Tcopy-mode-vi a { command-prompt -1p'(around object)' { send -X select-object 'a%%' } }",
         "bind -Tcopy-mode-vi b { send -X previous-word }",
         "bind -Tcopy-mode-vi e { send -X next-word-end }",
         "bind -Tcopy-mode-vi f { command-prompt -1p'(jump forward)' { send -X jump-forward '%%' } }",
         "bind -Tcopy-mode-vi g { send -X history-top }",
         "bind -Tcopy-mode-vi h { send -X cursor-left }",
+        "bind -Tcopy-mode-vi i { command-prompt -1p'(inner object)' { send -X select-object 'i%%' } }",
         "bind -Tcopy-mode-vi j { send -X cursor-down }",
         "bind -Tcopy-mode-vi k { send -X cursor-up }",
         "bind -Tcopy-mode-vi z { send -X scroll-middle }",
         "bind -Tcopy-mode-vi l { send -X cursor-right }",
+        "bind -Tcopy-mode-vi m { command-prompt -1p'(mark)' { send -X set-mark '%%' } }",
         "bind -Tcopy-mode-vi n { send -X search-again }",
         "bind -Tcopy-mode-vi o { send -X other-end }",
         "bind -Tcopy-mode-vi q { send -X cancel }",
@@ -641,6 +650,36 @@ pub unsafe fn key_bindings_init() {
         "bind -Tcopy-mode-vi M-x { send -X jump-to-mark }",
         "bind -Tcopy-mode-vi C-Up { send -X scroll-up }",
         "bind -Tcopy-mode-vi C-Down { send -X scroll-down }",
+        "bind -Tresize-mode Left { send -X resize-left }",
+        "bind -Tresize-mode h { send -X resize-left }",
+        "bind -Tresize-mode Right { send -X resize-right }",
+        "bind -Tresize-mode l { send -X resize-right }",
+        "bind -Tresize-mode Up { send -X resize-up }",
+        "bind -Tresize-mode k { send -X resize-up }",
+        "bind -Tresize-mode Down { send -X resize-down }",
+        "bind -Tresize-mode j { send -X resize-down }",
+        "bind -Tresize-mode S-Left { send -N5 -X resize-left }",
+        "bind -Tresize-mode H { send -N5 -X resize-left }",
+        "bind -Tresize-mode S-Right { send -N5 -X resize-right }",
+        "bind -Tresize-mode L { send -N5 -X resize-right }",
+        "bind -Tresize-mode S-Up { send -N5 -X resize-up }",
+        "bind -Tresize-mode K { send -N5 -X resize-up }",
+        "bind -Tresize-mode S-Down { send -N5 -X resize-down }",
+        "bind -Tresize-mode J { send -N5 -X resize-down }",
+        "bind -Tresize-mode 1 { send -X ratio 1 }",
+        "bind -Tresize-mode 2 { send -X ratio 2 }",
+        "bind -Tresize-mode 3 { send -X ratio 3 }",
+        "bind -Tresize-mode 4 { send -X ratio 4 }",
+        "bind -Tresize-mode 5 { send -X ratio 5 }",
+        "bind -Tresize-mode 6 { send -X ratio 6 }",
+        "bind -Tresize-mode 7 { send -X ratio 7 }",
+        "bind -Tresize-mode 8 { send -X ratio 8 }",
+        "bind -Tresize-mode 9 { send -X ratio 9 }",
+        "bind -Tresize-mode Tab { send -X switch-direction }",
+        "bind -Tresize-mode Enter { send -X accept }",
+        "bind -Tresize-mode Escape { send -X cancel }",
+        "bind -Tresize-mode q { send -X cancel }",
+        "bind -Tresize-mode C-c { send -X cancel }",
     ];
 
     unsafe {
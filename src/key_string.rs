@@ -234,6 +234,11 @@ static KEY_STRING_TABLE: [key_string_table_entry; 469] = const {
     out
 };
 
+/// Names of the keys in the table.
+pub fn key_string_names() -> impl Iterator<Item = *const u8> {
+    KEY_STRING_TABLE.iter().map(|key_string| key_string.string)
+}
+
 /// Find key string in table.
 pub unsafe fn key_string_search_table(string: *const u8) -> key_code {
     unsafe {
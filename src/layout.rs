@@ -35,6 +35,10 @@ pub unsafe fn layout_create_cell(lcparent: *mut layout_cell) -> *mut layout_cell
 
         (*lc).wp = null_mut();
 
+        (&raw mut (*lc).constraint_x).write(layout_constraint::default());
+        (&raw mut (*lc).constraint_y).write(layout_constraint::default());
+        (&raw mut (*lc).tabs).write(Vec::new());
+
         lc
     }
 }
@@ -53,9 +57,13 @@ pub unsafe fn layout_free_cell(lc: *mut layout_cell) {
                 if !(*lc).wp.is_null() {
                     (*(*lc).wp).layout_cell = null_mut();
                 }
+                for &wp in &(*lc).tabs {
+                    (*wp).layout_cell = null_mut();
+                }
             }
         }
 
+        std::ptr::drop_in_place(&raw mut (*lc).tabs);
         free_(lc);
     }
 }
@@ -299,29 +307,224 @@ pub unsafe fn layout_count_cells(lc: *mut layout_cell) -> u32 {
     }
 }
 
+/// Parse a size constraint: a single size for a fixed size, or a range
+/// `min-max` where either end may be omitted. Each size is a number of cells
+/// or a percentage of the window.
+pub fn layout_constraint_parse(s: &str) -> Result<layout_constraint, String> {
+    fn limit(s: &str) -> Result<layout_limit, String> {
+        if s.is_empty() {
+            return Ok(layout_limit::None);
+        }
+        let (digits, percent) = match s.strip_suffix('%') {
+            Some(digits) => (digits, true),
+            None => (s, false),
+        };
+        match digits.parse::<u32>() {
+            Ok(n) if percent && n <= 100 => Ok(layout_limit::Percent(n)),
+            Ok(n) if !percent && n <= WINDOW_MAXIMUM => Ok(layout_limit::Cells(n)),
+            _ => Err(format!("bad size: {s}")),
+        }
+    }
+
+    if s.is_empty() || s == "none" {
+        return Ok(layout_constraint::default());
+    }
+    match s.split_once('-') {
+        Some((min, max)) => {
+            let (min, max) = (limit(min)?, limit(max)?);
+            match (min, max) {
+                (layout_limit::Cells(a), layout_limit::Cells(b))
+                | (layout_limit::Percent(a), layout_limit::Percent(b))
+                    if a > b =>
+                {
+                    Err(format!("minimum is larger than maximum: {s}"))
+                }
+                _ => Ok(layout_constraint { min, max }),
+            }
+        }
+        None => {
+            let size = limit(s)?;
+            Ok(layout_constraint {
+                min: size,
+                max: size,
+            })
+        }
+    }
+}
+
+/// Print a size constraint in the form accepted by `layout_constraint_parse`.
+pub fn layout_constraint_string(lcon: &layout_constraint) -> String {
+    fn limit(limit: layout_limit) -> String {
+        match limit {
+            layout_limit::None => String::new(),
+            layout_limit::Cells(n) => format!("{n}"),
+            layout_limit::Percent(n) => format!("{n}%"),
+        }
+    }
+
+    if lcon.min == lcon.max {
+        return limit(lcon.min);
+    }
+    format!("{}-{}", limit(lcon.min), limit(lcon.max))
+}
+
+/// Get the size a limit allows given the size of the whole layout.
+fn layout_limit_size(limit: layout_limit, total: u32) -> Option<u32> {
+    match limit {
+        layout_limit::None => None,
+        layout_limit::Cells(n) => Some(n),
+        layout_limit::Percent(n) => Some(total * n / 100),
+    }
+}
+
+/// Check if a cell or any of its children has a size constraint.
+pub unsafe fn layout_cell_constrained(lc: *mut layout_cell) -> bool {
+    unsafe {
+        if (*lc).type_ == layout_type::LAYOUT_WINDOWPANE {
+            return (*lc).constraint_x != layout_constraint::default()
+                || (*lc).constraint_y != layout_constraint::default();
+        }
+        tailq_foreach(&raw mut (*lc).cells).any(|lcchild| layout_cell_constrained(lcchild.as_ptr()))
+    }
+}
+
+/// Size constraints of each pane in a window, saved before its layout is
+/// rebuilt so they can be given back to the new cells.
+pub type layout_constraints = Vec<(*mut window_pane, layout_constraint, layout_constraint)>;
+
+pub unsafe fn layout_constraints_save(w: *mut window) -> layout_constraints {
+    unsafe {
+        let mut saved = Vec::new();
+        for wp in tailq_foreach::<_, discr_entry>(&raw mut (*w).panes).map(NonNull::as_ptr) {
+            let lc = (*wp).layout_cell;
+            if !lc.is_null() && layout_cell_constrained(lc) {
+                saved.push((wp, (*lc).constraint_x, (*lc).constraint_y));
+            }
+        }
+        saved
+    }
+}
+
+/// Put saved constraints back on the panes' new cells and resize any pane
+/// the new layout has made too big or too small.
+pub unsafe fn layout_constraints_restore(w: *mut window, saved: layout_constraints) {
+    unsafe {
+        for (wp, constraint_x, constraint_y) in saved {
+            let lc = (*wp).layout_cell;
+            if lc.is_null() || (*wp).window != w {
+                continue;
+            }
+            (*lc).constraint_x = constraint_x;
+            (*lc).constraint_y = constraint_y;
+            layout_constraints_fit(wp);
+        }
+    }
+}
+
+/// Resize a pane if it is outside the limits of its constraints.
+pub unsafe fn layout_constraints_fit(wp: *mut window_pane) {
+    unsafe {
+        let w = (*wp).window;
+        let lc = (*wp).layout_cell;
+        for type_ in [layout_type::LAYOUT_LEFTRIGHT, layout_type::LAYOUT_TOPBOTTOM] {
+            let size = if type_ == layout_type::LAYOUT_LEFTRIGHT {
+                (*lc).sx
+            } else {
+                (*lc).sy
+            };
+            let (minimum, maximum) = layout_cell_limits(w, lc, type_);
+            if size < minimum || size > maximum {
+                layout_resize_pane_to(wp, type_, size.clamp(minimum, maximum));
+            }
+        }
+    }
+}
+
+/// Work out the smallest and largest size a cell may be in one direction.
+/// Percentages are of the layout root, which has already been given its new
+/// size when the window is resized.
+pub unsafe fn layout_cell_limits(
+    w: *mut window,
+    lc: *mut layout_cell,
+    type_: layout_type,
+) -> (u32, u32) {
+    unsafe {
+        if (*lc).type_ == layout_type::LAYOUT_WINDOWPANE {
+            let (lcon, total, border) = if type_ == layout_type::LAYOUT_LEFTRIGHT {
+                ((*lc).constraint_x, (*(*w).layout_root).sx, 0)
+            } else {
+                let status = pane_status::try_from(options_get_number_(
+                    (*w).options,
+                    c"pane-border-status",
+                ) as i32)
+                .unwrap();
+                let border = layout_add_border(w, lc, status) as u32;
+                ((*lc).constraint_y, (*(*w).layout_root).sy, border)
+            };
+
+            let mut minimum = PANE_MINIMUM;
+            if let Some(size) = layout_limit_size(lcon.min, total) {
+                minimum = minimum.max(size);
+            }
+            let maximum = match layout_limit_size(lcon.max, total) {
+                Some(size) => size.max(minimum) + border,
+                None => u32::MAX,
+            };
+            return (minimum + border, maximum);
+        }
+
+        let mut minimum = 0u32;
+        let mut maximum = 0u32;
+        if (*lc).type_ == type_ {
+            /* Same type: total of child cells and the borders between them. */
+            for lcchild in tailq_foreach(&raw mut (*lc).cells) {
+                let (min, max) = layout_cell_limits(w, lcchild.as_ptr(), type_);
+                if minimum != 0 {
+                    minimum += 1;
+                    maximum = maximum.saturating_add(1);
+                }
+                minimum += min;
+                maximum = maximum.saturating_add(max);
+            }
+        } else {
+            /* Different type: the tightest of the child cells. */
+            maximum = u32::MAX;
+            for lcchild in tailq_foreach(&raw mut (*lc).cells) {
+                let (min, max) = layout_cell_limits(w, lcchild.as_ptr(), type_);
+                minimum = minimum.max(min);
+                maximum = maximum.min(max);
+            }
+        }
+        (minimum, maximum.max(minimum))
+    }
+}
+
+/// Calculate how much a cell may grow before reaching its maximum size.
+pub unsafe fn layout_grow_check(w: *mut window, lc: *mut layout_cell, type_: layout_type) -> u32 {
+    unsafe {
+        let size = if type_ == layout_type::LAYOUT_LEFTRIGHT {
+            (*lc).sx
+        } else {
+            (*lc).sy
+        };
+        layout_cell_limits(w, lc, type_).1.saturating_sub(size)
+    }
+}
+
 /// Calculate how much size is available to be removed from a cell.
 pub unsafe fn layout_resize_check(w: *mut window, lc: *mut layout_cell, type_: layout_type) -> u32 {
     unsafe {
         let mut available: u32;
         let mut minimum: u32;
 
-        let status: pane_status =
-            pane_status::try_from(options_get_number_((*w).options, c"pane-border-status") as i32)
-                .unwrap();
-
         if (*lc).type_ == layout_type::LAYOUT_WINDOWPANE {
             /* Space available in this cell only. */
             if type_ == layout_type::LAYOUT_LEFTRIGHT {
                 available = (*lc).sx;
-                minimum = PANE_MINIMUM;
             } else {
                 available = (*lc).sy;
-                if layout_add_border(w, lc, status) {
-                    minimum = PANE_MINIMUM + 1;
-                } else {
-                    minimum = PANE_MINIMUM;
-                }
             }
+            minimum = layout_cell_limits(w, lc, type_).0;
             if available > minimum {
                 available -= minimum;
             } else {
@@ -378,14 +581,45 @@ pub unsafe fn layout_resize_adjust(
             return;
         }
 
-        // Child cell runs in the same direction. Adjust each child equally
-        // until no further change is possible
+        // Child cell runs in the same direction. Constrained children are
+        // brought back within their limits first
+        if layout_cell_constrained(lc) {
+            for lcchild in tailq_foreach(&raw mut (*lc).cells).map(NonNull::as_ptr) {
+                if change == 0 {
+                    break;
+                }
+                let size = if type_ == layout_type::LAYOUT_LEFTRIGHT {
+                    (*lcchild).sx
+                } else {
+                    (*lcchild).sy
+                } as i32;
+                let (minimum, maximum) = layout_cell_limits(w, lcchild, type_);
+                if change > 0 && size < minimum as i32 {
+                    let n = (minimum as i32 - size).min(change);
+                    layout_resize_adjust(w, lcchild, type_, n);
+                    change -= n;
+                } else if change < 0 && maximum != u32::MAX && size > maximum as i32 {
+                    let n = (size - maximum as i32).min(-change);
+                    layout_resize_adjust(w, lcchild, type_, -n);
+                    change += n;
+                }
+            }
+        }
+
+        // Then adjust each child equally until no further change is possible,
+        // not growing children past their maximum unless all are there
         while change != 0 {
+            let grow = change > 0
+                && tailq_foreach(&raw mut (*lc).cells)
+                    .any(|lcchild| layout_grow_check(w, lcchild.as_ptr(), type_) > 0);
             for lcchild in tailq_foreach(&raw mut (*lc).cells) {
                 if change == 0 {
                     break;
                 }
                 if change > 0 {
+                    if grow && layout_grow_check(w, lcchild.as_ptr(), type_) == 0 {
+                        continue;
+                    }
                     layout_resize_adjust(w, lcchild.as_ptr(), type_, 1);
                     change -= 1;
                     continue;
@@ -465,6 +699,42 @@ pub unsafe fn layout_init(w: *mut window, wp: *mut window_pane) {
     }
 }
 
+/// Make a layout with a row or column of panes, for zooming several panes.
+/// Returns false if the window is too small for them.
+pub unsafe fn layout_init_panes(
+    w: *mut window,
+    panes: &[*mut window_pane],
+    type_: layout_type,
+) -> bool {
+    unsafe {
+        let size = if type_ == layout_type::LAYOUT_LEFTRIGHT {
+            (*w).sx
+        } else {
+            (*w).sy
+        };
+        if panes.len() as u32 * (PANE_MINIMUM + 1) - 1 > size {
+            return false;
+        }
+
+        let lc = layout_create_cell(std::ptr::null_mut());
+        (*w).layout_root = lc;
+        layout_set_size(lc, (*w).sx, (*w).sy, 0, 0);
+        layout_make_node(lc, type_);
+
+        for &wp in panes {
+            let lcnew = layout_create_cell(lc);
+            layout_make_leaf(lcnew, wp);
+            (*lcnew).sx = (*w).sx;
+            (*lcnew).sy = (*w).sy;
+            tailq_insert_tail(&raw mut (*lc).cells, lcnew);
+        }
+        layout_spread_cell(w, lc);
+        layout_fix_offsets(w);
+        layout_fix_panes(w, std::ptr::null_mut());
+        true
+    }
+}
+
 pub unsafe fn layout_free(w: *mut window) {
     unsafe {
         layout_free_cell((*w).layout_root);
@@ -664,10 +934,11 @@ pub unsafe fn layout_resize_pane_grow(
             return 0;
         }
 
-        // Change the cells
+        // Change the cells, not growing past the maximum size
         if size > needed as u32 {
             size = needed as u32;
         }
+        size = size.min(layout_grow_check(w, lcadd, type_));
         layout_resize_adjust(w, lcadd, type_, size as c_int);
         layout_resize_adjust(w, lcremove, type_, -(size as c_int));
         size as c_int
@@ -706,10 +977,11 @@ pub unsafe fn layout_resize_pane_shrink(
             return 0;
         }
 
-        // Change the cells
+        // Change the cells, not growing past the maximum size
         if size > (-needed) as u32 {
             size = (-needed) as u32;
         }
+        size = size.min(layout_grow_check(w, lcadd, type_));
         layout_resize_adjust(w, lcadd, type_, size as c_int);
         layout_resize_adjust(w, lcremove, type_, -(size as c_int));
         size as c_int
@@ -994,6 +1266,22 @@ pub unsafe fn layout_split_pane(
         } else if size2 > saved_size - 2 {
             size2 = saved_size - 2;
         }
+
+        // Keep the pane being split within its size constraints.
+        if !full_size && layout_cell_constrained(lc) {
+            let (minimum, maximum) = layout_cell_limits((*wp).window, lc, type_);
+            let before = (flags & SPAWN_BEFORE) != 0;
+            let mut keep = if before {
+                size2
+            } else {
+                saved_size - 1 - size2
+            };
+            keep = keep
+                .max(minimum)
+                .min(maximum)
+                .clamp(PANE_MINIMUM, saved_size - 2);
+            size2 = if before { keep } else { saved_size - 1 - keep };
+        }
         let size1 = saved_size - 1 - size2;
 
         // Which size are we using?
@@ -1097,7 +1385,7 @@ pub unsafe fn layout_split_pane(
                 layout_resize_child_cells((*wp).window, lc);
             }
             layout_fix_offsets((*wp).window);
-        } else {
+        } else if !layout_stack_hidden(wp) {
             layout_make_leaf(lc, wp);
         }
 
@@ -1110,8 +1398,10 @@ pub unsafe fn layout_close_pane(wp: *mut window_pane) {
     unsafe {
         let w = (*wp).window;
 
-        // Remove the cell
-        layout_destroy_cell(w, (*wp).layout_cell, &raw mut (*w).layout_root);
+        // Remove the pane from its stack or, if not stacked, remove the cell
+        if !layout_stack_remove(wp) {
+            layout_destroy_cell(w, (*wp).layout_cell, &raw mut (*w).layout_root);
+        }
 
         // Fix pane offsets and sizes
         if !(*w).layout_root.is_null() {
@@ -1151,6 +1441,9 @@ pub unsafe fn layout_spread_cell(w: *mut window, parent: *mut layout_cell) -> c_
         if size < number - 1 {
             return 0;
         }
+        if layout_cell_constrained(parent) {
+            return layout_spread_constrained(w, parent, size - (number - 1), status);
+        }
 
         let mut each = (size - (number - 1)) / number;
         if each == 0 {
@@ -1193,6 +1486,91 @@ pub unsafe fn layout_spread_cell(w: *mut window, parent: *mut layout_cell) -> c_
     }
 }
 
+/// Spread cells as evenly as their size constraints allow. available is the
+/// space left once the borders between the cells are removed.
+unsafe fn layout_spread_constrained(
+    w: *mut window,
+    parent: *mut layout_cell,
+    available: u32,
+    status: pane_status,
+) -> c_int {
+    unsafe {
+        let type_ = (*parent).type_;
+        let cells: Vec<*mut layout_cell> = tailq_foreach(&raw mut (*parent).cells)
+            .map(NonNull::as_ptr)
+            .collect();
+
+        // Work out the limits of each cell without its border line.
+        let mut limits = Vec::with_capacity(cells.len());
+        let mut size = available;
+        for &lc in &cells {
+            let border = if type_ == layout_type::LAYOUT_TOPBOTTOM {
+                layout_add_border(w, lc, status) as u32
+            } else {
+                0
+            };
+            let (minimum, maximum) = layout_cell_limits(w, lc, type_);
+            size = size.saturating_sub(border);
+            limits.push((border, minimum - border, maximum.saturating_sub(border)));
+        }
+
+        // Fix any cell whose even share is outside its limits and share out
+        // what is left among the others until none change.
+        let mut sizes: Vec<Option<u32>> = vec![None; cells.len()];
+        loop {
+            let fixed: u32 = sizes.iter().flatten().sum();
+            let free = sizes.iter().filter(|s| s.is_none()).count() as u32;
+            if free == 0 || fixed > size {
+                break;
+            }
+            let each = (size - fixed) / free;
+            let mut again = false;
+            for (i, &(_, minimum, maximum)) in limits.iter().enumerate() {
+                if sizes[i].is_none() && (each < minimum || each > maximum) {
+                    sizes[i] = Some(each.clamp(minimum, maximum));
+                    again = true;
+                }
+            }
+            if !again {
+                for s in sizes.iter_mut().filter(|s| s.is_none()) {
+                    *s = Some(each);
+                }
+                break;
+            }
+        }
+        let mut sizes: Vec<u32> = sizes.into_iter().map(|s| s.unwrap_or(0)).collect();
+        let total: u32 = sizes.iter().sum();
+        if total > size {
+            return 0;
+        }
+
+        // Give anything left over to the last cell still able to grow.
+        let last = (0..cells.len())
+            .rev()
+            .find(|&i| sizes[i] < limits[i].2)
+            .unwrap_or(cells.len() - 1);
+        sizes[last] += size - total;
+        if sizes.contains(&0) {
+            return 0;
+        }
+
+        let mut changed = 0;
+        for (i, &lc) in cells.iter().enumerate() {
+            let current = if type_ == layout_type::LAYOUT_LEFTRIGHT {
+                (*lc).sx
+            } else {
+                (*lc).sy
+            };
+            let change = (sizes[i] + limits[i].0) as i32 - current as i32;
+            if change != 0 {
+                layout_resize_adjust(w, lc, type_, change);
+                changed = 1;
+            }
+        }
+        changed
+    }
+}
+
 /// Spread out a pane and its parent cells
 pub unsafe fn layout_spread_out(wp: *mut window_pane) {
     unsafe {
@@ -1216,3 +1594,53 @@ pub unsafe fn layout_spread_out(wp: *mut window_pane) {
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn error(s: &str) -> String {
+        match layout_constraint_parse(s) {
+            Ok(_) => panic!("{s} should not parse"),
+            Err(error) => error,
+        }
+    }
+
+    fn constraint(min: layout_limit, max: layout_limit) -> layout_constraint {
+        layout_constraint { min, max }
+    }
+
+    #[test]
+    fn test_constraint_parse() {
+        use layout_limit::{Cells, None, Percent};
+
+        assert!(layout_constraint_parse("none").unwrap() == layout_constraint::default());
+        assert!(layout_constraint_parse("").unwrap() == layout_constraint::default());
+        assert!(layout_constraint_parse("20").unwrap() == constraint(Cells(20), Cells(20)));
+        assert!(layout_constraint_parse("10-30%").unwrap() == constraint(Cells(10), Percent(30)));
+        assert!(layout_constraint_parse("-40").unwrap() == constraint(None, Cells(40)));
+        assert!(layout_constraint_parse("25%-").unwrap() == constraint(Percent(25), None));
+        assert!(layout_constraint_parse("100%").unwrap() == constraint(Percent(100), Percent(100)));
+        assert!(layout_constraint_parse("80%-10").unwrap() == constraint(Percent(80), Cells(10)));
+    }
+
+    #[test]
+    fn test_constraint_parse_errors() {
+        assert_eq!(error("101%"), "bad size: 101%");
+        assert_eq!(error("x"), "bad size: x");
+        assert_eq!(error("10-%"), "bad size: %");
+        assert!(layout_constraint_parse(&format!("{}", WINDOW_MAXIMUM + 1)).is_err());
+        assert_eq!(error("50-10"), "minimum is larger than maximum: 50-10");
+        assert_eq!(error("80%-20%"), "minimum is larger than maximum: 80%-20%");
+    }
+
+    #[test]
+    fn test_constraint_string() {
+        for s in ["", "20", "10-30%", "-40", "25%-"] {
+            assert_eq!(
+                layout_constraint_string(&layout_constraint_parse(s).unwrap()),
+                s
+            );
+        }
+    }
+}
@@ -16,7 +16,9 @@ use crate::*;
 use crate::libc::sscanf;
 
 use crate::compat::{
-    queue::{tailq_first, tailq_foreach, tailq_insert_tail, tailq_last, tailq_next},
+    queue::{
+        tailq_first, tailq_foreach, tailq_insert_after, tailq_insert_tail, tailq_last, tailq_next,
+    },
     strlcat,
 };
 
@@ -35,7 +37,7 @@ pub unsafe fn layout_checksum(mut layout: *const u8) -> u16 {
         let mut csum = 0u16;
         while *layout != b'\0' as _ {
             csum = (csum >> 1) + ((csum & 1) << 15);
-            csum += *layout as u16;
+            csum = csum.wrapping_add(*layout as u16);
             layout = layout.add(1);
         }
         csum
@@ -166,52 +168,36 @@ pub unsafe fn layout_check(lc: *mut layout_cell) -> i32 {
     1
 }
 
-pub unsafe fn layout_parse(w: *mut window, mut layout: *const u8, cause: *mut *mut u8) -> i32 {
-    let __func__ = c!("layout_parse");
+/// Build a cell tree from a layout string without applying it to a window.
+pub unsafe fn layout_parse_cells(mut layout: *const u8, cause: *mut *mut u8) -> *mut layout_cell {
+    let __func__ = c!("layout_parse_cells");
     unsafe {
-        let mut lc: *mut layout_cell;
+        let lc: *mut layout_cell;
         let mut csum: u16 = 0;
 
         'fail: {
             /* Check validity. */
             if sscanf(layout.cast(), c"%hx,".as_ptr(), &raw mut csum) != 1 {
                 *cause = xstrdup_(c"invalid layout").as_ptr();
-                return -1;
+                return null_mut();
             }
             layout = layout.add(5);
             if csum != layout_checksum(layout) {
                 *cause = xstrdup_(c"invalid layout").as_ptr();
-                return -1;
+                return null_mut();
             }
 
             /* Build the layout. */
             lc = layout_construct(null_mut(), &raw mut layout);
             if lc.is_null() {
                 *cause = xstrdup_(c"invalid layout").as_ptr();
-                return -1;
+                return null_mut();
             }
             if *layout != b'\0' as _ {
                 *cause = xstrdup_(c"invalid layout").as_ptr();
                 break 'fail;
             }
 
-            /* Check this window will fit into the layout. */
-            loop {
-                let npanes = window_count_panes(w);
-                let ncells = layout_count_cells(lc);
-                if npanes > ncells {
-                    *cause = format_nul!("have {} panes but need {}", npanes, ncells);
-                    break 'fail;
-                }
-                if npanes == ncells {
-                    break;
-                }
-
-                /* Fewer panes than cells - close the bottom right. */
-                let lcchild = layout_find_bottomright(lc);
-                layout_destroy_cell(w, lcchild, &raw mut lc);
-            }
-
             /*
              * It appears older versions of tmux were able to generate layouts with
              * an incorrect top cell size - if it is larger than the top child then
@@ -249,31 +235,179 @@ pub unsafe fn layout_parse(w: *mut window, mut layout: *const u8, cause: *mut *m
                 break 'fail;
             }
 
-            /* Resize to the layout size. */
-            window_resize(w, (*lc).sx, (*lc).sy, -1, -1);
+            return lc;
+        }
+        // fail:
+        layout_free_cell(lc);
+        null_mut()
+    }
+}
 
-            /* Destroy the old layout and swap to the new. */
-            layout_free_cell((*w).layout_root);
-            (*w).layout_root = lc;
+/// Find the largest cell which can be split in two.
+unsafe fn layout_find_largest(lc: *mut layout_cell) -> *mut layout_cell {
+    unsafe {
+        if (*lc).type_ == layout_type::LAYOUT_WINDOWPANE {
+            if (*lc).sx < PANE_MINIMUM * 2 + 1 && (*lc).sy < PANE_MINIMUM * 2 + 1 {
+                return null_mut();
+            }
+            return lc;
+        }
 
-            /* Assign the panes into the cells. */
-            let mut wp = tailq_first(&raw mut (*w).panes);
-            layout_assign(&raw mut wp, lc);
+        let mut largest: *mut layout_cell = null_mut();
+        for lcchild in tailq_foreach(&raw mut (*lc).cells).map(NonNull::as_ptr) {
+            let found = layout_find_largest(lcchild);
+            if !found.is_null()
+                && (largest.is_null() || (*found).sx * (*found).sy > (*largest).sx * (*largest).sy)
+            {
+                largest = found;
+            }
+        }
+        largest
+    }
+}
 
-            /* Update pane offsets and sizes. */
-            layout_fix_offsets(w);
-            layout_fix_panes(w, null_mut());
-            recalculate_sizes();
+/// Split a cell in two along its longest side, returning the new cell.
+unsafe fn layout_split_cell(lc: *mut layout_cell) -> *mut layout_cell {
+    unsafe {
+        let type_ = if (*lc).sx >= (*lc).sy * 2 && (*lc).sx > PANE_MINIMUM * 2 {
+            layout_type::LAYOUT_LEFTRIGHT
+        } else if (*lc).sy > PANE_MINIMUM * 2 {
+            layout_type::LAYOUT_TOPBOTTOM
+        } else {
+            layout_type::LAYOUT_LEFTRIGHT
+        };
 
-            layout_print_cell(lc, __func__, 0);
+        /* If the parent is not the same type, make this cell into a node. */
+        let mut lcparent = (*lc).parent;
+        let mut lcfirst = lc;
+        if lcparent.is_null() || (*lcparent).type_ != type_ {
+            lcfirst = layout_create_cell(lc);
+            layout_set_size(lcfirst, (*lc).sx, (*lc).sy, (*lc).xoff, (*lc).yoff);
+            layout_make_node(lc, type_);
+            tailq_insert_tail(&raw mut (*lc).cells, lcfirst);
+            lcparent = lc;
+        }
 
-            notify_window(c"window-layout-changed", w);
+        let lcnew = layout_create_cell(lcparent);
+        if type_ == layout_type::LAYOUT_LEFTRIGHT {
+            let size = (*lcfirst).sx;
+            (*lcfirst).sx = (size - 1).div_ceil(2);
+            layout_set_size(lcnew, size - 1 - (*lcfirst).sx, (*lcfirst).sy, 0, 0);
+        } else {
+            let size = (*lcfirst).sy;
+            (*lcfirst).sy = (size - 1).div_ceil(2);
+            layout_set_size(lcnew, (*lcfirst).sx, size - 1 - (*lcfirst).sy, 0, 0);
+        }
+        tailq_insert_after(&raw mut (*lcparent).cells, lcfirst, lcnew);
+        lcnew
+    }
+}
 
-            return 0;
+/// Adjust a cell tree to have one cell for each pane in the window. Extra
+/// cells are removed from the bottom right and missing cells are made by
+/// splitting the largest.
+pub unsafe fn layout_fit_cells(w: *mut window, lc: *mut *mut layout_cell) -> i32 {
+    unsafe {
+        let npanes = window_count_panes(w);
+        loop {
+            let ncells = layout_count_cells(*lc);
+            if npanes == ncells {
+                return 0;
+            }
+            if npanes < ncells {
+                let lcchild = layout_find_bottomright(*lc);
+                layout_destroy_cell(w, lcchild, lc);
+            } else {
+                let lcchild = layout_find_largest(*lc);
+                if lcchild.is_null() {
+                    return -1;
+                }
+                layout_split_cell(lcchild);
+            }
         }
-        // fail:
-        layout_free_cell(lc);
-        -1
+    }
+}
+
+/// Apply a cell tree to a window, replacing its existing layout. The panes
+/// are assigned to the cells in order, either from the given list or in the
+/// order they appear in the window.
+pub unsafe fn layout_apply_cells(
+    w: *mut window,
+    lc: *mut layout_cell,
+    panes: Option<&[*mut window_pane]>,
+) {
+    let __func__ = c!("layout_apply_cells");
+    unsafe {
+        /* Resize to the layout size. */
+        window_resize(w, (*lc).sx, (*lc).sy, -1, -1);
+
+        /* Destroy the old layout and swap to the new. */
+        let constraints = layout_constraints_save(w);
+        layout_free_cell((*w).layout_root);
+        (*w).layout_root = lc;
+
+        /* Assign the panes into the cells. */
+        match panes {
+            Some(panes) => layout_assign_list(lc, &mut panes.iter()),
+            None => {
+                let mut wp = tailq_first(&raw mut (*w).panes);
+                layout_assign(&raw mut wp, lc);
+            }
+        }
+
+        /* Update pane offsets and sizes. */
+        layout_fix_offsets(w);
+        layout_fix_panes(w, null_mut());
+        recalculate_sizes();
+        layout_constraints_restore(w, constraints);
+
+        layout_print_cell(lc, __func__, 0);
+
+        notify_window(c"window-layout-changed", w);
+    }
+}
+
+pub unsafe fn layout_parse(w: *mut window, layout: *const u8, cause: *mut *mut u8) -> i32 {
+    unsafe {
+        let mut lc = layout_parse_cells(layout, cause);
+        if lc.is_null() {
+            return -1;
+        }
+
+        /* Check this window will fit into the layout. */
+        let npanes = window_count_panes(w);
+        let ncells = layout_count_cells(lc);
+        if npanes > ncells {
+            *cause = format_nul!("have {} panes but need {}", npanes, ncells);
+            layout_free_cell(lc);
+            return -1;
+        }
+
+        /* Fewer panes than cells - close the bottom right. */
+        layout_fit_cells(w, &raw mut lc);
+
+        layout_apply_cells(w, lc, None);
+        0
+    }
+}
+
+/// Apply a layout string to a window with any number of panes, adding or
+/// removing cells as needed.
+pub unsafe fn layout_parse_adapt(w: *mut window, layout: *const u8, cause: *mut *mut u8) -> i32 {
+    unsafe {
+        let mut lc = layout_parse_cells(layout, cause);
+        if lc.is_null() {
+            return -1;
+        }
+
+        if layout_fit_cells(w, &raw mut lc) != 0 {
+            *cause = format_nul!("not enough space for {} panes", window_count_panes(w));
+            layout_free_cell(lc);
+            return -1;
+        }
+
+        layout_apply_cells(w, lc, None);
+        0
     }
 }
 
@@ -295,6 +429,27 @@ unsafe fn layout_assign(wp: *mut *mut window_pane, lc: *mut layout_cell) {
     }
 }
 
+/// Assign panes into cells from a list.
+unsafe fn layout_assign_list<'a>(
+    lc: *mut layout_cell,
+    panes: &mut impl Iterator<Item = &'a *mut window_pane>,
+) {
+    unsafe {
+        match (*lc).type_ {
+            layout_type::LAYOUT_WINDOWPANE => {
+                if let Some(&wp) = panes.next() {
+                    layout_make_leaf(lc, wp);
+                }
+            }
+            layout_type::LAYOUT_LEFTRIGHT | layout_type::LAYOUT_TOPBOTTOM => {
+                for lcchild in tailq_foreach(&raw mut (*lc).cells).map(NonNull::as_ptr) {
+                    layout_assign_list(lcchild, panes);
+                }
+            }
+        }
+    }
+}
+
 /// Construct a cell from all or part of a layout tree.
 unsafe fn layout_construct(lcparent: *mut layout_cell, layout: *mut *const u8) -> *mut layout_cell {
     unsafe {
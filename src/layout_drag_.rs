@@ -0,0 +1,248 @@
+//! Dragging panes by their status line to rearrange the layout.
+//!
+//! A drag started on a pane's status line (pane-border-status) follows the
+//! mouse over the other panes in the window. Dropping near the edge of a pane
+//! splits it and moves the dragged pane into the new cell; dropping in the
+//! middle swaps the two panes.
+use crate::*;
+
+use crate::compat::queue::{
+    tailq_insert_after, tailq_insert_before, tailq_insert_head, tailq_prev, tailq_remove,
+    tailq_replace,
+};
+
+#[derive(Copy, Clone, Eq, PartialEq)]
+#[repr(i32)]
+pub enum layout_drop {
+    None = 0,
+    Centre,
+    Left,
+    Right,
+    Top,
+    Bottom,
+}
+
+/// Pane being dragged by a client and where it would be dropped.
+#[derive(Copy, Clone)]
+pub struct layout_drag {
+    pane: u32,
+    target: u32,
+    drop: layout_drop,
+}
+
+/// Get the window position of the mouse, or of the last mouse event.
+unsafe fn layout_drag_position(m: *mut mouse_event, last: bool) -> (u32, u32) {
+    unsafe {
+        let (x, mut y) = if last {
+            ((*m).lx + (*m).ox, (*m).ly + (*m).oy)
+        } else {
+            ((*m).x + (*m).ox, (*m).y + (*m).oy)
+        };
+        if (*m).statusat == 0 && y >= (*m).statuslines {
+            y -= (*m).statuslines;
+        } else if (*m).statusat > 0 && y >= (*m).statusat as u32 {
+            y = ((*m).statusat - 1) as u32;
+        }
+        (x, y)
+    }
+}
+
+/// Work out which part of a pane a position is in. The middle half in both
+/// directions is the centre, otherwise it is the nearest edge.
+unsafe fn layout_drag_zone(wp: *mut window_pane, x: u32, y: u32) -> layout_drop {
+    unsafe {
+        let sx = (*wp).sx.max(1);
+        let sy = (*wp).sy.max(1);
+        let rx = x.saturating_sub((*wp).xoff).min(sx - 1);
+        let ry = y.saturating_sub((*wp).yoff).min(sy - 1);
+
+        if 4 * rx >= sx && 4 * rx < 3 * sx && 4 * ry >= sy && 4 * ry < 3 * sy {
+            return layout_drop::Centre;
+        }
+        [
+            (rx * sy, layout_drop::Left),
+            ((sx - 1 - rx) * sy, layout_drop::Right),
+            (ry * sx, layout_drop::Top),
+            ((sy - 1 - ry) * sx, layout_drop::Bottom),
+        ]
+        .into_iter()
+        .min_by_key(|&(distance, _)| distance)
+        .map(|(_, drop)| drop)
+        .unwrap()
+    }
+}
+
+/// Start dragging a pane if the mouse was pressed on its status line.
+/// Returns false if this is not a pane drag.
+pub unsafe fn layout_drag_start(c: *mut client, m: *mut mouse_event) -> bool {
+    unsafe {
+        let wl: *mut winlink = transmute_ptr(cmd_mouse_window(m, null_mut()));
+        if wl.is_null() {
+            return false;
+        }
+        let w = (*wl).window;
+        if (*w).flags.intersects(window_flag::ZOOMED) || window_count_panes(w) < 2 {
+            return false;
+        }
+
+        // Only the title text starts a drag, the rest of the status line is
+        // left as a border which can be dragged to resize.
+        let (x, y) = layout_drag_position(m, true);
+        let wp = window_get_status_at(w, x, y);
+        if wp.is_null() || x < (*wp).xoff + 2 || x >= (*wp).xoff + 2 + (*wp).status_text_size {
+            return false;
+        }
+        log_debug!("{}: dragging pane %%{}", _s((*c).name), (*wp).id);
+
+        (*c).pane_drag = layout_drag {
+            pane: (*wp).id,
+            target: 0,
+            drop: layout_drop::None,
+        };
+        (*c).tty.mouse_drag_update = Some(layout_drag_update);
+        (*c).tty.mouse_drag_release = Some(layout_drag_release);
+        layout_drag_update(c, m);
+        true
+    }
+}
+
+unsafe fn layout_drag_update(c: *mut client, m: *mut mouse_event) {
+    unsafe {
+        let mut target = 0;
+        let mut drop = layout_drop::None;
+
+        let wl: *mut winlink = transmute_ptr(cmd_mouse_window(m, null_mut()));
+        let src = window_pane_find_by_id((*c).pane_drag.pane);
+        if !wl.is_null() && !src.is_null() && (*src).window == (*wl).window {
+            let (x, y) = layout_drag_position(m, false);
+            let wp = window_get_active_at((*wl).window, x, y);
+            if !wp.is_null() && wp != src {
+                target = (*wp).id;
+                drop = layout_drag_zone(wp, x, y);
+            }
+        }
+
+        if target != (*c).pane_drag.target || drop != (*c).pane_drag.drop {
+            (*c).pane_drag.target = target;
+            (*c).pane_drag.drop = drop;
+            (*c).flags |= client_flag::REDRAWWINDOW;
+        }
+    }
+}
+
+unsafe fn layout_drag_release(c: *mut client, _m: *mut mouse_event) {
+    unsafe {
+        let drag = (*c).pane_drag;
+        if drag.drop == layout_drop::None {
+            return;
+        }
+        (*c).pane_drag.drop = layout_drop::None;
+        (*c).flags |= client_flag::REDRAWWINDOW;
+
+        let src = window_pane_find_by_id(drag.pane);
+        let dst = window_pane_find_by_id(drag.target);
+        if src.is_null() || dst.is_null() || src == dst || (*src).window != (*dst).window {
+            return;
+        }
+        let w = (*src).window;
+        if (*w).flags.intersects(window_flag::ZOOMED) {
+            return;
+        }
+
+        if drag.drop == layout_drop::Centre {
+            layout_drag_swap(w, src, dst);
+        } else if !layout_drag_move(w, src, dst, drag.drop) {
+            return;
+        }
+        window_set_active_pane(w, src, 1);
+
+        recalculate_sizes();
+        server_redraw_window(w);
+        notify_window(c"window-layout-changed", w);
+    }
+}
+
+/// Swap two panes in the same window.
+unsafe fn layout_drag_swap(w: *mut window, src: *mut window_pane, dst: *mut window_pane) {
+    unsafe {
+        let mut tmp_wp = tailq_prev::<_, _, discr_entry>(dst);
+        tailq_remove::<_, discr_entry>(&raw mut (*w).panes, dst);
+        tailq_replace::<_, discr_entry>(&raw mut (*w).panes, src, dst);
+        if tmp_wp == src {
+            tmp_wp = dst;
+        }
+        if tmp_wp.is_null() {
+            tailq_insert_head::<_, discr_entry>(&raw mut (*w).panes, src);
+        } else {
+            tailq_insert_after::<_, discr_entry>(&raw mut (*w).panes, tmp_wp, src);
+        }
+
+        layout_stack_swap(src, dst);
+
+        let sx = (*src).sx;
+        let sy = (*src).sy;
+        let xoff = (*src).xoff;
+        let yoff = (*src).yoff;
+        (*src).xoff = (*dst).xoff;
+        (*src).yoff = (*dst).yoff;
+        window_pane_resize(src, (*dst).sx, (*dst).sy);
+        (*dst).xoff = xoff;
+        (*dst).yoff = yoff;
+        window_pane_resize(dst, sx, sy);
+    }
+}
+
+/// Move a pane into a new cell split from the edge of another pane.
+unsafe fn layout_drag_move(
+    w: *mut window,
+    src: *mut window_pane,
+    dst: *mut window_pane,
+    drop: layout_drop,
+) -> bool {
+    unsafe {
+        let (type_, flags) = match drop {
+            layout_drop::Left => (layout_type::LAYOUT_LEFTRIGHT, SPAWN_BEFORE),
+            layout_drop::Right => (layout_type::LAYOUT_LEFTRIGHT, 0),
+            layout_drop::Top => (layout_type::LAYOUT_TOPBOTTOM, SPAWN_BEFORE),
+            _ => (layout_type::LAYOUT_TOPBOTTOM, 0),
+        };
+
+        let lc = layout_split_pane(dst, type_, -1, flags);
+        if lc.is_null() {
+            return false;
+        }
+        layout_close_pane(src);
+
+        tailq_remove::<_, discr_entry>(&raw mut (*w).panes, src);
+        if flags & SPAWN_BEFORE != 0 {
+            tailq_insert_before::<_, discr_entry>(dst, src);
+        } else {
+            tailq_insert_after::<_, discr_entry>(&raw mut (*w).panes, dst, src);
+        }
+        layout_assign_pane(lc, src, 0);
+        true
+    }
+}
+
+/// Get the area of a pane to highlight as the drop target of a client's drag,
+/// as offset and size within the pane.
+pub unsafe fn layout_drag_area(
+    c: *mut client,
+    wp: *mut window_pane,
+) -> Option<(u32, u32, u32, u32)> {
+    unsafe {
+        let drag = &(*c).pane_drag;
+        if drag.drop == layout_drop::None || drag.target != (*wp).id {
+            return None;
+        }
+        let sx = (*wp).sx;
+        let sy = (*wp).sy;
+        Some(match drag.drop {
+            layout_drop::Left => (0, 0, sx.div_ceil(2), sy),
+            layout_drop::Right => (sx / 2, 0, sx - sx / 2, sy),
+            layout_drop::Top => (0, 0, sx, sy.div_ceil(2)),
+            layout_drop::Bottom => (0, sy / 2, sx, sy - sy / 2),
+            _ => (0, 0, sx, sy),
+        })
+    }
+}
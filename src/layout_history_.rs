@@ -0,0 +1,247 @@
+//! Per-window layout history for undo and redo.
+//!
+//! A snapshot is taken when a `window-layout-changed` notification is added,
+//! rather than when it is handled, so a queue of undo or redo commands does
+//! not record layouts after the fact. Each snapshot records the layout string
+//! and the IDs of the panes in the order they appear in it, so undo can put
+//! the same panes back in the same cells. The cells of panes which have since
+//! closed are removed; a snapshot cannot be used if the window has new panes.
+use crate::*;
+
+use std::ffi::CString;
+
+use crate::compat::queue::tailq_foreach;
+
+struct layout_history_entry {
+    layout: CString,
+    panes: Vec<u32>,
+}
+
+pub struct layout_history {
+    entries: Vec<layout_history_entry>,
+    current: usize,
+    held: bool,
+}
+
+unsafe fn layout_history_get<'a>(w: *mut window) -> &'a mut layout_history {
+    unsafe {
+        if (*w).layout_history.is_null() {
+            (*w).layout_history = Box::into_raw(Box::new(layout_history {
+                entries: Vec::new(),
+                current: 0,
+                held: false,
+            }));
+        }
+        &mut *(*w).layout_history
+    }
+}
+
+unsafe fn layout_history_panes(lc: *mut layout_cell, panes: &mut Vec<u32>) {
+    unsafe {
+        if (*lc).type_ == layout_type::LAYOUT_WINDOWPANE {
+            if !(*lc).wp.is_null() {
+                panes.push((*(*lc).wp).id);
+            }
+            return;
+        }
+        for lcchild in tailq_foreach(&raw mut (*lc).cells).map(NonNull::as_ptr) {
+            layout_history_panes(lcchild, panes);
+        }
+    }
+}
+
+/// Take a snapshot of the current layout of a window.
+unsafe fn layout_history_entry(w: *mut window) -> Option<layout_history_entry> {
+    unsafe {
+        let dump = layout_dump((*w).layout_root);
+        if dump.is_null() {
+            return None;
+        }
+        let layout = CStr::from_ptr(dump.cast()).to_owned();
+        free_(dump);
+
+        let mut panes = Vec::new();
+        layout_history_panes((*w).layout_root, &mut panes);
+        Some(layout_history_entry { layout, panes })
+    }
+}
+
+/// Record the current layout of a window, discarding anything that could be
+/// redone.
+pub unsafe fn layout_history_add(w: *mut window) {
+    unsafe {
+        if (*w).layout_root.is_null() {
+            return;
+        }
+        let limit = options_get_number_((*w).options, c"layout-history-limit") as usize;
+        if limit == 0 {
+            layout_history_free(w);
+            return;
+        }
+
+        let lh = layout_history_get(w);
+        if lh.held {
+            return;
+        }
+        let Some(entry) = layout_history_entry(w) else {
+            return;
+        };
+        if lh
+            .entries
+            .get(lh.current)
+            .is_some_and(|e| e.layout == entry.layout)
+        {
+            return;
+        }
+
+        lh.entries.truncate(lh.current + 1);
+        lh.entries.push(entry);
+        if lh.entries.len() > limit {
+            lh.entries.drain(..lh.entries.len() - limit);
+        }
+        lh.current = lh.entries.len() - 1;
+        log_debug!(
+            "layout_history_add: @{} has {} entries",
+            (*w).id,
+            lh.entries.len()
+        );
+    }
+}
+
+/// Stop recording layouts for a window, while it is changed in several steps.
+pub unsafe fn layout_history_hold(w: *mut window) {
+    unsafe {
+        layout_history_get(w).held = true;
+    }
+}
+
+/// Start recording layouts again and record the layout as it now is.
+pub unsafe fn layout_history_release(w: *mut window) {
+    unsafe {
+        layout_history_get(w).held = false;
+        layout_history_add(w);
+    }
+}
+
+pub unsafe fn layout_history_free(w: *mut window) {
+    unsafe {
+        if !(*w).layout_history.is_null() {
+            drop(Box::from_raw((*w).layout_history));
+            (*w).layout_history = null_mut();
+        }
+    }
+}
+
+/// Get the panes for a history entry, or None for those which have since
+/// closed. The entry is not usable if the window has panes it does not know.
+unsafe fn layout_history_usable(
+    w: *mut window,
+    entry: &layout_history_entry,
+) -> Option<Vec<Option<*mut window_pane>>> {
+    unsafe {
+        let panes: Vec<_> = entry
+            .panes
+            .iter()
+            .map(|&id| {
+                let wp = window_pane_find_by_id(id);
+                (!wp.is_null() && (*wp).window == w).then_some(wp)
+            })
+            .collect();
+        let alive = panes.iter().flatten().count();
+        if alive == 0 || alive != window_count_panes(w) as usize {
+            return None;
+        }
+        Some(panes)
+    }
+}
+
+unsafe fn layout_history_leaves(lc: *mut layout_cell, leaves: &mut Vec<*mut layout_cell>) {
+    unsafe {
+        if (*lc).type_ == layout_type::LAYOUT_WINDOWPANE {
+            leaves.push(lc);
+            return;
+        }
+        for lcchild in tailq_foreach(&raw mut (*lc).cells).map(NonNull::as_ptr) {
+            layout_history_leaves(lcchild, leaves);
+        }
+    }
+}
+
+/// Remove the cells of panes which have closed, giving their space to their
+/// neighbours as if each had been killed.
+unsafe fn layout_history_prune(
+    w: *mut window,
+    lc: &mut *mut layout_cell,
+    panes: &[Option<*mut window_pane>],
+) {
+    unsafe {
+        let mut leaves = Vec::new();
+        layout_history_leaves(*lc, &mut leaves);
+        for (leaf, wp) in leaves.into_iter().zip(panes) {
+            if wp.is_none() {
+                layout_destroy_cell(w, leaf, lc);
+            }
+        }
+    }
+}
+
+/// Step backwards (undo) or forwards (redo) through the history and apply
+/// the first usable layout.
+pub unsafe fn layout_history_step(w: *mut window, forward: bool, cause: *mut *mut u8) -> i32 {
+    unsafe {
+        let what = if forward { "redo" } else { "undo" };
+        if (*w).layout_history.is_null() {
+            *cause = format_nul!("no layout to {what}");
+            return -1;
+        }
+        let lh = &mut *(*w).layout_history;
+
+        let before = layout_history_entry(w).map(|e| e.layout);
+        let mut idx = lh.current;
+        loop {
+            if forward {
+                if idx + 1 >= lh.entries.len() {
+                    break;
+                }
+                idx += 1;
+            } else {
+                if idx == 0 {
+                    break;
+                }
+                idx -= 1;
+            }
+            let entry = &lh.entries[idx];
+            let Some(panes) = layout_history_usable(w, entry) else {
+                continue;
+            };
+
+            let mut lc = layout_parse_cells(entry.layout.as_ptr().cast(), cause);
+            if lc.is_null() {
+                return -1;
+            }
+            layout_history_prune(w, &mut lc, &panes);
+            let panes: Vec<_> = panes.into_iter().flatten().collect();
+
+            lh.current = idx;
+            lh.held = true;
+            layout_apply_cells(w, lc, Some(&panes));
+            let lh = layout_history_get(w);
+            lh.held = false;
+
+            // Replace the entry with the layout as applied, without the panes
+            // which have closed, so it is the one undo and redo move from. If
+            // that leaves it the same as before, keep going.
+            let Some(entry) = layout_history_entry(w) else {
+                return 0;
+            };
+            let same = before.as_ref() == Some(&entry.layout);
+            lh.entries[idx] = entry;
+            if !same {
+                return 0;
+            }
+        }
+
+        *cause = format_nul!("no layout to {what}");
+        -1
+    }
+}
@@ -0,0 +1,175 @@
+//! Named layouts saved in the server.
+//!
+//! Each entry is a layout string as produced by `layout_dump`, checked when
+//! it is saved. Applying a saved layout to a window with a different number
+//! of panes adds or removes cells with `layout_parse_adapt`.
+use crate::*;
+
+use std::collections::BTreeMap;
+use std::ffi::CString;
+
+pub struct layout_saved {
+    pub name: CString,
+    pub layout: CString,
+    pub panes: u32,
+    pub sx: u32,
+    pub sy: u32,
+}
+
+static mut LAYOUT_SAVED: BTreeMap<CString, layout_saved> = BTreeMap::new();
+
+/// Save a layout string under a name, replacing any existing layout with the
+/// same name.
+pub unsafe fn layout_saved_set(name: *const u8, layout: *const u8, cause: *mut *mut u8) -> i32 {
+    unsafe {
+        if *name == b'\0' {
+            *cause = xstrdup_(c"empty layout name").as_ptr();
+            return -1;
+        }
+        if layout_set_lookup(name) != -1 {
+            *cause = format_nul!("name conflicts with a preset layout: {}", _s(name));
+            return -1;
+        }
+
+        let lc = layout_parse_cells(layout, cause);
+        if lc.is_null() {
+            return -1;
+        }
+        let ls = layout_saved {
+            name: CStr::from_ptr(name.cast()).to_owned(),
+            layout: CStr::from_ptr(layout.cast()).to_owned(),
+            panes: layout_count_cells(lc),
+            sx: (*lc).sx,
+            sy: (*lc).sy,
+        };
+        layout_free_cell(lc);
+
+        log_debug!("layout_saved_set: {} = {}", _s(name), _s(layout));
+        let saved = &raw mut LAYOUT_SAVED;
+        (*saved).insert(ls.name.clone(), ls);
+        0
+    }
+}
+
+/// Remove a saved layout, returning false if it does not exist.
+pub unsafe fn layout_saved_remove(name: *const u8) -> bool {
+    unsafe {
+        let name = CStr::from_ptr(name.cast());
+        let saved = &raw mut LAYOUT_SAVED;
+        (*saved).remove(name).is_some()
+    }
+}
+
+/// Find a saved layout by name.
+pub unsafe fn layout_saved_get(name: *const u8) -> Option<&'static layout_saved> {
+    unsafe {
+        let name = CStr::from_ptr(name.cast());
+        let saved = &raw const LAYOUT_SAVED;
+        (*saved).get(name)
+    }
+}
+
+/// Saved layout names in order.
+pub unsafe fn layout_saved_names() -> Vec<CString> {
+    unsafe {
+        let saved = &raw const LAYOUT_SAVED;
+        (*saved).keys().cloned().collect()
+    }
+}
+
+pub unsafe fn layout_saved_count() -> usize {
+    unsafe {
+        let saved = &raw const LAYOUT_SAVED;
+        (*saved).len()
+    }
+}
+
+/// Add the format variables for a saved layout.
+pub unsafe fn format_defaults_layout_saved(ft: *mut format_tree, ls: &layout_saved) {
+    unsafe {
+        format_add!(ft, c!("layout_name"), "{}", ls.name.to_string_lossy());
+        format_add!(ft, c!("layout_string"), "{}", ls.layout.to_string_lossy());
+        format_add!(ft, c!("layout_panes"), "{}", ls.panes);
+        format_add!(ft, c!("layout_width"), "{}", ls.sx);
+        format_add!(ft, c!("layout_height"), "{}", ls.sy);
+    }
+}
+
+/// Draw an outline of a saved layout scaled to fit into the given size.
+pub unsafe fn layout_saved_draw(ls: &layout_saved, ctx: *mut screen_write_ctx, sx: u32, sy: u32) {
+    unsafe {
+        if sx < 3 || sy < 3 {
+            return;
+        }
+        let mut cause = null_mut();
+        let lc = layout_parse_cells(ls.layout.as_ptr().cast(), &raw mut cause);
+        if lc.is_null() {
+            free_(cause);
+            return;
+        }
+
+        // Draw into a scratch screen, then copy it in as a preview.
+        let mut s: screen = zeroed();
+        let mut sctx: screen_write_ctx = zeroed();
+        screen_init(&raw mut s, sx, sy, 0);
+        s.mode &= !mode_flag::MODE_CURSOR;
+        screen_write_start(&raw mut sctx, &raw mut s);
+
+        let mut n = 0;
+        let (lsx, lsy) = ((*lc).sx + 1, (*lc).sy + 1);
+        layout_saved_draw_cell(lc, &raw mut sctx, sx, sy, lsx, lsy, &mut n);
+        layout_free_cell(lc);
+
+        screen_write_stop(&raw mut sctx);
+        screen_write_preview(ctx, &raw mut s, sx, sy);
+        screen_free(&raw mut s);
+    }
+}
+
+unsafe fn layout_saved_draw_cell(
+    lc: *mut layout_cell,
+    ctx: *mut screen_write_ctx,
+    sx: u32,
+    sy: u32,
+    lsx: u32,
+    lsy: u32,
+    n: &mut u32,
+) {
+    unsafe {
+        if (*lc).type_ != layout_type::LAYOUT_WINDOWPANE {
+            for lcchild in tailq_foreach(&raw mut (*lc).cells).map(NonNull::as_ptr) {
+                layout_saved_draw_cell(lcchild, ctx, sx, sy, lsx, lsy, n);
+            }
+            return;
+        }
+
+        // Scale the cell including its borders so that neighbours share them.
+        let x0 = (*lc).xoff * (sx - 1) / lsx;
+        let y0 = (*lc).yoff * (sy - 1) / lsy;
+        let x1 = ((*lc).xoff + (*lc).sx + 1) * (sx - 1) / lsx;
+        let y1 = ((*lc).yoff + (*lc).sy + 1) * (sy - 1) / lsy;
+        if x1 < x0 + 2 || y1 < y0 + 2 {
+            *n += 1;
+            return;
+        }
+
+        screen_write_cursormove(ctx, x0 as i32, y0 as i32, 0);
+        screen_write_box(
+            ctx,
+            x1 - x0 + 1,
+            y1 - y0 + 1,
+            box_lines::BOX_LINES_DEFAULT,
+            null(),
+            null(),
+        );
+
+        let label = format!("{}", *n);
+        if x1 - x0 > label.len() as u32 {
+            let x = x0 + (x1 - x0 + 1 - label.len() as u32) / 2;
+            let y = y0 + (y1 - y0) / 2;
+            screen_write_cursormove(ctx, x as i32, y as i32, 0);
+            screen_write_nputs!(ctx, -1, &raw const GRID_DEFAULT_CELL, "{}", label);
+        }
+        *n += 1;
+    }
+}
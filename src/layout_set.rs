@@ -28,7 +28,7 @@ impl layout_sets_entry {
     }
 }
 
-const LAYOUT_SETS_LEN: usize = 7;
+const LAYOUT_SETS_LEN: usize = 10;
 static LAYOUT_SETS: [layout_sets_entry; LAYOUT_SETS_LEN] = [
     layout_sets_entry::new(c"even-horizontal", layout_set_even_h),
     layout_sets_entry::new(c"even-vertical", layout_set_even_v),
@@ -37,8 +37,16 @@ static LAYOUT_SETS: [layout_sets_entry; LAYOUT_SETS_LEN] = [
     layout_sets_entry::new(c"main-vertical", layout_set_main_v),
     layout_sets_entry::new(c"main-vertical-mirrored", layout_set_main_v_mirrored),
     layout_sets_entry::new(c"tiled", layout_set_tiled),
+    layout_sets_entry::new(c"main-center", layout_set_main_c),
+    layout_sets_entry::new(c"spiral", layout_set_spiral),
+    layout_sets_entry::new(c"grid", layout_set_grid),
 ];
 
+/// Names of the preset layouts in order.
+pub fn layout_set_names() -> impl Iterator<Item = *const u8> {
+    LAYOUT_SETS.iter().map(|ls| ls.name.as_ptr())
+}
+
 pub unsafe fn layout_set_lookup(name: *const u8) -> i32 {
     unsafe {
         let mut matched: i32 = -1;
@@ -63,15 +71,27 @@ pub unsafe fn layout_set_lookup(name: *const u8) -> i32 {
     }
 }
 
+/// Arrange a window with a preset layout, keeping the pane constraints. The
+/// layout is recorded in the history only once the constraints are back.
+unsafe fn layout_set_arrange(w: *mut window, layout: u32) {
+    unsafe {
+        if let Some(arrange) = LAYOUT_SETS[layout as usize].arrange {
+            let constraints = layout_constraints_save(w);
+            layout_history_hold(w);
+            arrange(w);
+            layout_constraints_restore(w, constraints);
+            layout_history_release(w);
+        }
+    }
+}
+
 pub unsafe fn layout_set_select(w: *mut window, mut layout: u32) -> u32 {
     unsafe {
         if layout > LAYOUT_SETS_LEN as u32 - 1 {
             layout = LAYOUT_SETS_LEN as u32 - 1;
         }
 
-        if let Some(arrange) = LAYOUT_SETS[layout as usize].arrange {
-            arrange(w);
-        }
+        layout_set_arrange(w, layout);
 
         (*w).lastlayout = layout as i32;
         layout
@@ -91,9 +111,7 @@ pub unsafe fn layout_set_next(w: *mut window) -> u32 {
             }
         }
 
-        if let Some(arrange) = LAYOUT_SETS[layout as usize].arrange {
-            arrange(w);
-        }
+        layout_set_arrange(w, layout);
         (*w).lastlayout = layout as i32;
         layout
     }
@@ -114,9 +132,7 @@ pub unsafe fn layout_set_previous(w: *mut window) -> u32 {
             }
         }
 
-        if let Some(arrange) = LAYOUT_SETS[layout as usize].arrange {
-            arrange(w);
-        }
+        layout_set_arrange(w, layout);
         (*w).lastlayout = layout as i32;
         layout
     }
@@ -735,3 +751,332 @@ pub unsafe fn layout_set_tiled(w: *mut window) {
         server_redraw_window(w);
     }
 }
+
+/// Add a column of `n` panes starting at `wp` to a cell, returning the first
+/// pane not used.
+unsafe fn layout_set_add_column(
+    w: *mut window,
+    lc: *mut layout_cell,
+    sx: u32,
+    sy: u32,
+    mut wp: *mut window_pane,
+    n: u32,
+) -> *mut window_pane {
+    unsafe {
+        let lccolumn = layout_create_cell(lc);
+        layout_set_size(lccolumn, sx, sy, 0, 0);
+        tailq_insert_tail(&raw mut (*lc).cells, lccolumn);
+
+        if n == 1 {
+            layout_make_leaf(lccolumn, wp);
+            return tailq_next::<_, _, discr_entry>(wp);
+        }
+
+        layout_make_node(lccolumn, layout_type::LAYOUT_TOPBOTTOM);
+        for _ in 0..n {
+            let lcchild = layout_create_cell(lccolumn);
+            layout_set_size(lcchild, sx, PANE_MINIMUM, 0, 0);
+            layout_make_leaf(lcchild, wp);
+            tailq_insert_tail(&raw mut (*lccolumn).cells, lcchild);
+            wp = tailq_next::<_, _, discr_entry>(wp);
+        }
+        layout_spread_cell(w, lccolumn);
+        wp
+    }
+}
+
+pub unsafe fn layout_set_main_c(w: *mut window) {
+    let __func__ = c!("layout_set_main_c");
+    unsafe {
+        let mut cause: *mut u8 = null_mut();
+
+        layout_print_cell((*w).layout_root, __func__, 1);
+
+        // Get number of panes.
+        let mut n = window_count_panes(w);
+        if n <= 1 {
+            return;
+        }
+        n -= 1; // take off main pane
+
+        // With only one other pane there is no left column.
+        let (nleft, nright) = (n / 2, n - n / 2);
+        let columns = if nleft == 0 { 1 } else { 2 };
+
+        // Find available width - take off one line for each border.
+        let sx = (*w).sx - columns;
+
+        // Get the main pane width.
+        let s = options_get_string_((*w).options, c"main-pane-width");
+        let mut mainw = args_string_percentage(s, 0, sx as i64, sx as i64, &raw mut cause) as u32;
+        if !cause.is_null() {
+            mainw = 80;
+            free_(cause);
+            cause = null_mut();
+        }
+
+        // Work out the other pane widths.
+        let mut otherw: u32;
+        if mainw + (columns * PANE_MINIMUM) >= sx {
+            if sx <= (columns + 1) * PANE_MINIMUM {
+                mainw = PANE_MINIMUM;
+            } else {
+                mainw = sx - (columns * PANE_MINIMUM);
+            }
+            otherw = PANE_MINIMUM;
+        } else {
+            let s = options_get_string_((*w).options, c"other-pane-width");
+            otherw = args_string_percentage(s, 0, sx as i64, sx as i64, &raw mut cause) as u32;
+            if !cause.is_null() || otherw == 0 {
+                otherw = (sx - mainw) / columns;
+                free_(cause);
+            } else if otherw * columns > sx || sx - (otherw * columns) < mainw {
+                otherw = (sx - mainw) / columns;
+            } else {
+                mainw = sx - (otherw * columns);
+            }
+        }
+        let mut leftw = otherw;
+        let mut rightw = otherw;
+        if columns == 1 {
+            leftw = 0;
+            if mainw + rightw < sx {
+                rightw = sx - mainw;
+            }
+        } else if mainw + leftw + rightw < sx {
+            rightw = sx - mainw - leftw;
+        }
+
+        // Work out what height is needed.
+        let mut sy = (nright * (PANE_MINIMUM + 1)) - 1;
+        if sy < (*w).sy {
+            sy = (*w).sy;
+        }
+
+        // Free old tree and create a new root.
+        layout_free(w);
+        let lc = layout_create_cell(null_mut());
+        (*w).layout_root = lc;
+        layout_set_size(lc, mainw + leftw + rightw + columns, sy, 0, 0);
+        layout_make_node(lc, layout_type::LAYOUT_LEFTRIGHT);
+
+        // Create the left column, the main pane and the right column.
+        let wpmain = tailq_first(&raw mut (*w).panes);
+        let mut wp = tailq_next::<_, _, discr_entry>(wpmain);
+        if nleft != 0 {
+            wp = layout_set_add_column(w, lc, leftw, sy, wp, nleft);
+        }
+
+        let lcmain = layout_create_cell(lc);
+        layout_set_size(lcmain, mainw, sy, 0, 0);
+        layout_make_leaf(lcmain, wpmain);
+        tailq_insert_tail(&raw mut (*lc).cells, lcmain);
+
+        layout_set_add_column(w, lc, rightw, sy, wp, nright);
+
+        // Fix cell offsets.
+        layout_fix_offsets(w);
+        layout_fix_panes(w, null_mut());
+
+        layout_print_cell((*w).layout_root, __func__, 1);
+
+        window_resize(w, (*lc).sx, (*lc).sy, -1, -1);
+        notify_window(c"window-layout-changed", w);
+        server_redraw_window(w);
+    }
+}
+
+/// Smallest size needed for a spiral of `n` panes starting at `depth`.
+fn layout_set_spiral_needed(n: u32, depth: u32) -> (u32, u32) {
+    if n <= 1 {
+        return (PANE_MINIMUM, PANE_MINIMUM);
+    }
+    let (x, y) = layout_set_spiral_needed(n - 1, depth + 1);
+    if depth.is_multiple_of(2) {
+        (PANE_MINIMUM + 1 + x, y)
+    } else {
+        (x, PANE_MINIMUM + 1 + y)
+    }
+}
+
+/// Fill a cell with a spiral of `n` panes starting at `wp`. Each level gives
+/// half the cell to one pane and the rest to the next level, turning
+/// clockwise so the panes wind inwards.
+unsafe fn layout_set_spiral_cell(lc: *mut layout_cell, wp: *mut window_pane, n: u32, depth: u32) {
+    unsafe {
+        if n == 1 {
+            layout_make_leaf(lc, wp);
+            return;
+        }
+
+        let (needx, needy) = layout_set_spiral_needed(n - 1, depth + 1);
+        let (type_, size, need) = if depth.is_multiple_of(2) {
+            (layout_type::LAYOUT_LEFTRIGHT, (*lc).sx, needx)
+        } else {
+            (layout_type::LAYOUT_TOPBOTTOM, (*lc).sy, needy)
+        };
+        layout_make_node(lc, type_);
+
+        // Work out the size of this pane and the remainder.
+        let mut first = size / 2;
+        if first + 1 + need > size {
+            first = size - 1 - need;
+        }
+        if first < PANE_MINIMUM {
+            first = PANE_MINIMUM;
+        }
+        let rest = size - 1 - first;
+
+        let lcmain = layout_create_cell(lc);
+        let lcrest = layout_create_cell(lc);
+        if type_ == layout_type::LAYOUT_LEFTRIGHT {
+            layout_set_size(lcmain, first, (*lc).sy, 0, 0);
+            layout_set_size(lcrest, rest, (*lc).sy, 0, 0);
+        } else {
+            layout_set_size(lcmain, (*lc).sx, first, 0, 0);
+            layout_set_size(lcrest, (*lc).sx, rest, 0, 0);
+        }
+        layout_make_leaf(lcmain, wp);
+
+        // Left and top first, then right and bottom.
+        if depth % 4 < 2 {
+            tailq_insert_tail(&raw mut (*lc).cells, lcmain);
+            tailq_insert_tail(&raw mut (*lc).cells, lcrest);
+        } else {
+            tailq_insert_tail(&raw mut (*lc).cells, lcrest);
+            tailq_insert_tail(&raw mut (*lc).cells, lcmain);
+        }
+
+        layout_set_spiral_cell(
+            lcrest,
+            tailq_next::<_, _, discr_entry>(wp),
+            n - 1,
+            depth + 1,
+        );
+    }
+}
+
+pub unsafe fn layout_set_spiral(w: *mut window) {
+    let __func__ = c!("layout_set_spiral");
+    unsafe {
+        layout_print_cell((*w).layout_root, __func__, 1);
+
+        // Get number of panes.
+        let n = window_count_panes(w);
+        if n <= 1 {
+            return;
+        }
+
+        // Work out what size is needed.
+        let (mut sx, mut sy) = layout_set_spiral_needed(n, 0);
+        if sx < (*w).sx {
+            sx = (*w).sx;
+        }
+        if sy < (*w).sy {
+            sy = (*w).sy;
+        }
+
+        // Free old tree and create a new root.
+        layout_free(w);
+        let lc = layout_create_cell(null_mut());
+        (*w).layout_root = lc;
+        layout_set_size(lc, sx, sy, 0, 0);
+        layout_set_spiral_cell(lc, tailq_first(&raw mut (*w).panes), n, 0);
+
+        // Fix cell offsets.
+        layout_fix_offsets(w);
+        layout_fix_panes(w, null_mut());
+
+        layout_print_cell((*w).layout_root, __func__, 1);
+
+        window_resize(w, (*lc).sx, (*lc).sy, -1, -1);
+        notify_window(c"window-layout-changed", w);
+        server_redraw_window(w);
+    }
+}
+
+pub unsafe fn layout_set_grid(w: *mut window) {
+    let __func__ = c!("layout_set_grid");
+    unsafe {
+        layout_print_cell((*w).layout_root, __func__, 1);
+
+        // Get number of panes.
+        let n = window_count_panes(w);
+        if n <= 1 {
+            return;
+        }
+
+        // How many rows and columns are wanted?
+        let mut columns = options_get_number_((*w).options, c"grid-layout-columns") as u32;
+        if columns > n {
+            columns = n;
+        }
+        let rows = n.div_ceil(columns);
+
+        // Work out what size is needed.
+        let mut sx = (columns * (PANE_MINIMUM + 1)) - 1;
+        if sx < (*w).sx {
+            sx = (*w).sx;
+        }
+        let mut sy = (rows * (PANE_MINIMUM + 1)) - 1;
+        if sy < (*w).sy {
+            sy = (*w).sy;
+        }
+
+        // Free old tree and create a new root.
+        layout_free(w);
+        let lc = layout_create_cell(null_mut());
+        (*w).layout_root = lc;
+        layout_set_size(lc, sx, sy, 0, 0);
+        if rows == 1 {
+            layout_make_node(lc, layout_type::LAYOUT_LEFTRIGHT);
+        } else {
+            layout_make_node(lc, layout_type::LAYOUT_TOPBOTTOM);
+        }
+
+        // Create the rows, each with up to the number of columns.
+        let mut wp = tailq_first(&raw mut (*w).panes);
+        let mut left = n;
+        while !wp.is_null() {
+            let count = left.min(columns);
+            left -= count;
+
+            let lcrow = if rows == 1 {
+                lc
+            } else {
+                let lcrow = layout_create_cell(lc);
+                layout_set_size(lcrow, sx, PANE_MINIMUM, 0, 0);
+                tailq_insert_tail(&raw mut (*lc).cells, lcrow);
+                if count == 1 {
+                    layout_make_leaf(lcrow, wp);
+                    wp = tailq_next::<_, _, discr_entry>(wp);
+                    continue;
+                }
+                layout_make_node(lcrow, layout_type::LAYOUT_LEFTRIGHT);
+                lcrow
+            };
+
+            for _ in 0..count {
+                let lcchild = layout_create_cell(lcrow);
+                layout_set_size(lcchild, PANE_MINIMUM, (*lcrow).sy, 0, 0);
+                layout_make_leaf(lcchild, wp);
+                tailq_insert_tail(&raw mut (*lcrow).cells, lcchild);
+                wp = tailq_next::<_, _, discr_entry>(wp);
+            }
+            layout_spread_cell(w, lcrow);
+        }
+        if rows != 1 {
+            layout_spread_cell(w, lc);
+        }
+
+        // Fix cell offsets.
+        layout_fix_offsets(w);
+        layout_fix_panes(w, null_mut());
+
+        layout_print_cell((*w).layout_root, __func__, 1);
+
+        window_resize(w, (*lc).sx, (*lc).sy, -1, -1);
+        notify_window(c"window-layout-changed", w);
+        server_redraw_window(w);
+    }
+}
@@ -0,0 +1,482 @@
+//! Human-readable layout descriptions.
+//!
+//! Unlike the strings from `layout_dump`, a description gives the shape of a
+//! layout rather than cell coordinates, so it can be written by hand:
+//!
+//! ```text
+//! h(30% "htop", v("vim", 10 pane))
+//! ```
+//!
+//! `h(...)` places its children side by side and `v(...)` stacks them from top
+//! to bottom. Any node may be preceded by a size, either a percentage of the
+//! parent (`30%`) or a fixed number of cells (`10`); nodes without a size share
+//! the remaining space. A pane is written as `pane`, as a quoted shell command,
+//! or as `pane` followed by a command. A size on its own is a pane with no
+//! command. Commands are only used when creating a window with `new-window -L`.
+use crate::*;
+
+use std::ffi::CString;
+
+use crate::compat::queue::{tailq_first, tailq_insert_tail, tailq_next};
+
+/// Requested size of a layout node.
+#[derive(Copy, Clone, Eq, PartialEq)]
+pub enum layout_spec_size {
+    Auto,
+    Percent(u32),
+    Cells(u32),
+}
+
+/// Parsed layout description. A node with no children is a pane.
+pub struct layout_spec {
+    pub size: layout_spec_size,
+    pub type_: layout_type,
+    pub command: Option<CString>,
+    pub children: Vec<layout_spec>,
+}
+
+struct layout_spec_parser<'a> {
+    s: &'a [u8],
+    pos: usize,
+}
+
+impl layout_spec_parser<'_> {
+    fn peek(&self) -> Option<u8> {
+        self.s.get(self.pos).copied()
+    }
+
+    fn skip_space(&mut self) {
+        while self.peek().is_some_and(|ch| ch.is_ascii_whitespace()) {
+            self.pos += 1;
+        }
+    }
+
+    fn error(&self, msg: &str) -> String {
+        format!("{msg} at position {}", self.pos + 1)
+    }
+
+    fn keyword(&mut self, word: &[u8]) -> bool {
+        let end = self.pos + word.len();
+        if self.s.get(self.pos..end) != Some(word) {
+            return false;
+        }
+        if self
+            .s
+            .get(end)
+            .is_some_and(|ch| ch.is_ascii_alphanumeric() || *ch == b'_')
+        {
+            return false;
+        }
+        self.pos = end;
+        true
+    }
+
+    fn size(&mut self) -> Result<layout_spec_size, String> {
+        let start = self.pos;
+        let mut value: u32 = 0;
+        while let Some(ch) = self.peek()
+            && ch.is_ascii_digit()
+        {
+            value = value
+                .checked_mul(10)
+                .and_then(|v| v.checked_add((ch - b'0') as u32))
+                .filter(|v| *v <= WINDOW_MAXIMUM)
+                .ok_or_else(|| self.error("size too large"))?;
+            self.pos += 1;
+        }
+        if self.pos == start {
+            return Ok(layout_spec_size::Auto);
+        }
+
+        if self.peek() == Some(b'%') {
+            if value == 0 || value > 100 {
+                self.pos = start;
+                return Err(self.error("percentage must be between 1 and 100"));
+            }
+            self.pos += 1;
+            return Ok(layout_spec_size::Percent(value));
+        }
+        if value < PANE_MINIMUM {
+            self.pos = start;
+            return Err(self.error("size too small"));
+        }
+        Ok(layout_spec_size::Cells(value))
+    }
+
+    fn string(&mut self) -> Result<CString, String> {
+        let start = self.pos;
+        let quote = self.s[self.pos];
+        self.pos += 1;
+
+        let mut out = Vec::new();
+        loop {
+            match self.peek() {
+                None => {
+                    self.pos = start;
+                    return Err(self.error("unterminated string"));
+                }
+                Some(ch) if ch == quote => {
+                    self.pos += 1;
+                    break;
+                }
+                Some(b'\\') if quote == b'"' && self.s.get(self.pos + 1).is_some() => {
+                    out.push(self.s[self.pos + 1]);
+                    self.pos += 2;
+                }
+                Some(ch) => {
+                    out.push(ch);
+                    self.pos += 1;
+                }
+            }
+        }
+        if out.is_empty() {
+            self.pos = start;
+            return Err(self.error("empty command"));
+        }
+        CString::new(out).map_err(|_| self.error("invalid command"))
+    }
+
+    fn node(&mut self) -> Result<layout_spec, String> {
+        self.skip_space();
+        let size = self.size()?;
+        let sized = size != layout_spec_size::Auto;
+        self.skip_space();
+
+        let type_ = match self.peek() {
+            Some(b'h') if self.s.get(self.pos + 1) == Some(&b'(') => {
+                Some(layout_type::LAYOUT_LEFTRIGHT)
+            }
+            Some(b'v') if self.s.get(self.pos + 1) == Some(&b'(') => {
+                Some(layout_type::LAYOUT_TOPBOTTOM)
+            }
+            _ => None,
+        };
+        if let Some(type_) = type_ {
+            self.pos += 2;
+            let mut children = Vec::new();
+            loop {
+                children.push(self.node()?);
+                self.skip_space();
+                match self.peek() {
+                    Some(b',') => self.pos += 1,
+                    Some(b')') => {
+                        self.pos += 1;
+                        break;
+                    }
+                    _ => return Err(self.error("expected ',' or ')'")),
+                }
+            }
+
+            // A split with one child is the same as the child.
+            if children.len() == 1 {
+                let mut child = children.pop().unwrap();
+                if sized {
+                    child.size = size;
+                }
+                return Ok(child);
+            }
+            return Ok(layout_spec {
+                size,
+                type_,
+                command: None,
+                children,
+            });
+        }
+
+        let mut command = None;
+        if self.keyword(b"pane") {
+            self.skip_space();
+            if let Some(b'"' | b'\'') = self.peek() {
+                command = Some(self.string()?);
+            }
+        } else if let Some(b'"' | b'\'') = self.peek() {
+            command = Some(self.string()?);
+        } else if !sized || !matches!(self.peek(), None | Some(b',' | b')')) {
+            return Err(self.error("expected pane, command or split"));
+        }
+        Ok(layout_spec {
+            size,
+            type_: layout_type::LAYOUT_WINDOWPANE,
+            command,
+            children: Vec::new(),
+        })
+    }
+}
+
+/// Parse a layout description.
+pub fn layout_spec_parse(s: &[u8]) -> Result<layout_spec, String> {
+    let mut parser = layout_spec_parser { s, pos: 0 };
+
+    let spec = parser.node()?;
+    parser.skip_space();
+    if parser.peek().is_some() {
+        return Err(parser.error("unexpected text"));
+    }
+    Ok(spec)
+}
+
+/// Is this a layout description rather than a string from `layout_dump`?
+pub unsafe fn layout_spec_is_description(layout: *const u8) -> bool {
+    unsafe {
+        let s = CStr::from_ptr(layout.cast()).to_bytes();
+        !(s.len() > 4 && s[..4].iter().all(u8::is_ascii_hexdigit) && s[4] == b',')
+    }
+}
+
+/// Count the panes in a description.
+pub fn layout_spec_count(spec: &layout_spec) -> u32 {
+    if spec.children.is_empty() {
+        return 1;
+    }
+    spec.children.iter().map(layout_spec_count).sum()
+}
+
+/// Get the panes in a description, in order.
+pub fn layout_spec_leaves<'a>(spec: &'a layout_spec, leaves: &mut Vec<&'a layout_spec>) {
+    if spec.children.is_empty() {
+        leaves.push(spec);
+    }
+    for child in &spec.children {
+        layout_spec_leaves(child, leaves);
+    }
+}
+
+/// Smallest size a description can be laid out in.
+fn layout_spec_needed(spec: &layout_spec) -> (u32, u32) {
+    if spec.children.is_empty() {
+        return (PANE_MINIMUM, PANE_MINIMUM);
+    }
+
+    let borders = spec.children.len() as u32 - 1;
+    let needed = spec.children.iter().map(layout_spec_needed);
+    if spec.type_ == layout_type::LAYOUT_LEFTRIGHT {
+        let sy = needed.clone().map(|n| n.1).max().unwrap_or(PANE_MINIMUM);
+        (needed.map(|n| n.0).sum::<u32>() + borders, sy)
+    } else {
+        let sx = needed.clone().map(|n| n.0).max().unwrap_or(PANE_MINIMUM);
+        (sx, needed.map(|n| n.1).sum::<u32>() + borders)
+    }
+}
+
+/// Share the size of a split between its children.
+fn layout_spec_sizes(spec: &layout_spec, size: u32) -> Vec<u32> {
+    let children = &spec.children;
+    let available = size.saturating_sub(children.len() as u32 - 1);
+    let horizontal = spec.type_ == layout_type::LAYOUT_LEFTRIGHT;
+
+    let needed: Vec<u32> = children
+        .iter()
+        .map(|child| {
+            let (sx, sy) = layout_spec_needed(child);
+            if horizontal { sx } else { sy }
+        })
+        .collect();
+
+    // Fixed and percentage sizes first, then share the rest.
+    let mut sizes: Vec<u32> = children
+        .iter()
+        .map(|child| match child.size {
+            layout_spec_size::Auto => 0,
+            layout_spec_size::Percent(pct) => available * pct / 100,
+            layout_spec_size::Cells(cells) => cells,
+        })
+        .collect();
+    let flexible = children
+        .iter()
+        .filter(|child| child.size == layout_spec_size::Auto)
+        .count() as u32;
+    if flexible != 0 {
+        let mut rest = available.saturating_sub(sizes.iter().sum());
+        let mut left = flexible;
+        for (i, child) in children.iter().enumerate() {
+            if child.size == layout_spec_size::Auto {
+                sizes[i] = rest / left;
+                rest -= sizes[i];
+                left -= 1;
+            }
+        }
+    }
+    for (size, needed) in sizes.iter_mut().zip(&needed) {
+        if *size < *needed {
+            *size = *needed;
+        }
+    }
+
+    // Make the sizes add up, growing the last unsized child or shrinking from
+    // the end.
+    let total: u32 = sizes.iter().sum();
+    if total < available {
+        let last = children
+            .iter()
+            .rposition(|child| child.size == layout_spec_size::Auto)
+            .unwrap_or(children.len() - 1);
+        sizes[last] += available - total;
+    } else {
+        let mut excess = total - available;
+        for i in (0..sizes.len()).rev() {
+            let take = excess.min(sizes[i] - needed[i]);
+            sizes[i] -= take;
+            excess -= take;
+        }
+    }
+    sizes
+}
+
+/// Construct the cells for a description, adding the leaf cells to a list.
+unsafe fn layout_spec_construct(
+    spec: &layout_spec,
+    lcparent: *mut layout_cell,
+    sx: u32,
+    sy: u32,
+    leaves: &mut Vec<*mut layout_cell>,
+) -> *mut layout_cell {
+    unsafe {
+        let lc = layout_create_cell(lcparent);
+        layout_set_size(lc, sx, sy, 0, 0);
+        if spec.children.is_empty() {
+            leaves.push(lc);
+            return lc;
+        }
+        layout_make_node(lc, spec.type_);
+
+        let horizontal = spec.type_ == layout_type::LAYOUT_LEFTRIGHT;
+        let sizes = layout_spec_sizes(spec, if horizontal { sx } else { sy });
+        for (child, size) in spec.children.iter().zip(sizes) {
+            let lcchild = if horizontal {
+                layout_spec_construct(child, lc, size, sy, leaves)
+            } else {
+                layout_spec_construct(child, lc, sx, size, leaves)
+            };
+            tailq_insert_tail(&raw mut (*lc).cells, lcchild);
+        }
+        lc
+    }
+}
+
+/// Build a new layout tree for a window from a description, replacing the
+/// existing one. The leaf cells are returned in order without panes; the
+/// caller must assign a pane to each of them.
+pub unsafe fn layout_spec_build(w: *mut window, spec: &layout_spec) -> Vec<*mut layout_cell> {
+    unsafe {
+        let (mut sx, mut sy) = layout_spec_needed(spec);
+        if sx < (*w).sx {
+            sx = (*w).sx;
+        }
+        if sy < (*w).sy {
+            sy = (*w).sy;
+        }
+
+        let mut leaves = Vec::new();
+        let lc = layout_spec_construct(spec, null_mut(), sx, sy, &mut leaves);
+
+        window_resize(w, (*lc).sx, (*lc).sy, -1, -1);
+        layout_free(w);
+        (*w).layout_root = lc;
+        leaves
+    }
+}
+
+/// Apply a layout description to the existing panes of a window.
+pub unsafe fn layout_spec_apply(w: *mut window, layout: *const u8, cause: *mut *mut u8) -> i32 {
+    let __func__ = c!("layout_spec_apply");
+    unsafe {
+        let spec = match layout_spec_parse(CStr::from_ptr(layout.cast()).to_bytes()) {
+            Ok(spec) => spec,
+            Err(error) => {
+                *cause = format_nul!("{error}");
+                return -1;
+            }
+        };
+
+        let npanes = window_count_panes(w);
+        let ncells = layout_spec_count(&spec);
+        if npanes != ncells {
+            *cause = format_nul!("have {} panes but layout has {}", npanes, ncells);
+            return -1;
+        }
+
+        let constraints = layout_constraints_save(w);
+        let leaves = layout_spec_build(w, &spec);
+        let mut wp = tailq_first(&raw mut (*w).panes);
+        for lc in leaves {
+            layout_make_leaf(lc, wp);
+            wp = tailq_next::<_, _, discr_entry>(wp);
+        }
+
+        layout_fix_offsets(w);
+        layout_fix_panes(w, null_mut());
+        recalculate_sizes();
+        layout_constraints_restore(w, constraints);
+
+        layout_print_cell((*w).layout_root, __func__, 0);
+
+        notify_window(c"window-layout-changed", w);
+        0
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn error(s: &str) -> String {
+        match layout_spec_parse(s.as_bytes()) {
+            Ok(_) => panic!("{s} should not parse"),
+            Err(error) => error,
+        }
+    }
+
+    #[test]
+    fn test_parse_tree() {
+        let spec = layout_spec_parse(br#"h(30% "htop", v('vim', 10 pane))"#).unwrap();
+        assert!(spec.type_ == layout_type::LAYOUT_LEFTRIGHT);
+        assert_eq!(layout_spec_count(&spec), 3);
+
+        let left = &spec.children[0];
+        assert!(left.size == layout_spec_size::Percent(30));
+        assert_eq!(left.command.as_deref(), Some(c"htop"));
+
+        let right = &spec.children[1];
+        assert!(right.type_ == layout_type::LAYOUT_TOPBOTTOM);
+        assert!(right.children[1].size == layout_spec_size::Cells(10));
+        assert!(right.children[1].command.is_none());
+    }
+
+    #[test]
+    fn test_parse_single_child_split() {
+        let spec = layout_spec_parse(b"v(h(pane, 50%))").unwrap();
+        assert!(spec.type_ == layout_type::LAYOUT_LEFTRIGHT);
+        assert_eq!(layout_spec_count(&spec), 2);
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert_eq!(error("h(pane pane)"), "expected ',' or ')' at position 8");
+        assert_eq!(
+            error("v(pane, 150%)"),
+            "percentage must be between 1 and 100 at position 9"
+        );
+        assert_eq!(error("h(\"vim, pane)"), "unterminated string at position 3");
+        assert_eq!(
+            error("h(pane,)"),
+            "expected pane, command or split at position 8"
+        );
+        assert_eq!(error("pane x"), "unexpected text at position 6");
+    }
+
+    #[test]
+    fn test_sizes() {
+        let spec = layout_spec_parse(b"h(25%, 10, pane, pane)").unwrap();
+        assert_eq!(layout_spec_sizes(&spec, 103), vec![25, 10, 32, 33]);
+    }
+
+    #[test]
+    fn test_description_or_dump() {
+        unsafe {
+            assert!(!layout_spec_is_description(c!(
+                "bb62,159x48,0,0{79x48,0,0,79x48,80,0}"
+            )));
+            assert!(layout_spec_is_description(c!("h(pane, pane)")));
+        }
+    }
+}
@@ -0,0 +1,218 @@
+//! Tabbed pane stacks.
+//!
+//! A stack is a layout cell shared by several panes. The cell's `wp` is the
+//! visible tab and `tabs` lists every pane in the stack in tab order; the
+//! other panes keep the same `layout_cell` so they are sized with it but are
+//! not drawn. A cell that is not a stack has an empty `tabs`.
+use crate::*;
+
+use crate::compat::queue::{
+    tailq_first, tailq_foreach, tailq_insert_after, tailq_insert_head, tailq_prev, tailq_remove,
+};
+
+/// Stacks taken out of a window while its layout is rebuilt.
+pub struct layout_stack_saved {
+    stacks: Vec<(*mut window_pane, Vec<*mut window_pane>)>,
+    hidden: Vec<(*mut window_pane, *mut window_pane)>,
+}
+
+/// Is this pane a tab that is not currently shown?
+pub unsafe fn layout_stack_hidden(wp: *mut window_pane) -> bool {
+    unsafe {
+        let lc = (*wp).layout_cell;
+        !lc.is_null() && !(*lc).wp.is_null() && (*lc).wp != wp
+    }
+}
+
+/// Get the panes in the stack containing a pane, empty if it is not stacked.
+pub unsafe fn layout_stack_tabs(wp: *mut window_pane) -> Vec<*mut window_pane> {
+    unsafe {
+        let lc = (*wp).layout_cell;
+        if lc.is_null() {
+            return Vec::new();
+        }
+        (*lc).tabs.clone()
+    }
+}
+
+/// Add a pane to the stack of another pane in the same window, making a new
+/// stack if needed.
+pub unsafe fn layout_stack_add(dst: *mut window_pane, wp: *mut window_pane, select: bool) {
+    unsafe {
+        let w = (*dst).window;
+
+        if (*wp).layout_cell == (*dst).layout_cell {
+            return;
+        }
+        layout_close_pane(wp);
+
+        let lc = (*dst).layout_cell;
+        if (*lc).tabs.is_empty() {
+            (*lc).tabs.push((*lc).wp);
+        }
+        let idx = (*lc).tabs.iter().position(|&tab| tab == dst).unwrap_or(0);
+        (*lc).tabs.insert(idx + 1, wp);
+        (*wp).layout_cell = lc;
+
+        if select {
+            layout_stack_select(wp);
+        } else if (*w).active == wp {
+            window_set_active_pane(w, (*lc).wp, 1);
+        }
+        layout_fix_panes(w, null_mut());
+        notify_window(c"window-layout-changed", w);
+    }
+}
+
+/// Take a pane out of its stack, leaving it without a layout cell. Returns
+/// false if the pane was not in a stack.
+pub unsafe fn layout_stack_remove(wp: *mut window_pane) -> bool {
+    unsafe {
+        let lc = (*wp).layout_cell;
+        if lc.is_null() {
+            return false;
+        }
+        let tabs = &mut (*lc).tabs;
+        let Some(idx) = tabs.iter().position(|&tab| tab == wp) else {
+            return false;
+        };
+
+        tabs.remove(idx);
+        if (*lc).wp == wp {
+            (*lc).wp = tabs[idx.min(tabs.len() - 1)];
+            (*(*lc).wp).flags |= window_pane_flags::PANE_REDRAW;
+        }
+        if tabs.len() == 1 {
+            tabs.clear();
+        }
+        (*wp).layout_cell = null_mut();
+
+        server_redraw_window((*wp).window);
+        true
+    }
+}
+
+/// Show a pane in its stack. This does not change the active pane.
+pub unsafe fn layout_stack_select(wp: *mut window_pane) {
+    unsafe {
+        let lc = (*wp).layout_cell;
+        if lc.is_null() || (*lc).wp == wp {
+            return;
+        }
+        (*lc).wp = wp;
+        (*wp).flags |= window_pane_flags::PANE_REDRAW;
+        server_redraw_window((*wp).window);
+    }
+}
+
+/// Show the next or previous tab in a pane's stack, keeping it the active
+/// pane if the stack was active. Returns the pane now shown.
+pub unsafe fn layout_stack_cycle(wp: *mut window_pane, forward: bool) -> *mut window_pane {
+    unsafe {
+        let w = (*wp).window;
+        let lc = (*wp).layout_cell;
+        if lc.is_null() || (*lc).tabs.is_empty() {
+            return wp;
+        }
+        let tabs = &(*lc).tabs;
+        let idx = tabs.iter().position(|&tab| tab == (*lc).wp).unwrap_or(0);
+        let next = if forward {
+            tabs[(idx + 1) % tabs.len()]
+        } else {
+            tabs[(idx + tabs.len() - 1) % tabs.len()]
+        };
+
+        let active = (*w).active == (*lc).wp;
+        layout_stack_select(next);
+        if active {
+            window_set_active_pane(w, next, 1);
+        }
+        next
+    }
+}
+
+/// Swap two panes between their layout cells, including any stacks.
+pub unsafe fn layout_stack_swap(src: *mut window_pane, dst: *mut window_pane) {
+    unsafe {
+        let swap = |wp: *mut window_pane| {
+            if wp == src {
+                dst
+            } else if wp == dst {
+                src
+            } else {
+                wp
+            }
+        };
+
+        let src_lc = (*src).layout_cell;
+        let dst_lc = (*dst).layout_cell;
+        for lc in [src_lc, dst_lc] {
+            (*lc).wp = swap((*lc).wp);
+            for tab in (*lc).tabs.iter_mut() {
+                *tab = swap(*tab);
+            }
+            if src_lc == dst_lc {
+                break;
+            }
+        }
+        (*src).layout_cell = dst_lc;
+        (*dst).layout_cell = src_lc;
+    }
+}
+
+/// Take the hidden tabs out of a window so the layout can be rebuilt from
+/// the visible panes alone. Each stack stays with its visible pane.
+pub unsafe fn layout_stack_detach(w: *mut window) -> layout_stack_saved {
+    unsafe {
+        let mut saved = layout_stack_saved {
+            stacks: Vec::new(),
+            hidden: Vec::new(),
+        };
+
+        for wp in tailq_foreach::<_, discr_entry>(&raw mut (*w).panes).map(NonNull::as_ptr) {
+            let lc = (*wp).layout_cell;
+            if lc.is_null() {
+                continue;
+            }
+            if layout_stack_hidden(wp) {
+                saved.hidden.push((wp, tailq_prev::<_, _, discr_entry>(wp)));
+            } else if !(*lc).tabs.is_empty() {
+                saved.stacks.push((wp, std::mem::take(&mut (*lc).tabs)));
+            }
+        }
+        for &(wp, _) in &saved.hidden {
+            tailq_remove::<_, discr_entry>(&raw mut (*w).panes, wp);
+            (*wp).layout_cell = null_mut();
+        }
+
+        saved
+    }
+}
+
+/// Put back the tabs taken out by layout_stack_detach.
+pub unsafe fn layout_stack_attach(w: *mut window, saved: layout_stack_saved) {
+    unsafe {
+        for &(wp, prev) in &saved.hidden {
+            if prev.is_null() {
+                tailq_insert_head::<_, discr_entry>(&raw mut (*w).panes, wp);
+            } else {
+                tailq_insert_after::<_, discr_entry>(&raw mut (*w).panes, prev, wp);
+            }
+        }
+
+        for (wp, tabs) in saved.stacks {
+            let lc = (*wp).layout_cell;
+            if lc.is_null() {
+                continue;
+            }
+            for &tab in &tabs {
+                (*tab).layout_cell = lc;
+            }
+            (*lc).tabs = tabs;
+        }
+
+        if !tailq_first(&raw mut (*w).panes).is_null() && !(*w).layout_root.is_null() {
+            layout_fix_panes(w, null_mut());
+        }
+    }
+}
@@ -763,6 +763,18 @@ bitflags::bitflags! {
     }
 }
 
+/// Grid export formats.
+#[repr(i32)]
+#[derive(Copy, Clone, Eq, PartialEq, num_enum::TryFromPrimitive)]
+enum grid_export_format {
+    GRID_EXPORT_TEXT = 0,
+    GRID_EXPORT_ANSI = 1,
+    GRID_EXPORT_HTML = 2,
+    GRID_EXPORT_RTF = 3,
+    GRID_EXPORT_SVG = 4,
+    GRID_EXPORT_JSON = 5,
+}
+
 /// Cell positions.
 #[repr(i32)]
 #[derive(Copy, Clone, Eq, PartialEq)]
@@ -872,10 +884,15 @@ struct grid_line {
 
     flags: grid_line_flag,
     time: time_t,
+
+    marks: u32,
 }
 
 const GRID_HISTORY: i32 = 0x1; // scroll lines into history
 
+/// Number of named marks (a to z).
+const GRID_MARKS: usize = 26;
+
 /// Entire grid of cells.
 #[repr(C)]
 struct grid {
@@ -888,6 +905,13 @@ struct grid {
     hsize: u32,
     hlimit: u32,
 
+    /// lines trimmed from the top of the history so far
+    htrimmed: u64,
+    /// column of each named mark, the line is flagged in grid_line
+    markx: [u32; GRID_MARKS],
+    /// line of each named mark counting trimmed lines, checked against the flag
+    marky: [u64; GRID_MARKS],
+
     linedata: *mut grid_line,
 }
 
@@ -1267,6 +1291,21 @@ struct window_pane_resize {
 }
 type window_pane_resizes = tailq_head<window_pane_resize>;
 
+/// Named capture checkpoint, an absolute line in the pane history.
+crate::compat::impl_tailq_entry!(
+    window_pane_checkpoint,
+    entry,
+    tailq_entry<window_pane_checkpoint>
+);
+#[repr(C)]
+struct window_pane_checkpoint {
+    name: *mut u8,
+    line: u64,
+
+    entry: tailq_entry<window_pane_checkpoint>,
+}
+type window_pane_checkpoints = tailq_head<window_pane_checkpoint>;
+
 bitflags::bitflags! {
     #[repr(transparent)]
     #[derive(Copy, Clone, Eq, PartialEq)]
@@ -1285,6 +1324,7 @@ bitflags::bitflags! {
         const PANE_EMPTY = 0x800;
         const PANE_STYLECHANGED = 0x1000;
         const PANE_UNSEENCHANGES = 0x2000;
+        const PANE_ZOOMED = 0x4000;
     }
 }
 
@@ -1327,6 +1367,8 @@ struct window_pane {
     resize_queue: window_pane_resizes,
     resize_timer: event,
 
+    checkpoints: window_pane_checkpoints,
+
     ictx: *mut input_ctx,
 
     cached_gc: grid_cell,
@@ -1342,12 +1384,16 @@ struct window_pane {
 
     status_screen: screen,
     status_size: usize,
+    status_text_size: u32,
 
     modes: tailq_head<window_mode_entry>,
 
     searchstr: *mut u8,
     searchregex: i32,
 
+    /// name of the input group, or null
+    group: *mut u8,
+
     border_gc_set: i32,
     border_gc: grid_cell,
 
@@ -1412,7 +1458,9 @@ struct window {
     lastlayout: i32,
     layout_root: *mut layout_cell,
     saved_layout_root: *mut layout_cell,
+    zoom_type: layout_type,
     old_layout: *mut u8,
+    layout_history: *mut layout_history,
 
     sx: u32,
     sy: u32,
@@ -1521,6 +1569,23 @@ enum layout_type {
     LAYOUT_WINDOWPANE,
 }
 
+/// Size limit for a layout cell, in cells or as a percentage of the window.
+#[derive(Copy, Clone, Default, Eq, PartialEq)]
+enum layout_limit {
+    #[default]
+    None,
+    Cells(u32),
+    Percent(u32),
+}
+
+/// Minimum and maximum size of a pane in one direction. A fixed size has
+/// both set to the same value.
+#[derive(Copy, Clone, Default, Eq, PartialEq)]
+struct layout_constraint {
+    min: layout_limit,
+    max: layout_limit,
+}
+
 /// Layout cells queue.
 type layout_cells = tailq_head<layout_cell>;
 
@@ -1541,6 +1606,11 @@ struct layout_cell {
     wp: *mut window_pane,
     cells: layout_cells,
 
+    constraint_x: layout_constraint,
+    constraint_y: layout_constraint,
+
+    tabs: Vec<*mut window_pane>,
+
     entry: tailq_entry<layout_cell>,
 }
 
@@ -2275,6 +2345,19 @@ const PROMPT_INCREMENTAL: i32 = 0x4;
 const PROMPT_NOFORMAT: i32 = 0x8;
 const PROMPT_KEY: i32 = 0x8;
 
+/// Window being dragged along the status line by a client and where it would
+/// be dropped.
+#[derive(Copy, Clone)]
+struct status_drag {
+    session: u32,
+    window: u32,
+    dropping: bool,
+    target: i32,
+    before: bool,
+    x: u32,
+    line: u32,
+}
+
 //#[derive(Copy, Clone)]
 crate::compat::impl_tailq_entry!(client, entry, tailq_entry<client>);
 // #[derive(crate::compat::TailQEntry)]
@@ -2366,6 +2449,9 @@ struct client {
     pan_ox: c_uint,
     pan_oy: c_uint,
 
+    pane_drag: layout_drag,
+    status_drag: status_drag,
+
     overlay_check: overlay_check_cb,
     overlay_mode: overlay_mode_cb,
     overlay_draw: overlay_draw_cb,
@@ -2599,7 +2685,8 @@ use crate::format::{
     format_each, format_expand, format_expand_time, format_flags, format_free, format_get_pane,
     format_grid_hyperlink, format_grid_line, format_grid_word, format_job_tree, format_log_debug,
     format_lost_client, format_merge, format_pretty_time, format_single, format_single_from_state,
-    format_single_from_target, format_skip, format_tidy_jobs, format_tree, format_true,
+    format_single_from_target, format_skip, format_table_names, format_tidy_jobs, format_tree,
+    format_true,
 };
 
 mod format_draw_;
@@ -2637,13 +2724,15 @@ bitflags::bitflags! {
         const JOB_KEEPWRITE = 2;
         const JOB_PTY = 4;
         const JOB_DEFAULTSHELL = 8;
+        const JOB_STDERR = 16;
+        const JOB_PGROUP = 32;
     }
 }
 mod job_;
 use crate::job_::{
-    job, job_check_died, job_complete_cb, job_free, job_free_cb, job_get_data, job_get_event,
-    job_get_status, job_kill_all, job_print_summary, job_resize, job_run, job_still_running,
-    job_transfer, job_update_cb,
+    job, job_check_died, job_complete_cb, job_free, job_free_cb, job_get_data, job_get_error_event,
+    job_get_event, job_get_pid, job_get_status, job_kill_all, job_kill_group, job_print_summary,
+    job_resize, job_run, job_still_running, job_transfer, job_update_cb,
 };
 
 mod environ_;
@@ -2749,10 +2838,11 @@ use crate::cmd_::cmd_queue::{
     cmdq_get_name, cmdq_get_source, cmdq_get_state, cmdq_get_target, cmdq_get_target_client,
     cmdq_guard, cmdq_insert_after, cmdq_insert_hook, cmdq_item, cmdq_link_state, cmdq_list,
     cmdq_merge_formats, cmdq_new, cmdq_new_state, cmdq_next, cmdq_print, cmdq_print_data,
-    cmdq_running, cmdq_state,
+    cmdq_remove_group, cmdq_running, cmdq_state,
 };
 
 use crate::cmd_::cmd_wait_for::cmd_wait_for_flush;
+use crate::cmd_::cmd_wait_for_output::{cmd_wait_for_output_cancel, cmd_wait_for_output_check};
 
 mod client_;
 use crate::client_::client_main;
@@ -2766,7 +2856,7 @@ use crate::key_bindings_::{
 };
 
 mod key_string;
-use crate::key_string::{key_string_lookup_key, key_string_lookup_string};
+use crate::key_string::{key_string_lookup_key, key_string_lookup_string, key_string_names};
 
 mod alerts;
 use crate::alerts::{alerts_check_session, alerts_queue, alerts_reset_all};
@@ -2783,8 +2873,10 @@ use crate::file::{
 mod server;
 use crate::server::{
     CLIENTS, CURRENT_TIME, MARKED_PANE, MESSAGE_LOG, SERVER_PROC, server_add_accept,
-    server_add_message, server_check_marked, server_clear_marked, server_create_socket,
-    server_is_marked, server_set_marked, server_start, server_update_socket,
+    server_add_message, server_check_marked, server_check_marked_panes, server_clear_marked,
+    server_create_socket, server_is_marked, server_marked_panes, server_move_marked,
+    server_set_marked, server_start, server_toggle_marked, server_unmark_pane,
+    server_update_socket,
 };
 
 mod server_client;
@@ -2850,9 +2942,16 @@ use crate::grid_::{
     GRID_DEFAULT_CELL, grid_adjust_lines, grid_cells_equal, grid_cells_look_equal, grid_clear,
     grid_clear_history, grid_clear_lines, grid_collect_history, grid_compare, grid_create,
     grid_destroy, grid_duplicate_lines, grid_empty_line, grid_get_cell, grid_get_line,
-    grid_line_length, grid_move_cells, grid_move_lines, grid_peek_line, grid_reflow,
+    grid_get_mark, grid_line_length, grid_move_cells, grid_move_lines, grid_peek_line, grid_reflow,
     grid_remove_history, grid_scroll_history, grid_scroll_history_region, grid_set_cell,
-    grid_set_cells, grid_set_padding, grid_string_cells, grid_unwrap_position, grid_wrap_position,
+    grid_set_cells, grid_set_mark, grid_set_padding, grid_string_cells, grid_string_cells_code,
+    grid_unwrap_position, grid_wrap_position,
+};
+
+mod grid_export_;
+use crate::grid_export_::{
+    grid_export, grid_export_cell, grid_export_create, grid_export_find, grid_export_finish,
+    grid_export_newline, grid_export_trim_newline,
 };
 
 mod grid_reader_;
@@ -2915,41 +3014,79 @@ mod window_;
 use crate::window_::{
     ALL_WINDOW_PANES, WINDOWS, window_add_pane, window_add_ref, window_cmp, window_count_panes,
     window_create, window_destroy_panes, window_find_by_id, window_find_by_id_str,
-    window_find_string, window_get_active_at, window_has_pane, window_lost_pane,
-    window_pane_at_index, window_pane_cmp, window_pane_default_cursor, window_pane_destroy_ready,
-    window_pane_exited, window_pane_find_by_id, window_pane_find_by_id_str, window_pane_find_down,
-    window_pane_find_left, window_pane_find_right, window_pane_find_up, window_pane_get_new_data,
+    window_find_string, window_get_active_at, window_get_status_at, window_has_pane,
+    window_lost_pane, window_pane_at_index, window_pane_cmp, window_pane_default_cursor,
+    window_pane_destroy_ready, window_pane_exited, window_pane_find_by_id,
+    window_pane_find_by_id_str, window_pane_find_down, window_pane_find_left,
+    window_pane_find_right, window_pane_find_up, window_pane_get_new_data, window_pane_in_group,
     window_pane_index, window_pane_key, window_pane_mode, window_pane_next_by_number,
     window_pane_previous_by_number, window_pane_reset_mode, window_pane_reset_mode_all,
-    window_pane_resize, window_pane_search, window_pane_send_resize, window_pane_set_event,
-    window_pane_set_mode, window_pane_stack_push, window_pane_stack_remove,
-    window_pane_start_input, window_pane_update_focus, window_pane_update_used_data,
-    window_pane_visible, window_pop_zoom, window_printable_flags, window_push_zoom,
-    window_redraw_active_switch, window_remove_pane, window_remove_ref, window_resize,
-    window_set_active_pane, window_set_fill_character, window_set_name, window_unzoom,
-    window_update_activity, window_update_focus, window_zoom, winlink_add, winlink_clear_flags,
-    winlink_cmp, winlink_count, winlink_find_by_index, winlink_find_by_window,
-    winlink_find_by_window_id, winlink_next, winlink_next_by_number, winlink_previous,
-    winlink_previous_by_number, winlink_remove, winlink_set_window, winlink_shuffle_up,
-    winlink_stack_push, winlink_stack_remove,
+    window_pane_resize, window_pane_search, window_pane_send_resize, window_pane_set_checkpoint,
+    window_pane_set_event, window_pane_set_group, window_pane_set_mode, window_pane_stack_push,
+    window_pane_stack_remove, window_pane_start_input, window_pane_update_focus,
+    window_pane_update_used_data, window_pane_visible, window_pop_zoom, window_printable_flags,
+    window_push_zoom, window_redraw_active_switch, window_remove_pane, window_remove_ref,
+    window_resize, window_rezoom, window_set_active_pane, window_set_fill_character,
+    window_set_name, window_unzoom, window_update_activity, window_update_focus, window_zoom,
+    window_zoom_panes, winlink_add, winlink_clear_flags, winlink_cmp, winlink_count,
+    winlink_find_by_index, winlink_find_by_window, winlink_find_by_window_id, winlink_next,
+    winlink_next_by_number, winlink_previous, winlink_previous_by_number, winlink_remove,
+    winlink_set_window, winlink_shuffle_up, winlink_stack_push, winlink_stack_remove,
 };
 
 mod layout;
 use crate::layout::{
-    layout_assign_pane, layout_close_pane, layout_count_cells, layout_create_cell,
-    layout_destroy_cell, layout_fix_offsets, layout_fix_panes, layout_free, layout_free_cell,
-    layout_init, layout_make_leaf, layout_make_node, layout_print_cell, layout_resize,
+    layout_assign_pane, layout_cell_limits, layout_close_pane, layout_constraint_parse,
+    layout_constraint_string, layout_constraints_fit, layout_constraints_restore,
+    layout_constraints_save, layout_count_cells, layout_create_cell, layout_destroy_cell,
+    layout_fix_offsets, layout_fix_panes, layout_free, layout_free_cell, layout_init,
+    layout_init_panes, layout_make_leaf, layout_make_node, layout_print_cell, layout_resize,
     layout_resize_adjust, layout_resize_layout, layout_resize_pane, layout_resize_pane_to,
     layout_search_by_border, layout_set_size, layout_split_pane, layout_spread_cell,
     layout_spread_out,
 };
 
 mod layout_custom;
-use crate::layout_custom::{layout_dump, layout_parse};
+use crate::layout_custom::{
+    layout_apply_cells, layout_dump, layout_parse, layout_parse_adapt, layout_parse_cells,
+};
+
+mod layout_history_;
+use crate::layout_history_::{
+    layout_history, layout_history_add, layout_history_free, layout_history_hold,
+    layout_history_release, layout_history_step,
+};
+mod layout_stack;
+use crate::layout_stack::{
+    layout_stack_add, layout_stack_attach, layout_stack_cycle, layout_stack_detach,
+    layout_stack_hidden, layout_stack_remove, layout_stack_select, layout_stack_swap,
+    layout_stack_tabs,
+};
+
+mod layout_drag_;
+use crate::layout_drag_::{layout_drag, layout_drag_area, layout_drag_start};
+
+mod layout_saved_;
+use crate::layout_saved_::{
+    format_defaults_layout_saved, layout_saved, layout_saved_count, layout_saved_draw,
+    layout_saved_get, layout_saved_names, layout_saved_remove, layout_saved_set,
+};
+
+mod trash;
+use crate::trash::{
+    format_defaults_trash, trash_count, trash_entry, trash_find, trash_find_window, trash_free,
+    trash_kill_other_panes, trash_kill_pane, trash_kill_window, trash_restore, trash_walk,
+};
+
+mod layout_spec_;
+use crate::layout_spec_::{
+    layout_spec, layout_spec_apply, layout_spec_build, layout_spec_is_description,
+    layout_spec_leaves, layout_spec_parse,
+};
 
 mod layout_set;
 use crate::layout_set::{
-    layout_set_lookup, layout_set_next, layout_set_previous, layout_set_select,
+    layout_set_lookup, layout_set_names, layout_set_next, layout_set_previous, layout_set_select,
 };
 
 mod mode_tree;
@@ -2970,9 +3107,19 @@ use crate::window_buffer::WINDOW_BUFFER_MODE;
 mod window_tree;
 use crate::window_tree::WINDOW_TREE_MODE;
 
+mod window_layout;
+use crate::window_layout::WINDOW_LAYOUT_MODE;
+mod window_trash;
+use crate::window_trash::WINDOW_TRASH_MODE;
+mod window_search;
+use crate::window_search::{WINDOW_SEARCH_MODE, window_search_any, window_search_flags};
+
 mod window_clock;
 use crate::window_clock::{WINDOW_CLOCK_MODE, WINDOW_CLOCK_TABLE};
 
+mod window_resize;
+use crate::window_resize::WINDOW_RESIZE_MODE;
+
 mod window_client;
 use crate::window_client::WINDOW_CLIENT_MODE;
 
@@ -2980,7 +3127,8 @@ mod window_copy;
 use crate::window_copy::window_copy_add;
 use crate::window_copy::{
     WINDOW_COPY_MODE, WINDOW_VIEW_MODE, window_copy_get_line, window_copy_get_word,
-    window_copy_pagedown, window_copy_pageup, window_copy_start_drag, window_copy_vadd,
+    window_copy_pagedown, window_copy_pageup, window_copy_show_match, window_copy_start_drag,
+    window_copy_vadd,
 };
 
 mod window_customize;
@@ -3082,6 +3230,9 @@ use crate::server_acl::{
     server_acl_user_deny_write, server_acl_user_find,
 };
 
+mod git;
+use crate::git::{git_get, git_info};
+
 mod hyperlinks_;
 use crate::hyperlinks_::{
     hyperlinks, hyperlinks_copy, hyperlinks_free, hyperlinks_get, hyperlinks_init, hyperlinks_put,
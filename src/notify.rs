@@ -364,6 +364,10 @@ pub unsafe fn notify_window(name: &'static CStr, w: *mut window) {
     unsafe {
         let mut fs: cmd_find_state = zeroed();
 
+        if name == c"window-layout-changed" {
+            layout_history_add(w);
+        }
+
         cmd_find_from_window(&raw mut fs, w, 0);
         notify_add(
             name,
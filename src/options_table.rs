@@ -81,6 +81,8 @@ static mut OPTIONS_TABLE_POPUP_BORDER_LINES_LIST: [*const u8; 8] = [
 ];
 static mut OPTIONS_TABLE_SET_CLIPBOARD_LIST: [*const u8; 4] =
     [c!("off"), c!("external"), c!("on"), null()];
+static mut OPTIONS_TABLE_COPY_PIPE_FORMAT_LIST: [*const u8; 5] =
+    [c!("text"), c!("ansi"), c!("html"), c!("rtf"), null()];
 static mut OPTIONS_TABLE_WINDOW_SIZE_LIST: [*const u8; 5] = [
     c!("largest"),
     c!("smallest"),
@@ -251,7 +253,7 @@ macro_rules! options_table_window_hook {
     };
 }
 
-pub static mut OPTIONS_TABLE: [options_table_entry; 191] = [
+pub static mut OPTIONS_TABLE: [options_table_entry; 201] = [
     options_table_entry {
         name: c!("backspace"),
         type_: options_table_type::OPTIONS_TABLE_KEY,
@@ -294,6 +296,17 @@ pub static mut OPTIONS_TABLE: [options_table_entry; 191] = [
         text: c!("Shell command run when text is copied. If empty, no command is run."),
         ..unsafe { zeroed() }
     },
+    options_table_entry {
+        name: c!("copy-pipe-format"),
+        type_: options_table_type::OPTIONS_TABLE_CHOICE,
+        scope: OPTIONS_TABLE_SERVER,
+        choices: &raw const OPTIONS_TABLE_COPY_PIPE_FORMAT_LIST as *const *const u8,
+        default_num: 0,
+        text: c!(
+            "Format of text passed to the copy-pipe commands: plain 'text', or with colours and attributes as 'ansi' escape sequences, 'html' or 'rtf'."
+        ),
+        ..unsafe { zeroed() }
+    },
     options_table_entry {
         name: c!("cursor-colour"),
         type_: options_table_type::OPTIONS_TABLE_COLOUR,
@@ -497,6 +510,31 @@ pub static mut OPTIONS_TABLE: [options_table_entry; 191] = [
         text: c!("List of terminal features, used if they cannot be automatically detected."),
         ..unsafe { zeroed() }
     },
+    options_table_entry {
+        name: c!("trash-limit"),
+        type_: options_table_type::OPTIONS_TABLE_NUMBER,
+        scope: OPTIONS_TABLE_SERVER,
+        minimum: 0,
+        maximum: i32::MAX as u32,
+        default_num: 10,
+        text: c!(
+            "Maximum number of killed windows and panes kept so they can be restored; 0 disables the trash."
+        ),
+        ..unsafe { zeroed() }
+    },
+    options_table_entry {
+        name: c!("trash-time"),
+        type_: options_table_type::OPTIONS_TABLE_NUMBER,
+        scope: OPTIONS_TABLE_SERVER,
+        minimum: 0,
+        maximum: i32::MAX as u32,
+        default_num: 600,
+        unit: c!("seconds"),
+        text: c!(
+            "Time after which killed windows and panes are removed from the trash; 0 for never."
+        ),
+        ..unsafe { zeroed() }
+    },
     options_table_entry {
         name: c!("user-keys"),
         type_: options_table_type::OPTIONS_TABLE_STRING,
@@ -1094,6 +1132,26 @@ pub static mut OPTIONS_TABLE: [options_table_entry; 191] = [
         text: c!("Character used to fill unused parts of window."),
         ..unsafe { zeroed() }
     },
+    options_table_entry {
+        name: c!("grid-layout-columns"),
+        type_: options_table_type::OPTIONS_TABLE_NUMBER,
+        scope: OPTIONS_TABLE_WINDOW,
+        minimum: 1,
+        maximum: WINDOW_MAXIMUM,
+        default_num: 2,
+        text: c!("Number of columns in the 'grid' layout."),
+        ..unsafe { zeroed() }
+    },
+    options_table_entry {
+        name: c!("layout-history-limit"),
+        type_: options_table_type::OPTIONS_TABLE_NUMBER,
+        scope: OPTIONS_TABLE_WINDOW,
+        minimum: 0,
+        maximum: i32::MAX as u32,
+        default_num: 50,
+        text: c!("Maximum number of layouts kept for 'undo-layout' and 'redo-layout'."),
+        ..unsafe { zeroed() }
+    },
     options_table_entry {
         name: c!("main-pane-height"),
         type_: options_table_type::OPTIONS_TABLE_STRING,
@@ -1110,7 +1168,7 @@ pub static mut OPTIONS_TABLE: [options_table_entry; 191] = [
         scope: OPTIONS_TABLE_WINDOW,
         default_str: c!("80"),
         text: c!(
-            "Width of the main pane in the 'main-vertical' layout. This may be a percentage, for example '10%'."
+            "Width of the main pane in the 'main-vertical' and 'main-center' layouts. This may be a percentage, for example '10%'."
         ),
         ..unsafe { zeroed() }
     },
@@ -1175,7 +1233,7 @@ pub static mut OPTIONS_TABLE: [options_table_entry; 191] = [
         scope: OPTIONS_TABLE_WINDOW,
         default_str: c!("0"),
         text: c!(
-            "Height of the other panes in the 'main-vertical' layout. This may be a percentage, for example '10%'."
+            "Width of the other panes in the 'main-vertical' and 'main-center' layouts. This may be a percentage, for example '10%'."
         ),
         ..unsafe { zeroed() }
     },
@@ -1257,6 +1315,46 @@ pub static mut OPTIONS_TABLE: [options_table_entry; 191] = [
         text: c!("The default colour palette for colours zero to 255."),
         ..unsafe { zeroed() }
     },
+    options_table_entry {
+        name: c!("pane-drop-style"),
+        type_: options_table_type::OPTIONS_TABLE_STRING,
+        scope: OPTIONS_TABLE_WINDOW,
+        default_str: c!("bg=blue"),
+        flags: OPTIONS_TABLE_IS_STYLE,
+        separator: c!(","),
+        text: c!("Style of the area a pane will be dropped into when it is dragged by its title."),
+        ..unsafe { zeroed() }
+    },
+    options_table_entry {
+        name: c!("pane-group-border-style"),
+        type_: options_table_type::OPTIONS_TABLE_STRING,
+        scope: OPTIONS_TABLE_WINDOW,
+        default_str: c!("fg=yellow"),
+        flags: OPTIONS_TABLE_IS_STYLE,
+        separator: c!(","),
+        text: c!("Style of the pane border of panes in an input group."),
+        ..unsafe { zeroed() }
+    },
+    options_table_entry {
+        name: c!("pane-marked-border-style"),
+        type_: options_table_type::OPTIONS_TABLE_STRING,
+        scope: OPTIONS_TABLE_WINDOW,
+        default_str: c!("reverse"),
+        flags: OPTIONS_TABLE_IS_STYLE,
+        separator: c!(","),
+        text: c!("Style of the pane border of marked panes."),
+        ..unsafe { zeroed() }
+    },
+    options_table_entry {
+        name: c!("pane-stack-format"),
+        type_: options_table_type::OPTIONS_TABLE_STRING,
+        scope: OPTIONS_TABLE_WINDOW | OPTIONS_TABLE_PANE,
+        default_str: c!(
+            "#{?pane_stack_visible,#[reverse],} #{pane_index}:#{pane_current_command} #[default]"
+        ),
+        text: c!("Format of each tab in the status line of a pane stack."),
+        ..unsafe { zeroed() }
+    },
     options_table_entry {
         name: c!("popup-style"),
         type_: options_table_type::OPTIONS_TABLE_STRING,
@@ -1398,6 +1496,18 @@ pub static mut OPTIONS_TABLE: [options_table_entry; 191] = [
         text: c!("Style of the current window in the status line."),
         ..unsafe { zeroed() }
     },
+    options_table_entry {
+        name: c!("window-status-drop-style"),
+        type_: options_table_type::OPTIONS_TABLE_STRING,
+        scope: OPTIONS_TABLE_WINDOW,
+        default_str: c!("reverse"),
+        flags: OPTIONS_TABLE_IS_STYLE,
+        separator: c!(","),
+        text: c!(
+            "Style of the marker showing where a window dragged along the status line will be dropped."
+        ),
+        ..unsafe { zeroed() }
+    },
     options_table_entry {
         name: c!("window-status-format"),
         type_: options_table_type::OPTIONS_TABLE_STRING,
@@ -71,7 +71,7 @@ fn paste_cmp_times(a: *const paste_buffer, b: *const paste_buffer) -> Ordering {
         let x = (*a).order;
         let y = (*b).order;
 
-        u32::cmp(&x, &y)
+        u32::cmp(&y, &x)
     }
 }
 
@@ -342,3 +342,23 @@ pub unsafe fn paste_make_sample(pb: *mut paste_buffer) -> *mut u8 {
         buf
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_newest_first() {
+        let older = paste_buffer {
+            order: 1,
+            ..unsafe { zeroed() }
+        };
+        let newer = paste_buffer {
+            order: 2,
+            ..unsafe { zeroed() }
+        };
+        assert_eq!(paste_cmp_times(&newer, &older), Ordering::Less);
+        assert_eq!(paste_cmp_times(&older, &newer), Ordering::Greater);
+        assert_eq!(paste_cmp_times(&older, &older), Ordering::Equal);
+    }
+}
@@ -52,7 +52,7 @@ pub unsafe fn resize_window(w: *mut window, mut sx: u32, mut sy: u32, xpixel: i3
 
         /* Restore the window zoom state. */
         if zoomed {
-            window_zoom((*w).active);
+            window_rezoom(w);
         }
 
         tty_update_window_offset(w);
@@ -102,7 +102,7 @@ pub unsafe fn screen_reinit(s: *mut screen) {
         (*s).cy = 0;
 
         (*s).rupper = 0;
-        (*s).rlower = screen_size_y(s) - 1;
+        (*s).rlower = screen_size_y(s).saturating_sub(1);
 
         (*s).mode =
             mode_flag::MODE_CURSOR | mode_flag::MODE_WRAP | ((*s).mode & mode_flag::MODE_CRLF);
@@ -447,13 +447,20 @@ pub unsafe fn screen_redraw_make_pane_status(
 
         if wp.as_ptr() == server_client_get_pane(c) {
             style_apply(&mut gc, (*w).options, c!("pane-active-border-style"), ft);
+        } else if !(*wp.as_ptr()).group.is_null() {
+            style_apply(&mut gc, (*w).options, c!("pane-group-border-style"), ft);
         } else {
             style_apply(&mut gc, (*w).options, c!("pane-border-style"), ft);
         }
         let wp = wp.as_ptr();
         let fmt = options_get_string_((*wp).options, c"pane-border-format");
 
-        let expanded = format_expand_time(ft, fmt);
+        let tabs = layout_stack_tabs(wp);
+        let expanded = if tabs.is_empty() {
+            format_expand_time(ft, fmt)
+        } else {
+            screen_redraw_make_tab_strip(c, wp, &tabs)
+        };
         if (*wp).sx < 4 {
             (*wp).status_size = 0;
             width = 0;
@@ -491,6 +498,7 @@ pub unsafe fn screen_redraw_make_pane_status(
             0,
         );
         screen_write_stop(ctx.as_mut_ptr());
+        (*wp).status_text_size = format_width(expanded).min(width);
 
         free_(expanded);
         format_free(ft);
@@ -504,6 +512,40 @@ pub unsafe fn screen_redraw_make_pane_status(
     }
 }
 
+/// Build the status line of a pane stack from pane-stack-format expanded for
+/// each tab.
+unsafe fn screen_redraw_make_tab_strip(
+    c: *mut client,
+    wp: *mut window_pane,
+    tabs: &[*mut window_pane],
+) -> *mut u8 {
+    unsafe {
+        let fmt = options_get_string_((*wp).options, c"pane-stack-format");
+
+        let mut strip = String::new();
+        for &tab in tabs {
+            let ft = format_create(
+                c,
+                null_mut(),
+                (FORMAT_PANE | (*tab).id) as i32,
+                format_flags::FORMAT_STATUS,
+            );
+            format_defaults(
+                ft,
+                c,
+                NonNull::new((*c).session),
+                NonNull::new((*(*c).session).curw),
+                NonNull::new(tab),
+            );
+            let expanded = format_expand_time(ft, fmt);
+            strip.push_str(cstr_to_str(expanded));
+            free_(expanded);
+            format_free(ft);
+        }
+        format_nul!("{}", strip)
+    }
+}
+
 /// Draw pane status.
 pub unsafe fn screen_redraw_draw_pane_status(ctx: *mut screen_redraw_ctx) {
     unsafe {
@@ -550,7 +592,7 @@ pub unsafe fn screen_redraw_draw_pane_status(ctx: *mut screen_redraw_ctx) {
                 ((*ctx).ox - xoff, 0, size - ((*ctx).ox - xoff))
             } else {
                 // Right not visible
-                (0, xoff - (*ctx).ox, size - (xoff - (*ctx).ox))
+                (0, xoff - (*ctx).ox, (*ctx).sx - (xoff - (*ctx).ox))
             };
 
             if (*ctx).statustop != 0 {
@@ -770,6 +812,13 @@ pub unsafe fn screen_redraw_draw_borders_style(
                 c!("pane-active-border-style"),
                 ft,
             );
+        } else if !(*wp).group.is_null() {
+            style_apply(
+                &raw mut (*wp).border_gc,
+                oo,
+                c!("pane-group-border-style"),
+                ft,
+            );
         } else {
             style_apply(&raw mut (*wp).border_gc, oo, c!("pane-border-style"), ft);
         }
@@ -824,10 +873,12 @@ pub unsafe fn screen_redraw_draw_borders_cell(ctx: *mut screen_redraw_ctx, i: u3
             }
             memcpy__(&raw mut gc, tmp);
 
-            if server_is_marked(s, (*s).curw, MARKED_PANE.wp)
-                && screen_redraw_check_is(ctx, x, y, MARKED_PANE.wp) != 0
-            {
-                gc.attr ^= grid_attr::GRID_ATTR_REVERSE;
+            for fs in server_marked_panes() {
+                if fs.s == s && fs.wl == (*s).curw && screen_redraw_check_is(ctx, x, y, fs.wp) != 0
+                {
+                    style_add(&raw mut gc, oo, c!("pane-marked-border-style"), null_mut());
+                    break;
+                }
             }
         }
         screen_redraw_border_set(w, wp, (*ctx).pane_lines, cell_type, &raw mut gc);
@@ -962,6 +1013,21 @@ pub unsafe fn screen_redraw_draw_status(ctx: *mut screen_redraw_ctx) {
                 null_mut(),
             );
         }
+
+        let drag = &(*c).status_drag;
+        if drag.dropping && drag.line < (*ctx).statuslines && drag.x < (*c).tty.sx {
+            let mut gc = GRID_DEFAULT_CELL;
+            style_add(
+                &raw mut gc,
+                (*w).options,
+                c!("window-status-drop-style"),
+                null_mut(),
+            );
+            gc.attr |= grid_attr::GRID_ATTR_CHARSET;
+            utf8_set(&raw mut gc.data, b'x');
+            tty_cursor(tty, drag.x, y + drag.line);
+            tty_cell(tty, &raw mut gc, &GRID_DEFAULT_CELL, null_mut(), null_mut());
+        }
     }
 }
 
@@ -993,6 +1059,8 @@ pub unsafe fn screen_redraw_draw_pane(ctx: *mut screen_redraw_ctx, wp: *mut wind
             0
         };
 
+        let drop = layout_drag_area(c, wp);
+
         for j in 0..(*wp).sy {
             if (*wp).yoff + j < (*ctx).oy || (*wp).yoff + j >= (*ctx).oy + (*ctx).sy {
                 continue;
@@ -1030,6 +1098,29 @@ pub unsafe fn screen_redraw_draw_pane(ctx: *mut screen_redraw_ctx, wp: *mut wind
 
             tty_default_colours(&raw mut defaults, wp);
             tty_draw_line(tty, s, i, j, width, x, y, &raw mut defaults, palette);
+
+            if let Some((dx, dy, dsx, dsy)) = drop
+                && j >= dy
+                && j < dy + dsy
+            {
+                let start = i.max(dx);
+                let end = (i + width).min(dx + dsx);
+                if start < end {
+                    let mut gc = defaults;
+                    style_add(&raw mut gc, (*w).options, c!("pane-drop-style"), null_mut());
+                    tty_draw_line(
+                        tty,
+                        s,
+                        start,
+                        j,
+                        end - start,
+                        x + start - i,
+                        y,
+                        &raw mut gc,
+                        palette,
+                    );
+                }
+            }
         }
 
         #[cfg(feature = "sixel")]
@@ -36,6 +36,8 @@ pub static mut SERVER_EXIT: c_int = 0;
 pub static mut SERVER_EV_ACCEPT: event = unsafe { zeroed() };
 pub static mut SERVER_EV_TIDY: event = unsafe { zeroed() };
 pub static mut MARKED_PANE: cmd_find_state = unsafe { zeroed() };
+/// Every marked pane, including MARKED_PANE which is the last one marked.
+pub static mut MARKED_PANES: Vec<cmd_find_state> = Vec::new();
 pub static mut MESSAGE_NEXT: c_uint = 0;
 pub static mut MESSAGE_LOG: message_list = unsafe { zeroed() };
 pub static mut CURRENT_TIME: time_t = unsafe { zeroed() };
@@ -47,31 +49,105 @@ pub unsafe fn server_set_marked(s: *mut session, wl: *mut winlink, wp: *mut wind
         MARKED_PANE.wl = wl;
         MARKED_PANE.w = (*wl).window;
         MARKED_PANE.wp = wp;
+
+        let marked = &raw mut MARKED_PANES;
+        (*marked).clear();
+        (*marked).push(MARKED_PANE);
     }
 }
 
 pub unsafe fn server_clear_marked() {
     unsafe {
+        let marked = &raw mut MARKED_PANES;
         cmd_find_clear_state(&raw mut MARKED_PANE, 0);
+        (*marked).clear();
     }
 }
 
-pub unsafe fn server_is_marked(s: *mut session, wl: *mut winlink, wp: *mut window_pane) -> bool {
-    if s.is_null() || wl.is_null() || wp.is_null() {
-        return false;
+/// Add a pane to the marked panes or remove it if already there.
+pub unsafe fn server_toggle_marked(s: *mut session, wl: *mut winlink, wp: *mut window_pane) {
+    unsafe {
+        let marked = &raw mut MARKED_PANES;
+        let before = (*marked).len();
+        (*marked).retain(|fs| fs.s != s || fs.wl != wl || fs.wp != wp);
+        if (*marked).len() != before {
+            server_reset_marked();
+            return;
+        }
+
+        let mut fs: cmd_find_state = zeroed();
+        fs.s = s;
+        fs.wl = wl;
+        fs.w = (*wl).window;
+        fs.wp = wp;
+        (*marked).push(fs);
+        cmd_find_copy_state(&raw mut MARKED_PANE, &raw mut fs);
+    }
+}
+
+/// Remove a pane that is going away from the marked panes.
+pub unsafe fn server_unmark_pane(wp: *mut window_pane) {
+    unsafe {
+        let marked = &raw mut MARKED_PANES;
+        (*marked).retain(|fs| fs.wp != wp);
+        if MARKED_PANE.wp == wp {
+            server_reset_marked();
+        }
+    }
+}
+
+/// Update the marked panes for a winlink replaced by another.
+pub unsafe fn server_move_marked(wl: *mut winlink, wl_new: *mut winlink) {
+    unsafe {
+        let marked = &raw mut MARKED_PANES;
+        for fs in (*marked).iter_mut() {
+            if fs.wl == wl {
+                fs.wl = wl_new;
+            }
+        }
     }
+}
 
+/// Make the last of the marked panes the marked pane.
+unsafe fn server_reset_marked() {
     unsafe {
-        if MARKED_PANE.s != s || MARKED_PANE.wl != wl {
-            return false;
+        let marked = &raw mut MARKED_PANES;
+        cmd_find_clear_state(&raw mut MARKED_PANE, 0);
+        if let Some(fs) = (*marked).last_mut() {
+            cmd_find_copy_state(&raw mut MARKED_PANE, fs);
         }
-        if MARKED_PANE.wp != wp {
-            return false;
+    }
+}
+
+/// Drop any marked panes which are no longer valid. This is done once each
+/// time round the server loop and before a command uses the marked panes.
+pub unsafe fn server_check_marked_panes() {
+    unsafe {
+        let marked = &raw mut MARKED_PANES;
+        (*marked).retain(|fs| cmd_find_valid_state(fs));
+        if !server_check_marked() {
+            server_reset_marked();
         }
-        server_check_marked()
     }
 }
 
+/// Get the marked panes. These may include panes which are no longer valid
+/// until the next server_check_marked_panes.
+pub unsafe fn server_marked_panes() -> std::slice::Iter<'static, cmd_find_state> {
+    unsafe {
+        let marked = &raw const MARKED_PANES;
+        (*marked).iter()
+    }
+}
+
+pub unsafe fn server_is_marked(s: *mut session, wl: *mut winlink, wp: *mut window_pane) -> bool {
+    if s.is_null() || wl.is_null() || wp.is_null() {
+        return false;
+    }
+
+    unsafe { server_marked_panes().any(|fs| fs.s == s && fs.wl == wl && fs.wp == wp) }
+}
+
 pub unsafe fn server_check_marked() -> bool {
     unsafe { cmd_find_valid_state(&raw mut MARKED_PANE) }
 }
@@ -291,6 +367,7 @@ pub unsafe fn server_loop() -> i32 {
             }
         }
 
+        server_check_marked_panes();
         server_client_loop();
 
         if options_get_number_(GLOBAL_OPTIONS, c"exit-empty") == 0 && SERVER_EXIT == 0 {
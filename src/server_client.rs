@@ -834,22 +834,21 @@ pub unsafe fn server_client_check_mouse(c: *mut client, event: *mut key_event) -
                 px += (*m).ox;
                 py += (*m).oy;
 
-                /* Try the pane borders if not zoomed. */
+                /* Try the pane status lines and borders if not zoomed. */
                 if !(*(*(*s).curw).window).flags.intersects(window_flag::ZOOMED) {
-                    for wp_ in
-                        tailq_foreach::<_, discr_entry>(&raw mut (*(*(*s).curw).window).panes)
+                    wp = window_get_status_at((*(*s).curw).window, px, py);
+                    if wp.is_null() {
+                        wp = tailq_foreach::<_, discr_entry>(&raw mut (*(*(*s).curw).window).panes)
                             .map(NonNull::as_ptr)
-                    {
-                        wp = wp_;
-                        if ((*wp).xoff + (*wp).sx == px
-                            && (*wp).yoff <= 1 + py
-                            && (*wp).yoff + (*wp).sy >= py)
-                            || ((*wp).yoff + (*wp).sy == py
-                                && (*wp).xoff <= 1 + px
-                                && (*wp).xoff + (*wp).sx >= px)
-                        {
-                            break;
-                        }
+                            .find(|&wp| {
+                                ((*wp).xoff + (*wp).sx == px
+                                    && (*wp).yoff <= 1 + py
+                                    && (*wp).yoff + (*wp).sy >= py)
+                                    || ((*wp).yoff + (*wp).sy == py
+                                        && (*wp).xoff <= 1 + px
+                                        && (*wp).xoff + (*wp).sx >= px)
+                            })
+                            .unwrap_or(null_mut());
                     }
                     if !wp.is_null() {
                         where_ = where_::Border;
@@ -1890,7 +1889,7 @@ pub unsafe fn server_client_key_callback(item: *mut cmdq_item, data: *mut c_void
                  * the mode table instead of the default key table.
                  */
                 table = if server_client_is_default_key_table(c, (*c).keytable) != 0
-                    && wp.is_null()
+                    && !wp.is_null()
                     && ({
                         wme = tailq_first(&raw mut (*wp).modes);
                         !wme.is_null()
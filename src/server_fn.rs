@@ -335,7 +335,12 @@ pub unsafe fn server_destroy_pane(wp: *mut window_pane, notify: i32) {
             (*wp).fd = -1;
         }
 
-        let remain_on_exit = options_get_number_((*wp).options, c"remain-on-exit");
+        // Panes in the trash are always kept so they can be restored.
+        let remain_on_exit = if !trash_find_window(w).is_null() {
+            1
+        } else {
+            options_get_number_((*wp).options, c"remain-on-exit")
+        };
         if remain_on_exit != 0 && !(*wp).flags.intersects(window_pane_flags::PANE_STATUSREADY) {
             return;
         }
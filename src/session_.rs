@@ -779,6 +779,7 @@ pub unsafe fn session_renumber_windows(s: *mut session) {
             if wl == MARKED_PANE.wl {
                 marked_idx = (*wl_new).idx;
             }
+            server_move_marked(wl, wl_new);
             if wl == (*s).curw {
                 new_curw_idx = (*wl_new).idx;
             }
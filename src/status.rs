@@ -13,6 +13,7 @@
 // OUT OF OR IN CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
 use crate::*;
 
+use crate::cmd_::cmd_find;
 use crate::compat::{
     fgetln,
     queue::{tailq_init, tailq_remove},
@@ -20,6 +21,8 @@ use crate::compat::{
 };
 use crate::libc::strncmp;
 
+use std::ffi::CString;
+
 #[repr(C)]
 struct status_prompt_menu {
     c: *mut client,
@@ -1915,27 +1918,14 @@ unsafe fn status_prompt_complete_list(size: *mut u32, s: *const u8, at_start: i3
         let mut list: *mut *mut u8 = null_mut();
         let slen = strlen(s);
 
-        let layouts: [*const u8; 8] = [
-            c!("even-horizontal"),
-            c!("even-vertical"),
-            c!("main-horizontal"),
-            c!("main-horizontal-mirrored"),
-            c!("main-vertical"),
-            c!("main-vertical-mirrored"),
-            c!("tiled"),
-            null_mut(),
-        ];
-
         *size = 0;
-        let mut cmdent: *const *const cmd_entry = (&raw const CMD_TABLE) as *const *const cmd_entry;
-        while !(*cmdent).is_null() {
-            if strncmp((*(*cmdent)).name.as_ptr(), s, slen) == 0 {
-                status_prompt_add_list(&raw mut list, size, (*(*cmdent)).name.as_ptr());
+        for cmdent in CMD_TABLE {
+            if strncmp(cmdent.name.as_ptr(), s, slen) == 0 {
+                status_prompt_add_list(&raw mut list, size, cmdent.name.as_ptr());
             }
-            if !(*(*cmdent)).alias.is_null() && strncmp((*(*cmdent)).alias.as_ptr(), s, slen) == 0 {
-                status_prompt_add_list(&raw mut list, size, (*(*cmdent)).alias.as_ptr());
+            if !cmdent.alias.is_null() && strncmp(cmdent.alias.as_ptr(), s, slen) == 0 {
+                status_prompt_add_list(&raw mut list, size, cmdent.alias.as_ptr());
             }
-            cmdent = cmdent.add(1);
         }
         let o = options_get_only(GLOBAL_OPTIONS, c!("command-alias"));
         if !o.is_null() {
@@ -1970,12 +1960,10 @@ unsafe fn status_prompt_complete_list(size: *mut u32, s: *const u8, at_start: i3
             }
             oe = oe.add(1);
         }
-        let mut layout = (&raw const layouts) as *const *const u8;
-        while !(*layout).is_null() {
-            if strncmp(*layout, s, slen) == 0 {
-                status_prompt_add_list(&raw mut list, size, *layout);
+        for layout in layout_set_names() {
+            if strncmp(layout, s, slen) == 0 {
+                status_prompt_add_list(&raw mut list, size, layout);
             }
-            layout = layout.add(1);
         }
         list
     }
@@ -2049,6 +2037,7 @@ unsafe fn status_prompt_menu_callback(
 unsafe fn status_prompt_complete_list_menu(
     c: *mut client,
     list: *mut *mut u8,
+    names: *mut *mut u8,
     size: u32,
     mut offset: u32,
     flag: u8,
@@ -2087,7 +2076,11 @@ unsafe fn status_prompt_complete_list_menu(
 
         let menu = menu_create(c!(""));
         for i in (*spm).start..size {
-            item.name = SyncCharPtr::from_ptr(*list.add(i as usize));
+            if names.is_null() {
+                item.name = SyncCharPtr::from_ptr(*list.add(i as usize));
+            } else {
+                item.name = SyncCharPtr::from_ptr(*names.add(i as usize));
+            }
             item.key = b'0' as u64 + (i as i64 - (*spm).start as i64) as u64;
             item.command = SyncCharPtr::null();
             menu_add_item(menu, &raw mut item, null_mut(), c, null_mut());
@@ -2311,110 +2304,706 @@ unsafe fn status_prompt_complete_session(
     }
 }
 
+/// What the word being completed in a command prompt is.
+enum status_prompt_context {
+    /// Commands, options or layouts.
+    Any,
+    Command,
+    Flag(&'static cmd_entry),
+    Format,
+    Target,
+    Client,
+    Buffer,
+    Key,
+    KeyTable,
+    Option,
+    Hook,
+    /// Value of the named option.
+    Value(String),
+    Layout,
+    Nothing,
+}
+
+/// Return if a command flag takes an argument.
+unsafe fn status_prompt_flag_argument(entry: &cmd_entry, flag: u8) -> bool {
+    unsafe {
+        let template = cstr_to_str(entry.args.template.as_ptr()).as_bytes();
+        template
+            .windows(2)
+            .any(|pair| pair[0] == flag && pair[1] == b':')
+    }
+}
+
+/// Split a command usage into its flags, with the name of the argument for
+/// those that take one, and the names of its positional arguments.
+unsafe fn status_prompt_usage(
+    entry: &'static cmd_entry,
+) -> (Vec<(u8, Option<&'static str>)>, Vec<&'static str>) {
+    unsafe {
+        let mut flags = Vec::new();
+        let mut positional = Vec::new();
+
+        let mut words = cstr_to_str(entry.usage.as_ptr())
+            .split([' ', '[', ']'])
+            .filter(|word| !word.is_empty() && *word != "...");
+        while let Some(word) = words.next() {
+            match word.strip_prefix('-').map(str::as_bytes) {
+                Some([flag]) if status_prompt_flag_argument(entry, *flag) => {
+                    flags.push((*flag, words.next()))
+                }
+                Some(letters) if !letters.is_empty() => {
+                    flags.extend(letters.iter().map(|flag| (*flag, None)))
+                }
+                _ => positional.push(word),
+            }
+        }
+        (flags, positional)
+    }
+}
+
+/// Work out what a word is from the usage name of the argument it is for.
+fn status_prompt_classify(argument: &str, first: Option<&str>) -> status_prompt_context {
+    match argument {
+        "command" => status_prompt_context::Command,
+        "key" => status_prompt_context::Key,
+        "key-table" => status_prompt_context::KeyTable,
+        "option" => status_prompt_context::Option,
+        "hook" => status_prompt_context::Hook,
+        "value" => match first {
+            Some(option) => status_prompt_context::Value(option.to_string()),
+            None => status_prompt_context::Nothing,
+        },
+        "layout-name" => status_prompt_context::Layout,
+        _ if argument.contains("buffer") => status_prompt_context::Buffer,
+        _ if argument.ends_with("-name") => status_prompt_context::Nothing,
+        _ if argument.contains("client") => status_prompt_context::Client,
+        _ if ["session", "window", "pane"]
+            .iter()
+            .any(|kind| argument.contains(kind)) =>
+        {
+            status_prompt_context::Target
+        }
+        _ => status_prompt_context::Any,
+    }
+}
+
+/// Work out what the word is from the command words before it. A positional
+/// argument named "command" starts a new command, as for bind-key.
+unsafe fn status_prompt_context_words(words: &[&str], word: &str) -> (status_prompt_context, u8) {
+    unsafe {
+        let Some(name) = words.first() else {
+            return (status_prompt_context::Command, b'\0');
+        };
+        let Ok(name) = CString::new(*name) else {
+            return (status_prompt_context::Any, b'\0');
+        };
+        let entry = match cmd_find(name.as_ptr().cast()) {
+            Ok(entry) => entry,
+            Err(cause) => {
+                free_(cause);
+                if word.starts_with("-t") || word.starts_with("-s") {
+                    return (status_prompt_context::Target, word.as_bytes()[1]);
+                }
+                return (status_prompt_context::Any, b'\0');
+            }
+        };
+        let (flags, positional) = status_prompt_usage(entry);
+
+        let mut pending = None;
+        let mut parsing_flags = true;
+        let mut position = 0;
+        let mut first = None;
+        for (i, w) in words.iter().enumerate().skip(1) {
+            if pending.take().is_some() {
+                continue;
+            }
+            if parsing_flags && *w == "--" {
+                parsing_flags = false;
+                continue;
+            }
+            if parsing_flags && w.len() > 1 && w.starts_with('-') {
+                let letters = &w.as_bytes()[1..];
+                if let Some(j) = letters
+                    .iter()
+                    .position(|flag| status_prompt_flag_argument(entry, *flag))
+                    && j + 1 == letters.len()
+                {
+                    pending = Some(letters[j]);
+                }
+                continue;
+            }
+            parsing_flags = false;
+            if positional.get(position) == Some(&"command") {
+                return status_prompt_context_words(&words[i..], word);
+            }
+            if position == 0 {
+                first = Some(*w);
+            }
+            position += 1;
+        }
+
+        let argument = if let Some(flag) = pending {
+            flags
+                .iter()
+                .find(|(letter, _)| *letter == flag)
+                .and_then(|(_, name)| *name)
+        } else if parsing_flags && word.starts_with('-') {
+            match word.as_bytes().get(1) {
+                Some(&flag) if status_prompt_flag_argument(entry, flag) => {
+                    let argument = flags
+                        .iter()
+                        .find(|(letter, _)| *letter == flag)
+                        .and_then(|(_, name)| *name);
+                    return match argument {
+                        Some(argument) => (status_prompt_classify(argument, first), flag),
+                        None => (status_prompt_context::Nothing, b'\0'),
+                    };
+                }
+                _ => return (status_prompt_context::Flag(entry), b'\0'),
+            }
+        } else if position == 0 && positional.is_empty() {
+            return (status_prompt_context::Nothing, b'\0');
+        } else {
+            positional.get(position).copied()
+        };
+        match argument {
+            Some(argument) => (status_prompt_classify(argument, first), b'\0'),
+            None => (status_prompt_context::Any, b'\0'),
+        }
+    }
+}
+
+/// Work out what the word at offset in a command prompt is.
+unsafe fn status_prompt_context(
+    c: *mut client,
+    word: *const u8,
+    offset: u32,
+) -> (status_prompt_context, u8) {
+    unsafe {
+        let word = cstr_to_str(word);
+        if let Some(start) = word.rfind("#{")
+            && !word[start..].contains('}')
+        {
+            return (status_prompt_context::Format, b'\0');
+        }
+
+        let mut prefix = Vec::new();
+        for i in 0..offset as usize {
+            let ud = (*c).prompt_buffer.add(i);
+            prefix.extend_from_slice(&(&(*ud).data)[..(*ud).size as usize]);
+        }
+        let words = status_prompt_split(&String::from_utf8_lossy(&prefix));
+        let words: Vec<&str> = words.iter().map(String::as_str).collect();
+        status_prompt_context_words(&words, word)
+    }
+}
+
+/// Split a command prompt into the words of its last command, with quotes and
+/// escapes removed as the command parser does. A ; ending a word outside
+/// quotes ends a command.
+fn status_prompt_split(prefix: &str) -> Vec<String> {
+    let mut words = Vec::new();
+    let mut word = String::new();
+    let mut in_word = false;
+    let mut quote = None;
+
+    let mut chars = prefix.chars().peekable();
+    while let Some(ch) = chars.next() {
+        match (quote, ch) {
+            (Some(q), _) if ch == q => quote = None,
+            (None | Some('"'), '\\') => {
+                word.extend(chars.next());
+                in_word = true;
+            }
+            (Some(_), _) => word.push(ch),
+            (None, '\'' | '"') => {
+                quote = Some(ch);
+                in_word = true;
+            }
+            (None, ';') if chars.peek().is_none_or(|next| next.is_whitespace()) => {
+                words.clear();
+                word.clear();
+                in_word = false;
+            }
+            (None, _) if ch.is_whitespace() => {
+                if in_word {
+                    words.push(std::mem::take(&mut word));
+                    in_word = false;
+                }
+            }
+            (None, _) => {
+                word.push(ch);
+                in_word = true;
+            }
+        }
+    }
+    if in_word {
+        words.push(word);
+    }
+    words
+}
+
+/// Add to completion list with a different name shown in the menu.
+unsafe fn status_prompt_add_named(
+    list: *mut *mut *mut u8,
+    names: *mut *mut *mut u8,
+    size: *mut u32,
+    s: *const u8,
+    name: *const u8,
+) {
+    unsafe {
+        for i in 0..*size {
+            if libc::strcmp(*(*list).add(i as usize), s) == 0 {
+                return;
+            }
+        }
+        *list = xreallocarray_(*list, *size as usize + 1).as_ptr();
+        *names = xreallocarray_(*names, *size as usize + 1).as_ptr();
+        *(*list).add(*size as usize) = xstrdup(s).as_ptr();
+        *(*names).add(*size as usize) = xstrdup(name).as_ptr();
+        (*size) += 1
+    }
+}
+
+/// Complete the flags of a command.
+unsafe fn status_prompt_complete_flags(
+    entry: &'static cmd_entry,
+    word: *const u8,
+    list: *mut *mut *mut u8,
+    names: *mut *mut *mut u8,
+    size: *mut u32,
+) {
+    unsafe {
+        let (flags, _) = status_prompt_usage(entry);
+        for (flag, argument) in flags {
+            if !libc::strchr(word.add(1), flag as i32).is_null() {
+                continue;
+            }
+            let s = format_nul!("{}{}", _s(word), flag as char);
+            /* Menu items starting with - are disabled, so lead with a space. */
+            let name = match argument {
+                Some(argument) => format_nul!(" -{} {}", flag as char, argument),
+                None => format_nul!(" -{}", flag as char),
+            };
+            status_prompt_add_named(list, names, size, s, name);
+            free_(name);
+            free_(s);
+        }
+    }
+}
+
+/// Complete a format variable name after the last #{ in the word.
+unsafe fn status_prompt_complete_format(
+    word: *const u8,
+    list: *mut *mut *mut u8,
+    names: *mut *mut *mut u8,
+    size: *mut u32,
+) {
+    unsafe {
+        let w = cstr_to_str(word);
+        let Some(start) = w.rfind("#{") else {
+            return;
+        };
+        let (head, name) = w.split_at(start + 2);
+        for variable in format_table_names() {
+            if cstr_to_str(variable).starts_with(name) {
+                let s = format_nul!("{}{}}}", head, _s(variable));
+                status_prompt_add_named(list, names, size, s, variable);
+                free_(s);
+            }
+        }
+    }
+}
+
+/// Complete a key name, keeping any modifiers.
+unsafe fn status_prompt_complete_key(word: *const u8, list: *mut *mut *mut u8, size: *mut u32) {
+    unsafe {
+        let w = cstr_to_str(word);
+        let mut name = w;
+        while name.len() > 2
+            && let Some(rest) = ["C-", "M-", "S-", "c-", "m-", "s-"]
+                .iter()
+                .find_map(|m| name.strip_prefix(m))
+        {
+            name = rest;
+        }
+        let modifiers = &w[..w.len() - name.len()];
+        let name = name.to_ascii_lowercase();
+        for key in key_string_names() {
+            if cstr_to_str(key).to_ascii_lowercase().starts_with(&name) {
+                let s = format_nul!("{}{}", modifiers, _s(key));
+                status_prompt_add_list(list, size, s);
+                free_(s);
+            }
+        }
+    }
+}
+
+/// Complete an option or hook name.
+unsafe fn status_prompt_complete_option(
+    word: *const u8,
+    hooks: bool,
+    list: *mut *mut *mut u8,
+    size: *mut u32,
+) {
+    unsafe {
+        let slen = strlen(word);
+
+        let mut oe = (&raw mut OPTIONS_TABLE) as *mut options_table_entry;
+        while !(*oe).name.is_null() {
+            if ((*oe).flags & OPTIONS_TABLE_IS_HOOK != 0) == hooks
+                && strncmp((*oe).name, word, slen) == 0
+            {
+                status_prompt_add_list(list, size, (*oe).name);
+            }
+            oe = oe.add(1);
+        }
+        if hooks {
+            return;
+        }
+        for oo in [GLOBAL_OPTIONS, GLOBAL_S_OPTIONS, GLOBAL_W_OPTIONS] {
+            let mut o = options_first(oo);
+            while !o.is_null() {
+                let name = options_name(o);
+                if *name == b'@' && strncmp(name, word, slen) == 0 {
+                    status_prompt_add_list(list, size, name);
+                }
+                o = options_next(o);
+            }
+        }
+    }
+}
+
+/// Complete a value for an option from its type and choices.
+unsafe fn status_prompt_complete_value(
+    option: &str,
+    word: *const u8,
+    list: *mut *mut *mut u8,
+    size: *mut u32,
+) {
+    unsafe {
+        let slen = strlen(word);
+        let option = option.split('[').next().unwrap_or(option);
+
+        let mut oe = (&raw mut OPTIONS_TABLE) as *mut options_table_entry;
+        while !(*oe).name.is_null() && cstr_to_str((*oe).name) != option {
+            oe = oe.add(1);
+        }
+        if (*oe).name.is_null() {
+            return;
+        }
+        match (*oe).type_ {
+            options_table_type::OPTIONS_TABLE_CHOICE => {
+                let mut choice = (*oe).choices;
+                while !(*choice).is_null() {
+                    if strncmp(*choice, word, slen) == 0 {
+                        status_prompt_add_list(list, size, *choice);
+                    }
+                    choice = choice.add(1);
+                }
+            }
+            options_table_type::OPTIONS_TABLE_FLAG => {
+                for value in [c!("on"), c!("off")] {
+                    if strncmp(value, word, slen) == 0 {
+                        status_prompt_add_list(list, size, value);
+                    }
+                }
+            }
+            options_table_type::OPTIONS_TABLE_COLOUR => {
+                if strncmp(c!("default"), word, slen) == 0 {
+                    status_prompt_add_list(list, size, c!("default"));
+                }
+                for colour in (0..8).chain(90..98) {
+                    let value = colour_tostring(colour);
+                    if strncmp(value, word, slen) == 0 {
+                        status_prompt_add_list(list, size, value);
+                    }
+                }
+            }
+            options_table_type::OPTIONS_TABLE_KEY => {
+                status_prompt_complete_key(word, list, size);
+            }
+            options_table_type::OPTIONS_TABLE_COMMAND => {
+                *list = status_prompt_complete_list(size, word, 1);
+            }
+            _ => (),
+        }
+    }
+}
+
+/// Complete a pane in a window given as session:window.
+unsafe fn status_prompt_complete_pane(
+    c: *mut client,
+    s: *const u8,
+    list: *mut *mut *mut u8,
+    names: *mut *mut *mut u8,
+    size: *mut u32,
+) {
+    unsafe {
+        let target = cstr_to_str(s);
+        let Some((window, pane)) = target.rsplit_once('.') else {
+            return;
+        };
+        let Some((session_name, window)) = window.split_once(':') else {
+            return;
+        };
+
+        let session = if session_name.is_empty() {
+            (*c).session
+        } else {
+            let Ok(name) = CString::new(session_name) else {
+                return;
+            };
+            session_find(name.as_ptr().cast_mut().cast())
+        };
+        if session.is_null() {
+            return;
+        }
+        let wl = match window.parse::<i32>() {
+            Ok(idx) => winlink_find_by_index(&raw mut (*session).windows, idx),
+            Err(_) if window.is_empty() => (*session).curw,
+            Err(_) => return,
+        };
+        if wl.is_null() {
+            return;
+        }
+
+        let prefix = &target[..target.len() - pane.len()];
+        for wp in
+            tailq_foreach::<_, discr_entry>(&raw mut (*(*wl).window).panes).map(NonNull::as_ptr)
+        {
+            let mut idx = 0;
+            if window_pane_index(wp, &raw mut idx) != 0 {
+                continue;
+            }
+            let item = format_nul!("{}{}", prefix, idx);
+            if strncmp(item, s, strlen(s)) == 0 {
+                let name = format_nul!("{}{} ({})", prefix, idx, _s((*wp).base.title));
+                status_prompt_add_named(list, names, size, item, name);
+                free_(name);
+            }
+            free_(item);
+        }
+    }
+}
+
 /// Complete word.
 unsafe fn status_prompt_complete(c: *mut client, word: *const u8, mut offset: u32) -> *mut u8 {
     unsafe {
         let mut session: *mut session = null_mut();
 
-        let mut s: *const u8 = null();
+        let mut s: *const u8 = word;
         let mut colon: *mut u8 = null_mut();
 
-        let mut flag: u8 = b'\0';
-
         let mut list: *mut *mut u8 = null_mut();
+        let mut names: *mut *mut u8 = null_mut();
         let mut copy: *mut u8 = null_mut();
         let mut out: *mut u8 = null_mut();
         let mut size: u32 = 0;
 
+        let (context, mut flag) = match (*c).prompt_type {
+            prompt_type::PROMPT_TYPE_COMMAND => status_prompt_context(c, word, offset),
+            prompt_type::PROMPT_TYPE_TARGET | prompt_type::PROMPT_TYPE_WINDOW_TARGET => {
+                (status_prompt_context::Target, b'\0')
+            }
+            _ => (status_prompt_context::Any, b'\0'),
+        };
         if *word == b'\0'
-            && (*c).prompt_type != prompt_type::PROMPT_TYPE_TARGET
-            && (*c).prompt_type != prompt_type::PROMPT_TYPE_WINDOW_TARGET
+            && matches!(
+                context,
+                status_prompt_context::Any
+                    | status_prompt_context::Command
+                    | status_prompt_context::Key
+                    | status_prompt_context::Option
+                    | status_prompt_context::Hook
+            )
         {
             return null_mut();
         }
+        if flag != b'\0' {
+            s = word.add(2);
+            offset += 2;
+        }
 
         'found: {
-            if (*c).prompt_type != prompt_type::PROMPT_TYPE_TARGET
-                && (*c).prompt_type != prompt_type::PROMPT_TYPE_WINDOW_TARGET
-                && strncmp(word, c!("-t"), 2) != 0
-                && strncmp(word, c!("-s"), 2) != 0
-            {
-                list = status_prompt_complete_list(&raw mut size, word, (offset == 0) as i32);
-                out = if size == 0 {
-                    null_mut()
-                } else if size == 1 {
-                    format_nul!("{} ", _s(*list))
-                } else {
-                    status_prompt_complete_prefix(list, size)
-                };
-                break 'found;
-            }
-
-            if (*c).prompt_type == prompt_type::PROMPT_TYPE_TARGET
-                || (*c).prompt_type == prompt_type::PROMPT_TYPE_WINDOW_TARGET
-            {
-                s = word;
-                flag = b'\0';
-            } else {
-                s = word.add(2);
-                flag = *word.add(1);
-                offset += 2;
-            }
-
-            /* If this is a window completion, open the window menu. */
-            if (*c).prompt_type == prompt_type::PROMPT_TYPE_WINDOW_TARGET {
-                out = status_prompt_complete_window_menu(c, (*c).session, s, offset, b'\0');
-                break 'found;
-            }
-            colon = libc::strchr(s, b':' as i32);
+            let space = match context {
+                status_prompt_context::Any => {
+                    list = status_prompt_complete_list(&raw mut size, word, 0);
+                    true
+                }
+                status_prompt_context::Command => {
+                    list = status_prompt_complete_list(&raw mut size, word, 1);
+                    true
+                }
+                status_prompt_context::Flag(entry) => {
+                    status_prompt_complete_flags(
+                        entry,
+                        word,
+                        &raw mut list,
+                        &raw mut names,
+                        &raw mut size,
+                    );
+                    true
+                }
+                status_prompt_context::Format => {
+                    status_prompt_complete_format(
+                        word,
+                        &raw mut list,
+                        &raw mut names,
+                        &raw mut size,
+                    );
+                    false
+                }
+                status_prompt_context::Client => {
+                    for loop_ in tailq_foreach(&raw mut CLIENTS).map(NonNull::as_ptr) {
+                        if !(*loop_).name.is_null() && strncmp((*loop_).name, s, strlen(s)) == 0 {
+                            status_prompt_add_list(&raw mut list, &raw mut size, (*loop_).name);
+                        }
+                    }
+                    true
+                }
+                status_prompt_context::Buffer => {
+                    let mut pb = paste_walk(null_mut());
+                    while let Some(pbn) = NonNull::new(pb) {
+                        let name = paste_buffer_name(pbn);
+                        if strncmp(name, s, strlen(s)) == 0 {
+                            status_prompt_add_list(&raw mut list, &raw mut size, name);
+                        }
+                        pb = paste_walk(pb);
+                    }
+                    true
+                }
+                status_prompt_context::Key => {
+                    status_prompt_complete_key(s, &raw mut list, &raw mut size);
+                    true
+                }
+                status_prompt_context::KeyTable => {
+                    let mut table = key_bindings_first_table();
+                    while !table.is_null() {
+                        if strncmp((*table).name, s, strlen(s)) == 0 {
+                            status_prompt_add_list(&raw mut list, &raw mut size, (*table).name);
+                        }
+                        table = key_bindings_next_table(table);
+                    }
+                    true
+                }
+                status_prompt_context::Option | status_prompt_context::Hook => {
+                    status_prompt_complete_option(
+                        s,
+                        matches!(context, status_prompt_context::Hook),
+                        &raw mut list,
+                        &raw mut size,
+                    );
+                    true
+                }
+                status_prompt_context::Value(ref option) => {
+                    status_prompt_complete_value(option, s, &raw mut list, &raw mut size);
+                    true
+                }
+                status_prompt_context::Layout => {
+                    for layout in layout_set_names() {
+                        if strncmp(layout, s, strlen(s)) == 0 {
+                            status_prompt_add_list(&raw mut list, &raw mut size, layout);
+                        }
+                    }
+                    for name in layout_saved_names() {
+                        if strncmp(name.as_ptr().cast(), s, strlen(s)) == 0 {
+                            status_prompt_add_list(
+                                &raw mut list,
+                                &raw mut size,
+                                name.as_ptr().cast(),
+                            );
+                        }
+                    }
+                    true
+                }
+                status_prompt_context::Nothing => break 'found,
+                status_prompt_context::Target => {
+                    /* If this is a window completion, open the window menu. */
+                    if (*c).prompt_type == prompt_type::PROMPT_TYPE_WINDOW_TARGET {
+                        out = status_prompt_complete_window_menu(c, (*c).session, s, offset, b'\0');
+                        break 'found;
+                    }
+                    colon = libc::strchr(s, b':' as i32);
 
-            /* If there is no colon, complete as a session. */
-            if colon.is_null() {
-                out = status_prompt_complete_session(&raw mut list, &raw mut size, s, flag);
-                break 'found;
-            }
+                    /* If there is no colon, complete as a session. */
+                    if colon.is_null() {
+                        out = status_prompt_complete_session(&raw mut list, &raw mut size, s, flag);
+                        break 'found;
+                    }
 
-            /* If there is a colon but no period, find session and show a menu. */
-            if libc::strchr(colon.add(1), b'.' as i32).is_null() {
-                if *s == b':' {
-                    session = (*c).session;
-                } else {
-                    copy = xstrdup(s).as_ptr();
-                    *libc::strchr(copy, b':' as i32) = b'\0';
-                    session = session_find(copy);
-                    free_(copy);
-                    if session.is_null() {
+                    /* If there is a colon but no period, find session and show a menu. */
+                    if libc::strchr(colon.add(1), b'.' as i32).is_null() {
+                        if *s == b':' {
+                            session = (*c).session;
+                        } else {
+                            copy = xstrdup(s).as_ptr();
+                            *libc::strchr(copy, b':' as i32) = b'\0';
+                            session = session_find(copy);
+                            free_(copy);
+                            if session.is_null() {
+                                break 'found;
+                            }
+                        }
+                        out = status_prompt_complete_window_menu(
+                            c,
+                            session,
+                            colon.add(1),
+                            offset,
+                            flag,
+                        );
+                        if out.is_null() {
+                            return null_mut();
+                        }
                         break 'found;
                     }
+
+                    /* Otherwise complete the pane. */
+                    status_prompt_complete_pane(c, s, &raw mut list, &raw mut names, &raw mut size);
+                    false
                 }
-                out = status_prompt_complete_window_menu(c, session, colon.add(1), offset, flag);
-                if out.is_null() {
-                    return null_mut();
-                }
+            };
+
+            out = if size == 0 {
+                null_mut()
+            } else if size == 1 {
+                format_nul!("{}{}", _s(*list), if space { " " } else { "" })
+            } else {
+                status_prompt_complete_prefix(list, size)
+            };
+            if !out.is_null() && flag != b'\0' {
+                copy = format_nul!("-{}{}", flag as char, _s(out));
+                free_(out);
+                out = copy;
             }
         } // found:
-        if size != 0 {
+        if size != 0 && names.is_null() {
             libc::qsort(
                 list.cast(),
                 size as usize,
                 size_of::<*mut i8>(),
                 Some(status_prompt_complete_sort),
             );
-            for i in 0..size {
-                // log_debug("complete %u: %s", i, list[i]);
-            }
         }
 
         if !out.is_null() && libc::strcmp(word, out) == 0 {
             free_(out);
             out = null_mut();
         }
-        if !out.is_null() || status_prompt_complete_list_menu(c, list, size, offset, flag) == 0 {
+        if !out.is_null()
+            || status_prompt_complete_list_menu(c, list, names, size, offset, flag) == 0
+        {
             for i in 0..size {
                 free_(*list.add(i as usize));
             }
             free_(list);
         }
+        if !names.is_null() {
+            for i in 0..size {
+                free_(*names.add(i as usize));
+            }
+            free_(names);
+        }
         out
     }
 }
@@ -2515,3 +3104,200 @@ mod code {
     pub const LF: u64 = '\n' as u64;
     pub const ESC: u64 = '\x1b' as u64;
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    unsafe fn entry(name: &str) -> &'static cmd_entry {
+        unsafe {
+            let name = CString::new(name).unwrap();
+            match cmd_find(name.as_ptr().cast()) {
+                Ok(entry) => entry,
+                Err(cause) => panic!("{}", _s(cause)),
+            }
+        }
+    }
+
+    unsafe fn context(prefix: &str, word: &str) -> (status_prompt_context, u8) {
+        unsafe {
+            let words = status_prompt_split(prefix);
+            let words: Vec<&str> = words.iter().map(String::as_str).collect();
+            status_prompt_context_words(&words, word)
+        }
+    }
+
+    #[test]
+    fn test_prompt_usage() {
+        unsafe {
+            let (flags, positional) = status_prompt_usage(entry("bind-key"));
+            assert_eq!(
+                flags,
+                [
+                    (b'n', None),
+                    (b'r', None),
+                    (b'T', Some("key-table")),
+                    (b'N', Some("note")),
+                ]
+            );
+            assert_eq!(positional, ["key", "command", "arguments"]);
+
+            let (flags, positional) = status_prompt_usage(entry("set-option"));
+            assert_eq!(flags.len(), 11);
+            assert_eq!(flags[10], (b't', Some("target-pane")));
+            assert_eq!(positional, ["option", "value"]);
+
+            let (flags, positional) = status_prompt_usage(entry("select-pane"));
+            assert_eq!(
+                flags[11..],
+                [(b'T', Some("title")), (b't', Some("target-pane"))]
+            );
+            assert!(positional.is_empty());
+        }
+    }
+
+    #[test]
+    fn test_prompt_classify() {
+        use status_prompt_context as ctx;
+
+        assert!(matches!(
+            status_prompt_classify("command", None),
+            ctx::Command
+        ));
+        assert!(matches!(
+            status_prompt_classify("key-table", None),
+            ctx::KeyTable
+        ));
+        assert!(matches!(
+            status_prompt_classify("option", None),
+            ctx::Option
+        ));
+        assert!(matches!(
+            status_prompt_classify("value", Some("status")),
+            ctx::Value(option) if option == "status"
+        ));
+        assert!(matches!(
+            status_prompt_classify("value", None),
+            ctx::Nothing
+        ));
+        assert!(matches!(
+            status_prompt_classify("layout-name", None),
+            ctx::Layout
+        ));
+        assert!(matches!(
+            status_prompt_classify("buffer-name", None),
+            ctx::Buffer
+        ));
+        assert!(matches!(
+            status_prompt_classify("new-name", None),
+            ctx::Nothing
+        ));
+        assert!(matches!(
+            status_prompt_classify("target-client", None),
+            ctx::Client
+        ));
+        assert!(matches!(
+            status_prompt_classify("target-pane", None),
+            ctx::Target
+        ));
+        assert!(matches!(
+            status_prompt_classify("src-window", None),
+            ctx::Target
+        ));
+        assert!(matches!(
+            status_prompt_classify("shell-command", None),
+            ctx::Any
+        ));
+    }
+
+    #[test]
+    fn test_prompt_split() {
+        assert_eq!(
+            status_prompt_split("set -g @x 'a b' "),
+            ["set", "-g", "@x", "a b"]
+        );
+        assert_eq!(
+            status_prompt_split(r#"display "a \"b\" c""#),
+            ["display", r#"a "b" c"#]
+        );
+        assert_eq!(status_prompt_split(r"send a\ b c"), ["send", "a b", "c"]);
+        assert_eq!(status_prompt_split("new ; display x"), ["display", "x"]);
+        assert_eq!(status_prompt_split("new; display"), ["display"]);
+        assert_eq!(status_prompt_split(r"send a\; "), ["send", "a;"]);
+        assert_eq!(status_prompt_split("send 'a;' b"), ["send", "a;", "b"]);
+        assert_eq!(status_prompt_split("send a;b"), ["send", "a;b"]);
+        assert_eq!(status_prompt_split("send ''"), ["send", ""]);
+    }
+
+    #[test]
+    fn test_prompt_context_words() {
+        use status_prompt_context as ctx;
+
+        unsafe {
+            assert!(matches!(context("", "sel"), (ctx::Command, 0)));
+
+            // Flags and their arguments.
+            assert!(matches!(context("select-pane ", "-t"), (ctx::Target, b't')));
+            assert!(matches!(context("select-pane -t ", ""), (ctx::Target, 0)));
+            assert!(matches!(
+                context("select-pane -Z -t ", ""),
+                (ctx::Target, 0)
+            ));
+            assert!(matches!(context("select-pane -Zt ", ""), (ctx::Target, 0)));
+            assert!(matches!(
+                context("rename-window -t @1 ", ""),
+                (ctx::Nothing, 0)
+            ));
+            assert!(matches!(
+                context("select-pane -t %1 ", "-"),
+                (ctx::Flag(entry), 0) if cstr_to_str(entry.name.as_ptr()) == "select-pane"
+            ));
+            assert!(matches!(context("select-pane -- ", "-"), (ctx::Nothing, 0)));
+            assert!(matches!(context("kill-session -t ", ""), (ctx::Target, 0)));
+            assert!(matches!(context("detach-client -t ", ""), (ctx::Client, 0)));
+
+            // After -- a word starting with - is an argument.
+            assert!(matches!(
+                context("set-option -g -- ", "-"),
+                (ctx::Option, 0)
+            ));
+
+            // A nested command, including its own flags.
+            assert!(matches!(context("bind-key -T ", ""), (ctx::KeyTable, 0)));
+            assert!(matches!(context("bind-key ", ""), (ctx::Key, 0)));
+            assert!(matches!(
+                context("bind-key -n F1 ", "sel"),
+                (ctx::Command, 0)
+            ));
+            assert!(matches!(
+                context("bind-key F1 select-pane ", "-t"),
+                (ctx::Target, b't')
+            ));
+            assert!(matches!(
+                context("bind-key -r F1 select-pane -t ", ""),
+                (ctx::Target, 0)
+            ));
+            assert!(matches!(
+                context("set-hook -g pane-died ", ""),
+                (ctx::Command, 0)
+            ));
+            assert!(matches!(
+                context("new ; select-pane -t ", ""),
+                (ctx::Target, 0)
+            ));
+
+            // Option values, quoted or not.
+            assert!(matches!(context("set-option -g ", "sta"), (ctx::Option, 0)));
+            assert!(
+                matches!(context("set -g status ", ""), (ctx::Value(option), 0) if option == "status")
+            );
+            assert!(
+                matches!(context("set -g 'status' ", ""), (ctx::Value(option), 0) if option == "status")
+            );
+            assert!(
+                matches!(context("set -g \"mode-keys\" ", ""), (ctx::Value(option), 0) if option == "mode-keys")
+            );
+            assert!(matches!(context("set -g status on ", ""), (ctx::Any, 0)));
+        }
+    }
+}
@@ -0,0 +1,351 @@
+//! Killed windows and panes kept in the server so they can be restored.
+//!
+//! A window in the trash is unlinked from every session but holds a
+//! reference, so its panes keep running; a pane that exits while in the trash
+//! is kept dead as if `remain-on-exit` were on. A pane killed from a window
+//! with other panes is first moved into a window of its own; the panes killed
+//! by `kill-pane -a` share one window and so one entry. Entries are
+//! destroyed when they expire after `trash-time` seconds or when there are
+//! more than `trash-limit` of them.
+use crate::*;
+
+use crate::compat::{
+    queue::{
+        tailq_first, tailq_foreach, tailq_insert_head, tailq_insert_tail, tailq_last, tailq_next,
+        tailq_remove,
+    },
+    tree::rb_foreach,
+};
+
+crate::compat::impl_tailq_entry!(trash_entry, entry, tailq_entry<trash_entry>);
+#[repr(C)]
+pub struct trash_entry {
+    pub id: u32,
+    pub window: *mut window,
+    pub pane: i32,
+
+    pub session: *mut u8,
+    pub session_id: Option<u32>,
+    pub idx: i32,
+
+    pub time: time_t,
+    pub expires: time_t,
+    pub timer: event,
+
+    pub entry: tailq_entry<trash_entry>,
+}
+
+static mut TRASH: tailq_head<trash_entry> = compat::TAILQ_HEAD_INITIALIZER!(TRASH);
+static mut TRASH_NEXT_ID: u32 = 0;
+
+pub unsafe fn trash_count() -> u32 {
+    unsafe { tailq_foreach::<_, ()>(&raw mut TRASH).count() as u32 }
+}
+
+/// Walk the trash, newest first.
+pub unsafe fn trash_walk(te: *mut trash_entry) -> *mut trash_entry {
+    unsafe {
+        if te.is_null() {
+            return tailq_first(&raw mut TRASH);
+        }
+        tailq_next::<_, _, ()>(te)
+    }
+}
+
+pub unsafe fn trash_find(id: u32) -> *mut trash_entry {
+    unsafe {
+        tailq_foreach::<_, ()>(&raw mut TRASH)
+            .map(NonNull::as_ptr)
+            .find(|&te| (*te).id == id)
+            .unwrap_or(null_mut())
+    }
+}
+
+/// Find the trash entry holding a window, if any.
+pub unsafe fn trash_find_window(w: *mut window) -> *mut trash_entry {
+    unsafe {
+        tailq_foreach::<_, ()>(&raw mut TRASH)
+            .map(NonNull::as_ptr)
+            .find(|&te| (*te).window == w)
+            .unwrap_or(null_mut())
+    }
+}
+
+unsafe fn trash_remove(te: *mut trash_entry) -> *mut window {
+    unsafe {
+        let w = (*te).window;
+
+        tailq_remove(&raw mut TRASH, te);
+        evtimer_del(&raw mut (*te).timer);
+        free_((*te).session);
+        free_(te);
+        w
+    }
+}
+
+/// Destroy a trash entry and its window.
+pub unsafe fn trash_free(te: *mut trash_entry) {
+    unsafe {
+        log_debug!("trash_free: {} @{}", (*te).id, (*(*te).window).id);
+        window_remove_ref(trash_remove(te), c!("trash_free"));
+    }
+}
+
+unsafe extern "C" fn trash_timer(_fd: i32, _events: i16, arg: *mut c_void) {
+    unsafe {
+        trash_free(arg as *mut trash_entry);
+    }
+}
+
+/// Put a window into the trash, returning false if the trash is disabled.
+unsafe fn trash_add(w: *mut window, s: *mut session, idx: i32, pane: i32) -> bool {
+    unsafe {
+        let limit = options_get_number_(GLOBAL_OPTIONS, c"trash-limit") as u32;
+        if limit == 0 {
+            return false;
+        }
+
+        let te = xcalloc1::<trash_entry>() as *mut trash_entry;
+        (*te).id = TRASH_NEXT_ID;
+        TRASH_NEXT_ID += 1;
+        (*te).window = w;
+        (*te).pane = pane;
+        if !s.is_null() {
+            (*te).session = xstrdup((*s).name).as_ptr();
+            (*te).session_id = Some((*s).id);
+        }
+        (*te).idx = idx;
+        (*te).time = libc::time(null_mut());
+
+        window_add_ref(w, c!("trash_add"));
+        tailq_insert_head(&raw mut TRASH, te);
+        log_debug!("trash_add: {} @{}", (*te).id, (*w).id);
+
+        evtimer_set(&raw mut (*te).timer, Some(trash_timer), te.cast());
+        let expiry = options_get_number_(GLOBAL_OPTIONS, c"trash-time");
+        if expiry != 0 {
+            (*te).expires = (*te).time + expiry as time_t;
+            let mut tv = timeval {
+                tv_sec: expiry as time_t,
+                tv_usec: 0,
+            };
+            evtimer_add(&raw mut (*te).timer, &raw mut tv);
+        }
+
+        while trash_count() > limit {
+            trash_free(tailq_last(&raw mut TRASH));
+        }
+        true
+    }
+}
+
+/// Kill a window, keeping it in the trash if it is enabled.
+pub unsafe fn trash_kill_window(w: *mut window, mut s: *mut session, renumber: i32) {
+    unsafe {
+        if s.is_null() || session_has(s, w) == 0 {
+            s = rb_foreach(&raw mut SESSIONS)
+                .map(NonNull::as_ptr)
+                .find(|&s| session_has(s, w) != 0)
+                .unwrap_or(null_mut());
+        }
+        let mut idx = -1;
+        if !s.is_null()
+            && let Some(wl) = winlink_find_by_window(&raw mut (*s).windows, w)
+        {
+            idx = (*wl.as_ptr()).idx;
+        }
+
+        trash_add(w, s, idx, 0);
+        server_kill_window(w, renumber);
+    }
+}
+
+/// Take a pane out of its window and put it into another, or a new window if
+/// that is null. Closing its cell notifies the old window's layout change.
+unsafe fn trash_move_pane(wp: *mut window_pane, mut nw: *mut window) -> *mut window {
+    unsafe {
+        let w = (*wp).window;
+
+        tailq_remove::<_, discr_entry>(&raw mut (*w).panes, wp);
+        server_client_remove_pane(wp);
+        window_lost_pane(w, wp);
+        layout_close_pane(wp);
+
+        let first = nw.is_null();
+        if first {
+            nw = window_create((*w).sx, (*w).sy, (*w).xpixel, (*w).ypixel);
+        }
+        (*wp).window = nw;
+        options_set_parent((*wp).options, (*nw).options);
+        (*wp).flags |= window_pane_flags::PANE_STYLECHANGED;
+        tailq_insert_tail::<_, discr_entry>(&raw mut (*nw).panes, wp);
+
+        if first {
+            (*nw).active = wp;
+            let name = default_window_name(nw);
+            window_set_name(nw, name);
+            free_(name);
+            layout_init(nw, wp);
+        } else {
+            layout_set_select(nw, layout_set_lookup(c!("tiled")) as u32);
+        }
+        (*wp).flags |= window_pane_flags::PANE_CHANGED;
+        colour_palette_from_option(&raw mut (*wp).palette, (*wp).options);
+        nw
+    }
+}
+
+/// Kill a pane, moving it into a window of its own in the trash if it is
+/// enabled.
+pub unsafe fn trash_kill_pane(wp: *mut window_pane, s: *mut session) {
+    unsafe {
+        let w = (*wp).window;
+
+        if window_count_panes(w) == 1 {
+            trash_kill_window(w, s, 1);
+            recalculate_sizes();
+            return;
+        }
+        if options_get_number_(GLOBAL_OPTIONS, c"trash-limit") == 0 {
+            server_kill_pane(wp);
+            return;
+        }
+
+        server_unzoom_window(w);
+        let nw = trash_move_pane(wp, null_mut());
+        trash_add(nw, s, -1, 1);
+        server_redraw_window(w);
+    }
+}
+
+/// Kill every pane in a window except one. The killed panes go together into
+/// one window in the trash, so they take up a single entry.
+pub unsafe fn trash_kill_other_panes(wp: *mut window_pane, s: *mut session) {
+    unsafe {
+        let w = (*wp).window;
+        let limit = options_get_number_(GLOBAL_OPTIONS, c"trash-limit");
+
+        server_unzoom_window(w);
+        let mut nw = null_mut();
+        for loopwp in tailq_foreach::<_, discr_entry>(&raw mut (*w).panes).map(NonNull::as_ptr) {
+            if loopwp == wp {
+                continue;
+            }
+            if limit == 0 {
+                server_kill_pane(loopwp);
+            } else {
+                nw = trash_move_pane(loopwp, nw);
+            }
+        }
+        if !nw.is_null() {
+            trash_add(nw, s, -1, 1);
+        }
+        server_redraw_window(w);
+    }
+}
+
+/// Restore a trash entry into a session: the given one, the one it was
+/// killed from or, if that has gone or `new` is set, a new session.
+pub unsafe fn trash_restore(
+    te: *mut trash_entry,
+    mut s: *mut session,
+    new: bool,
+    select: bool,
+    cause: *mut *mut u8,
+) -> *mut winlink {
+    unsafe {
+        let w = (*te).window;
+        let mut idx = -1;
+        let mut created = false;
+
+        if s.is_null() && !new {
+            s = (*te)
+                .session_id
+                .and_then(|id| session_find_by_id(id))
+                .map(NonNull::as_ptr)
+                .unwrap_or(null_mut());
+            if !s.is_null()
+                && (*te).idx != -1
+                && winlink_find_by_index(&raw mut (*s).windows, (*te).idx).is_null()
+            {
+                idx = (*te).idx;
+            }
+        }
+        if s.is_null() {
+            let mut name = null();
+            if !(*te).session.is_null() && session_find((*te).session).is_null() {
+                name = (*te).session as *const u8;
+            }
+            s = session_create(
+                null(),
+                name,
+                server_client_get_cwd(null_mut(), null_mut()),
+                environ_create().as_ptr(),
+                options_create(GLOBAL_S_OPTIONS),
+                null_mut(),
+            );
+            created = true;
+        }
+
+        if idx == -1 {
+            idx = -1 - options_get_number_((*s).options, c"base-index") as i32;
+        }
+        let wl = session_attach(s, w, idx, cause);
+        if wl.is_null() {
+            if created {
+                session_destroy(s, 0, c!("trash_restore"));
+            }
+            return null_mut();
+        }
+        if select || created {
+            session_select(s, (*wl).idx);
+        }
+        if created {
+            notify_session(c"session-created", s);
+        }
+        log_debug!("trash_restore: {} @{} to ${}", (*te).id, (*w).id, (*s).id);
+
+        window_remove_ref(trash_remove(te), c!("trash_restore"));
+        recalculate_sizes();
+        server_redraw_session_group(s);
+        server_status_session_group(s);
+        wl
+    }
+}
+
+/// Add the format variables for a trash entry.
+pub unsafe fn format_defaults_trash(ft: *mut format_tree, te: *mut trash_entry) {
+    unsafe {
+        let w = (*te).window;
+
+        format_add!(ft, c!("trash_id"), "{}", (*te).id);
+        format_add!(
+            ft,
+            c!("trash_type"),
+            "{}",
+            if (*te).pane != 0 { "pane" } else { "window" }
+        );
+        format_add!(ft, c!("trash_name"), "{}", _s((*w).name));
+        if !(*te).session.is_null() {
+            format_add!(ft, c!("trash_session"), "{}", _s((*te).session));
+        }
+        if (*te).idx != -1 {
+            format_add!(ft, c!("trash_index"), "{}", (*te).idx);
+        }
+        format_add!(ft, c!("trash_time"), "{}", (*te).time);
+        if (*te).expires != 0 {
+            format_add!(ft, c!("trash_expires"), "{}", (*te).expires);
+        }
+
+        let mut panes = 0;
+        let mut dead = 0;
+        for wp in tailq_foreach::<_, discr_entry>(&raw mut (*w).panes).map(NonNull::as_ptr) {
+            panes += 1;
+            if (*wp).fd == -1 {
+                dead += 1;
+            }
+        }
+        format_add!(ft, c!("trash_panes"), "{}", panes);
+        format_add!(ft, c!("trash_dead_panes"), "{}", dead);
+    }
+}
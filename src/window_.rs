@@ -343,6 +343,7 @@ unsafe fn window_destroy(w: *mut window) {
             layout_free_cell((*w).saved_layout_root);
         }
         free((*w).old_layout as _);
+        layout_history_free(w);
 
         window_destroy_panes(w);
 
@@ -561,6 +562,9 @@ pub unsafe fn window_set_active_pane(w: *mut window, wp: *mut window_pane, notif
     unsafe {
         log_debug!("{}: pane %%{}", "window_set_active_pane", (*wp).id);
 
+        if layout_stack_hidden(wp) {
+            layout_stack_select(wp);
+        }
         if wp == (*w).active {
             return 0;
         }
@@ -634,7 +638,7 @@ pub unsafe fn window_redraw_active_switch(w: *mut window, mut wp: *mut window_pa
 pub unsafe fn window_get_active_at(w: *mut window, x: u32, y: u32) -> *mut window_pane {
     unsafe {
         for wp in tailq_foreach::<_, discr_entry>(&raw mut (*w).panes).map(NonNull::as_ptr) {
-            if window_pane_visible(wp) != 0 {
+            if window_pane_visible(wp) == 0 {
                 continue;
             }
             if x < (*wp).xoff || x > (*wp).xoff + (*wp).sx {
@@ -649,6 +653,31 @@ pub unsafe fn window_get_active_at(w: *mut window, x: u32, y: u32) -> *mut windo
     }
 }
 
+/// Get the pane whose status line (from pane-border-status) is at a position.
+pub unsafe fn window_get_status_at(w: *mut window, x: u32, y: u32) -> *mut window_pane {
+    unsafe {
+        let status = options_get_number_((*w).options, c"pane-border-status");
+        if status == pane_status::PANE_STATUS_OFF as i64 {
+            return null_mut();
+        }
+        for wp in tailq_foreach::<_, discr_entry>(&raw mut (*w).panes).map(NonNull::as_ptr) {
+            if window_pane_visible(wp) == 0 {
+                continue;
+            }
+            if x < (*wp).xoff || x >= (*wp).xoff + (*wp).sx {
+                continue;
+            }
+            if status == pane_status::PANE_STATUS_TOP as i64 && y + 1 == (*wp).yoff {
+                return wp;
+            }
+            if status == pane_status::PANE_STATUS_BOTTOM as i64 && y == (*wp).yoff + (*wp).sy {
+                return wp;
+            }
+        }
+        null_mut()
+    }
+}
+
 pub unsafe fn window_find_string(w: *mut window, s: *const u8) -> *mut window_pane {
     unsafe {
         let mut top: u32 = 0;
@@ -712,6 +741,7 @@ pub unsafe fn window_zoom(wp: *mut window_pane) -> i32 {
         for wp1 in tailq_foreach::<_, discr_entry>(&raw mut (*w).panes).map(NonNull::as_ptr) {
             (*wp1).saved_layout_cell = (*wp1).layout_cell;
             (*wp1).layout_cell = null_mut();
+            (*wp1).flags &= !window_pane_flags::PANE_ZOOMED;
         }
 
         (*w).saved_layout_root = (*w).layout_root;
@@ -723,6 +753,72 @@ pub unsafe fn window_zoom(wp: *mut window_pane) -> i32 {
     }
 }
 
+/// Zoom several panes together, laying them out in a temporary row or column.
+/// The panes stay marked as zoomed after unzoom so the zoom can be restored.
+pub unsafe fn window_zoom_panes(
+    w: *mut window,
+    panes: &[*mut window_pane],
+    type_: layout_type,
+) -> i32 {
+    unsafe {
+        if (*w).flags.intersects(window_flag::ZOOMED) || panes.is_empty() {
+            return -1;
+        }
+        if panes.len() == 1 {
+            return window_zoom(panes[0]);
+        }
+
+        let saved_layout_root = (*w).layout_root;
+        for wp in tailq_foreach::<_, discr_entry>(&raw mut (*w).panes).map(NonNull::as_ptr) {
+            (*wp).saved_layout_cell = (*wp).layout_cell;
+            (*wp).layout_cell = null_mut();
+        }
+        if !layout_init_panes(w, panes, type_) {
+            (*w).layout_root = saved_layout_root;
+            for wp in tailq_foreach::<_, discr_entry>(&raw mut (*w).panes).map(NonNull::as_ptr) {
+                (*wp).layout_cell = (*wp).saved_layout_cell;
+                (*wp).saved_layout_cell = null_mut();
+            }
+            return -1;
+        }
+        (*w).saved_layout_root = saved_layout_root;
+        (*w).zoom_type = type_;
+
+        for wp in tailq_foreach::<_, discr_entry>(&raw mut (*w).panes).map(NonNull::as_ptr) {
+            if panes.contains(&wp) {
+                (*wp).flags |= window_pane_flags::PANE_ZOOMED;
+            } else {
+                (*wp).flags &= !window_pane_flags::PANE_ZOOMED;
+            }
+        }
+        if !panes.contains(&(*w).active) {
+            window_set_active_pane(w, panes[0], 1);
+        }
+        (*w).flags |= window_flag::ZOOMED;
+        notify_window(c"window-layout-changed", w);
+
+        0
+    }
+}
+
+/// Zoom again after an unzoom, restoring a zoom of several panes if the
+/// active pane was one of them.
+pub unsafe fn window_rezoom(w: *mut window) -> i32 {
+    unsafe {
+        let active = (*w).active;
+        let panes: Vec<*mut window_pane> = tailq_foreach::<_, discr_entry>(&raw mut (*w).panes)
+            .map(NonNull::as_ptr)
+            .filter(|&wp| (*wp).flags.intersects(window_pane_flags::PANE_ZOOMED))
+            .collect();
+
+        if panes.len() > 1 && panes.contains(&active) {
+            window_zoom_panes(w, &panes, (*w).zoom_type)
+        } else {
+            window_zoom(active)
+        }
+    }
+}
+
 pub unsafe fn window_unzoom(w: *mut window, notify: i32) -> i32 {
     unsafe {
         if !(*w).flags.intersects(window_flag::ZOOMED) {
@@ -775,7 +871,7 @@ pub unsafe fn window_pop_zoom(w: *mut window) -> i32 {
             (*w).flags.intersects(window_flag::WASZOOMED) as i32,
         );
         if (*w).flags.intersects(window_flag::WASZOOMED) {
-            return if window_zoom((*w).active) == 0 { 1 } else { 0 };
+            return if window_rezoom(w) == 0 { 1 } else { 0 };
         }
     }
 
@@ -822,9 +918,7 @@ pub unsafe fn window_lost_pane(w: *mut window, wp: *mut window_pane) {
     unsafe {
         log_debug!("{}: @{} pane %%{}", "window_lost_pane", (*w).id, (*wp).id);
 
-        if wp == MARKED_PANE.wp {
-            server_clear_marked();
-        }
+        server_unmark_pane(wp);
 
         window_pane_stack_remove(&raw mut (*w).last_panes, wp);
         if wp == (*w).active {
@@ -836,6 +930,9 @@ pub unsafe fn window_lost_pane(w: *mut window, wp: *mut window_pane) {
                 }
             }
             if !(*w).active.is_null() {
+                if layout_stack_hidden((*w).active) {
+                    layout_stack_select((*w).active);
+                }
                 window_pane_stack_remove(&raw mut (*w).last_panes, (*w).active);
                 (*(*w).active).flags |= window_pane_flags::PANE_CHANGED;
                 notify_window(c"window-pane-changed", w);
@@ -969,7 +1066,7 @@ pub unsafe fn window_printable_flags(wl: *mut winlink, escape: i32) -> *const u8
             FLAGS[pos] = b'-';
             pos += 1;
         }
-        if server_check_marked() && wl == MARKED_PANE.wl {
+        if server_marked_panes().any(|fs| fs.wl == wl) {
             FLAGS[pos] = b'M';
             pos += 1;
         }
@@ -1027,6 +1124,7 @@ pub unsafe fn window_pane_create(
         tailq_init(&raw mut (*wp).modes);
 
         tailq_init(&raw mut (*wp).resize_queue);
+        tailq_init(&raw mut (*wp).checkpoints);
 
         (*wp).sx = sx;
         (*wp).sy = sy;
@@ -1056,7 +1154,9 @@ pub unsafe fn window_pane_create(
 unsafe fn window_pane_destroy(wp: *mut window_pane) {
     unsafe {
         window_pane_reset_mode_all(wp);
+        cmd_wait_for_output_cancel(wp);
         free((*wp).searchstr as _);
+        free_((*wp).group);
 
         if (*wp).fd != -1 {
             #[cfg(feature = "utempter")]
@@ -1086,6 +1186,11 @@ unsafe fn window_pane_destroy(wp: *mut window_pane) {
             tailq_remove::<_, ()>(&raw mut (*wp).resize_queue, r);
             free_(r);
         }
+        for cp in tailq_foreach(&raw mut (*wp).checkpoints).map(NonNull::as_ptr) {
+            tailq_remove::<_, ()>(&raw mut (*wp).checkpoints, cp);
+            free_((*cp).name);
+            free_(cp);
+        }
 
         rb_remove(&raw mut ALL_WINDOW_PANES, wp);
 
@@ -1121,6 +1226,7 @@ unsafe extern "C" fn window_pane_read_callback(_bufev: *mut bufferevent, data: *
             }
         }
         input_parse_pane(wp);
+        cmd_wait_for_output_check(wp);
         bufferevent_disable((*wp).event, EV_READ);
     }
 }
@@ -1161,6 +1267,26 @@ pub unsafe fn window_pane_set_event(wp: *mut window_pane) {
     }
 }
 
+/// Move a named checkpoint to an absolute line, returning where it was before.
+/// A new checkpoint is created at the line.
+pub unsafe fn window_pane_set_checkpoint(wp: *mut window_pane, name: *const u8, line: u64) -> u64 {
+    unsafe {
+        for cp in tailq_foreach(&raw mut (*wp).checkpoints).map(NonNull::as_ptr) {
+            if libc::strcmp((*cp).name, name) == 0 {
+                let old = (*cp).line;
+                (*cp).line = line;
+                return old;
+            }
+        }
+
+        let cp: *mut window_pane_checkpoint = xcalloc_::<window_pane_checkpoint>(1).as_ptr();
+        (*cp).name = xstrdup(name).as_ptr();
+        (*cp).line = line;
+        tailq_insert_tail(&raw mut (*wp).checkpoints, cp);
+        line
+    }
+}
+
 pub unsafe fn window_pane_resize(wp: *mut window_pane, sx: u32, sy: u32) {
     unsafe {
         if sx == (*wp).sx && sy == (*wp).sy {
@@ -1300,6 +1426,51 @@ unsafe fn window_pane_copy_key(wp: *mut window_pane, key: key_code) {
     }
 }
 
+/// Copy a key to the other panes in the same input group, which may be in
+/// any window. Panes already given the key by synchronize-panes are skipped.
+unsafe fn window_pane_copy_key_group(wp: *mut window_pane, key: key_code) {
+    unsafe {
+        let sync = options_get_number_((*wp).options, c"synchronize-panes") != 0;
+
+        for loop_ in rb_foreach(&raw mut ALL_WINDOW_PANES).map(NonNull::as_ptr) {
+            if loop_ == wp || !window_pane_in_group(loop_, (*wp).group) {
+                continue;
+            }
+            if sync
+                && (*loop_).window == (*wp).window
+                && window_pane_visible(loop_) != 0
+                && options_get_number_((*loop_).options, c"synchronize-panes") != 0
+            {
+                continue;
+            }
+            if tailq_empty(&raw mut (*loop_).modes)
+                && (*loop_).fd != -1
+                && !(*loop_).flags.intersects(window_pane_flags::PANE_INPUTOFF)
+            {
+                input_key_pane(loop_, key, null_mut());
+            }
+        }
+    }
+}
+
+/// Check if a pane is in the named input group.
+pub unsafe fn window_pane_in_group(wp: *mut window_pane, group: *const u8) -> bool {
+    unsafe { !(*wp).group.is_null() && !group.is_null() && libc::strcmp((*wp).group, group) == 0 }
+}
+
+/// Put a pane in an input group, or take it out if group is null.
+pub unsafe fn window_pane_set_group(wp: *mut window_pane, group: *const u8) {
+    unsafe {
+        free_((*wp).group);
+        (*wp).group = if group.is_null() {
+            null_mut()
+        } else {
+            xstrdup(group).as_ptr()
+        };
+        server_redraw_window_borders((*wp).window);
+    }
+}
+
 pub unsafe fn window_pane_key(
     wp: *mut window_pane,
     c: *mut client,
@@ -1335,6 +1506,9 @@ pub unsafe fn window_pane_key(
         if options_get_number_((*wp).options, c"synchronize-panes") != 0 {
             window_pane_copy_key(wp, key);
         }
+        if !(*wp).group.is_null() {
+            window_pane_copy_key_group(wp, key);
+        }
     }
 
     0
@@ -1342,10 +1516,17 @@ pub unsafe fn window_pane_key(
 
 pub unsafe fn window_pane_visible(wp: *mut window_pane) -> i32 {
     unsafe {
+        if layout_stack_hidden(wp) {
+            return 0;
+        }
         if !(*(*wp).window).flags.intersects(window_flag::ZOOMED) {
             return 1;
         }
-        if wp == (*(*wp).window).active { 1 } else { 0 }
+        if wp == (*(*wp).window).active || (*wp).flags.intersects(window_pane_flags::PANE_ZOOMED) {
+            1
+        } else {
+            0
+        }
     }
 }
 
@@ -1424,6 +1605,15 @@ pub unsafe fn window_pane_search(
     }
 }
 
+/// Is this pane hidden by a zoom of several panes including another pane?
+unsafe fn window_pane_zoomed_out(wp: *mut window_pane, next: *mut window_pane) -> bool {
+    unsafe {
+        (*(*wp).window).flags.intersects(window_flag::ZOOMED)
+            && (*wp).flags.intersects(window_pane_flags::PANE_ZOOMED)
+            && window_pane_visible(next) == 0
+    }
+}
+
 /// Get MRU pane from a list.
 unsafe fn window_pane_choose_best(list: *mut *mut window_pane, size: u32) -> *mut window_pane {
     if size == 0 {
@@ -1479,7 +1669,7 @@ pub unsafe fn window_pane_find_up(wp: *mut window_pane) -> *mut window_pane {
         let right = (*wp).xoff + (*wp).sx;
 
         for next in tailq_foreach::<_, discr_entry>(&raw mut (*w).panes).map(NonNull::as_ptr) {
-            if next == wp {
+            if next == wp || window_pane_zoomed_out(wp, next) {
                 continue;
             }
             if (*next).yoff + (*next).sy + 1 != edge {
@@ -1547,7 +1737,7 @@ pub unsafe fn window_pane_find_down(wp: *mut window_pane) -> *mut window_pane {
         let right = (*wp).xoff + (*wp).sx;
 
         for next in tailq_foreach::<_, discr_entry>(&raw mut (*w).panes).map(NonNull::as_ptr) {
-            if next == wp {
+            if next == wp || window_pane_zoomed_out(wp, next) {
                 continue;
             }
             if (*next).yoff != edge {
@@ -1598,7 +1788,7 @@ pub unsafe fn window_pane_find_left(wp: *mut window_pane) -> *mut window_pane {
         let bottom = (*wp).yoff + (*wp).sy;
 
         for next in tailq_foreach::<_, discr_entry>(&raw mut (*w).panes).map(NonNull::as_ptr) {
-            if next == wp {
+            if next == wp || window_pane_zoomed_out(wp, next) {
                 continue;
             }
             if (*next).xoff + (*next).sx + 1 != edge {
@@ -1649,7 +1839,7 @@ pub unsafe fn window_pane_find_right(wp: *mut window_pane) -> *mut window_pane {
         let bottom = (*wp).yoff + (*wp).sy;
 
         for next in tailq_foreach::<_, discr_entry>(&raw mut (*w).panes).map(NonNull::as_ptr) {
-            if next == wp {
+            if next == wp || window_pane_zoomed_out(wp, next) {
                 continue;
             }
             if (*next).xoff != edge {
@@ -122,6 +122,8 @@ const WINDOW_COPY_SEARCH_MAX_LINE: u32 = 2000;
 
 const WINDOW_COPY_DRAG_REPEAT_TIME: libc::suseconds_t = 50000;
 
+const WINDOW_COPY_JUMPLIST: usize = 100;
+
 /*
  * Copy mode's visible screen (the "screen" field) is filled from one of two
  * sources: the original contents of the pane (used when we actually enter via
@@ -192,9 +194,17 @@ pub struct window_copy_mode_data {
     my: u32,
     showmark: i32,
 
+    /* jump list of positions before large moves */
+    jumplist: [(u32, u32); WINDOW_COPY_JUMPLIST],
+    jumplistsize: u32,
+    jumplistat: u32,
+
     searchtype: window_copy,
     searchdirection: i32,
     searchregex: i32,
+    /// regcomp flags the search string was found with by search-panes, or
+    /// zero to ignore case only if the search string is all lowercase
+    searchcflags: i32,
     searchstr: *mut u8,
     searchmark: *mut u8,
     searchcount: i32,
@@ -279,6 +289,8 @@ pub unsafe fn window_copy_clone_screen(
         (*(*dst).grid).sy = sy - screen_hsize(src);
         (*(*dst).grid).hsize = screen_hsize(src);
         (*(*dst).grid).hscrolled = (*(*src).grid).hscrolled;
+        (*(*dst).grid).htrimmed = (*(*src).grid).htrimmed;
+        (*(*dst).grid).markx = (*(*src).grid).markx;
         if (*src).cy > (*(*dst).grid).sy - 1 {
             (*dst).cx = 0;
             (*dst).cy = (*(*dst).grid).sy - 1;
@@ -558,6 +570,45 @@ pub unsafe fn window_copy_pageup(wp: *mut window_pane, half_page: i32) {
     }
 }
 
+/// Put a pane into copy mode with the cursor on a search match, the line given
+/// as an absolute history line. The match is searched for again with the same
+/// regcomp flags it was found with.
+pub unsafe fn window_copy_show_match(
+    wp: *mut window_pane,
+    line: u64,
+    px: u32,
+    pattern: *const u8,
+    cflags: i32,
+) {
+    unsafe {
+        let args: *mut args = args_create();
+        window_pane_set_mode(wp, wp, &raw const WINDOW_COPY_MODE, null_mut(), args);
+        args_free(args);
+
+        let wme = tailq_first(&raw mut (*wp).modes);
+        if wme.is_null() || (*wme).mode != &raw const WINDOW_COPY_MODE {
+            return;
+        }
+        let data: *mut window_copy_mode_data = (*wme).data.cast();
+        let gd = (*(*data).backing).grid;
+
+        if line < (*gd).htrimmed || line - (*gd).htrimmed >= ((*gd).hsize + (*gd).sy) as u64 {
+            return;
+        }
+
+        free_((*data).searchstr);
+        (*data).searchstr = xstrdup(pattern).as_ptr();
+        (*data).searchcflags = cflags;
+        (*data).searchtype = window_copy::WINDOW_COPY_SEARCHDOWN;
+        (*data).searchregex = 1;
+        (*data).timeout = 0;
+
+        window_copy_scroll_to(wme, px, (line - (*gd).htrimmed) as u32, true);
+        window_copy_search_marks(wme, null_mut(), 1, 0);
+        window_copy_redraw_screen(wme);
+    }
+}
+
 pub unsafe fn window_copy_pageup1(wme: *mut window_mode_entry, half_page: i32) {
     unsafe {
         let data: *mut window_copy_mode_data = (*wme).data.cast();
@@ -938,6 +989,7 @@ pub unsafe fn window_copy_expand_search_string(cs: *mut window_copy_cmd_state) -
             free_((*data).searchstr);
             (*data).searchstr = xstrdup(ss).as_ptr();
         }
+        (*data).searchcflags = 0;
         1
     }
 }
@@ -1091,7 +1143,7 @@ pub unsafe fn window_copy_do_copy_end_of_line(
             if pipe != 0 {
                 window_copy_copy_pipe(wme, s, prefix, command);
             } else {
-                window_copy_copy_selection(wme, prefix);
+                window_copy_copy_selection(wme, prefix, grid_export_format::GRID_EXPORT_TEXT);
             }
 
             if cancel != 0 {
@@ -1185,7 +1237,7 @@ pub unsafe fn window_copy_do_copy_line(
             if pipe != 0 {
                 window_copy_copy_pipe(wme, s, prefix, command);
             } else {
-                window_copy_copy_selection(wme, prefix);
+                window_copy_copy_selection(wme, prefix, grid_export_format::GRID_EXPORT_TEXT);
             }
 
             if cancel != 0 {
@@ -1245,7 +1297,7 @@ pub unsafe fn window_copy_cmd_copy_selection_no_clear(
         }
 
         if !s.is_null() {
-            window_copy_copy_selection(wme, prefix);
+            window_copy_copy_selection(wme, prefix, grid_export_format::GRID_EXPORT_TEXT);
         }
 
         free_(prefix);
@@ -1277,6 +1329,44 @@ pub unsafe fn window_copy_cmd_copy_selection_and_cancel(
     }
 }
 
+pub unsafe fn window_copy_cmd_copy_selection_as(
+    cs: *mut window_copy_cmd_state,
+) -> window_copy_cmd_action {
+    unsafe {
+        let wme: *mut window_mode_entry = (*cs).wme;
+        let c: *mut client = (*cs).c;
+        let s: *mut session = (*cs).s;
+        let wl: *mut winlink = (*cs).wl;
+        let wp: *mut window_pane = (*wme).wp;
+        let mut prefix = null_mut();
+        let arg2 = args_string((*cs).args, 2);
+
+        let Some(format) = grid_export_find(args_string((*cs).args, 1)) else {
+            return window_copy_cmd_action::WINDOW_COPY_CMD_NOTHING;
+        };
+        if !arg2.is_null() {
+            prefix = format_single(null_mut(), arg2, c, s, wl, wp);
+        }
+
+        if !s.is_null() {
+            window_copy_copy_selection(wme, prefix, format);
+        }
+
+        free_(prefix);
+        window_copy_clear_selection(wme);
+        window_copy_cmd_action::WINDOW_COPY_CMD_REDRAW
+    }
+}
+
+pub unsafe fn window_copy_cmd_copy_selection_as_and_cancel(
+    cs: *mut window_copy_cmd_state,
+) -> window_copy_cmd_action {
+    unsafe {
+        window_copy_cmd_copy_selection_as(cs);
+        window_copy_cmd_action::WINDOW_COPY_CMD_CANCEL
+    }
+}
+
 pub unsafe fn window_copy_cmd_cursor_down(
     cs: *mut window_copy_cmd_state,
 ) -> window_copy_cmd_action {
@@ -1503,6 +1593,8 @@ pub unsafe fn window_copy_cmd_history_bottom(
         let data: *mut window_copy_mode_data = (*wme).data.cast();
         let s: *mut screen = (*data).backing;
 
+        window_copy_jump_push(wme);
+
         let oy = screen_hsize(s) + (*data).cy - (*data).oy;
         if (*data).lineflag == line_sel::LINE_SEL_RIGHT_LEFT && oy == (*data).endsely {
             window_copy_other_end(wme);
@@ -1527,6 +1619,8 @@ pub unsafe fn window_copy_cmd_history_top(
         let wme: *mut window_mode_entry = (*cs).wme;
         let data: *mut window_copy_mode_data = (*wme).data.cast();
 
+        window_copy_jump_push(wme);
+
         let oy = screen_hsize((*data).backing) + (*data).cy - (*data).oy;
         if (*data).lineflag == line_sel::LINE_SEL_LEFT_RIGHT && oy == (*data).sely {
             window_copy_other_end(wme);
@@ -1635,6 +1729,97 @@ pub unsafe fn window_copy_cmd_middle_line(
     }
 }
 
+/// Walk backward from a closing bracket, or from inside a pair, to the
+/// opening bracket which matches it. Empty lines are skipped.
+unsafe fn window_copy_bracket_back(
+    gd: *mut grid,
+    mut px: u32,
+    mut py: u32,
+    open: u8,
+    close: u8,
+) -> Option<(u32, u32)> {
+    unsafe {
+        let mut gc: grid_cell = zeroed();
+        let mut n = 1u32;
+
+        loop {
+            if px == 0 {
+                if py == 0 {
+                    return None;
+                }
+                let mut xx;
+                loop {
+                    py -= 1;
+                    xx = grid_line_length(gd, py);
+                    if !(xx == 0 && py > 0) {
+                        break;
+                    }
+                }
+                if xx == 0 && py == 0 {
+                    return None;
+                }
+                px = xx - 1;
+            } else {
+                px -= 1;
+            }
+
+            grid_get_cell(gd, px, py, &raw mut gc);
+            if gc.data.size == 1 && !gc.flags.intersects(grid_flag::PADDING) {
+                if gc.data.data[0] == close {
+                    n += 1;
+                } else if gc.data.data[0] == open {
+                    n -= 1;
+                }
+            }
+            if n == 0 {
+                return Some((px, py));
+            }
+        }
+    }
+}
+
+/// Walk forward from an opening bracket to the closing bracket which matches
+/// it.
+unsafe fn window_copy_bracket_forward(
+    gd: *mut grid,
+    mut px: u32,
+    mut py: u32,
+    open: u8,
+    close: u8,
+) -> Option<(u32, u32)> {
+    unsafe {
+        let mut gc: grid_cell = zeroed();
+        let yy = (*gd).hsize + (*gd).sy - 1;
+        let mut xx = grid_line_length(gd, py);
+        let mut n = 1u32;
+
+        loop {
+            if px > xx {
+                if py == yy {
+                    return None;
+                }
+                px = 0;
+                py += 1;
+                xx = grid_line_length(gd, py);
+            } else {
+                px += 1;
+            }
+
+            grid_get_cell(gd, px, py, &raw mut gc);
+            if gc.data.size == 1 && !gc.flags.intersects(grid_flag::PADDING) {
+                if gc.data.data[0] == open {
+                    n += 1;
+                } else if gc.data.data[0] == close {
+                    n -= 1;
+                }
+            }
+            if n == 0 {
+                return Some((px, py));
+            }
+        }
+    }
+}
+
 pub unsafe fn window_copy_cmd_previous_matching_bracket(
     cs: *mut window_copy_cmd_state,
 ) -> window_copy_cmd_action {
@@ -1654,8 +1839,8 @@ pub unsafe fn window_copy_cmd_previous_matching_bracket(
         'outer: while np != 0 {
             /* Get cursor position and line length. */
             let mut px = (*data).cx;
-            let mut py = screen_hsize(s) + (*data).cy - (*data).oy;
-            let mut xx = window_copy_find_length(wme, py);
+            let py = screen_hsize(s) + (*data).cy - (*data).oy;
+            let xx = window_copy_find_length(wme, py);
             if xx == 0 {
                 break;
             }
@@ -1688,46 +1873,8 @@ pub unsafe fn window_copy_cmd_previous_matching_bracket(
                 }
                 let start = open[cp.offset_from_unsigned((&raw const close).cast())];
 
-                /* Walk backward until the matching bracket is reached. */
-                let mut n: u32 = 1;
-                let mut failed = 0;
-                loop {
-                    if px == 0 {
-                        if py == 0 {
-                            failed = 1;
-                            break;
-                        }
-                        loop {
-                            py -= 1;
-                            xx = window_copy_find_length(wme, py);
-                            if !(xx == 0 && py > 0) {
-                                break;
-                            }
-                        }
-                        if xx == 0 && py == 0 {
-                            failed = 1;
-                            break;
-                        }
-                        px = xx - 1;
-                    } else {
-                        px -= 1;
-                    }
-
-                    grid_get_cell((*s).grid, px, py, &raw mut gc);
-                    if gc.data.size == 1 && !gc.flags.intersects(grid_flag::PADDING) {
-                        if gc.data.data[0] == found {
-                            n += 1;
-                        } else if gc.data.data[0] == start {
-                            n -= 1;
-                        }
-                    }
-                    if n == 0 {
-                        break;
-                    }
-                }
-
-                // Move the cursor to the found location if any.
-                if failed == 0 {
+                // Move the cursor to the matching bracket if any.
+                if let Some((px, py)) = window_copy_bracket_back((*s).grid, px, py, start, found) {
                     window_copy_scroll_to(wme, px, py, false);
                 }
                 break;
@@ -1840,37 +1987,8 @@ pub unsafe fn window_copy_cmd_next_matching_bracket(
                 }
                 let end = close[cp.offset_from_unsigned((&raw const open).cast())];
 
-                /* Walk forward until the matching bracket is reached. */
-                let mut n = 1;
-                let mut failed = false;
-                loop {
-                    if px > xx {
-                        if py == yy {
-                            failed = true;
-                            break;
-                        }
-                        px = 0;
-                        py += 1;
-                        xx = window_copy_find_length(wme, py);
-                    } else {
-                        px += 1;
-                    }
-
-                    grid_get_cell((*s).grid, px, py, &raw mut gc);
-                    if gc.data.size == 1 && !gc.flags.intersects(grid_flag::PADDING) {
-                        if gc.data.data[0] == found {
-                            n += 1;
-                        } else if gc.data.data[0] == end {
-                            n -= 1;
-                        }
-                    }
-                    if n == 0 {
-                        break;
-                    }
-                }
-
-                /* Move the cursor to the found location if any. */
-                if !failed {
+                /* Move the cursor to the matching bracket if any. */
+                if let Some((px, py)) = window_copy_bracket_forward((*s).grid, px, py, found, end) {
                     window_copy_scroll_to(wme, px, py, false);
                 }
                 break;
@@ -2167,6 +2285,8 @@ pub unsafe fn window_copy_cmd_search_again(
         let data: *mut window_copy_mode_data = (*wme).data.cast();
         let mut np = (*wme).prefix;
 
+        window_copy_jump_push(wme);
+
         if (*data).searchtype == window_copy::WINDOW_COPY_SEARCHUP {
             while np != 0 {
                 window_copy_search_up(wme, (*data).searchregex);
@@ -2190,6 +2310,8 @@ pub unsafe fn window_copy_cmd_search_reverse(
         let data: *mut window_copy_mode_data = (*wme).data.cast();
         let mut np = (*wme).prefix;
 
+        window_copy_jump_push(wme);
+
         if (*data).searchtype == window_copy::WINDOW_COPY_SEARCHUP {
             while np != 0 {
                 window_copy_search_down(wme, (*data).searchregex);
@@ -2294,9 +2416,458 @@ pub unsafe fn window_copy_cmd_select_word(
     }
 }
 
+/// Get the character at a position if it is a single byte, otherwise zero.
+unsafe fn window_copy_object_char(gd: *mut grid, px: u32, py: u32) -> u8 {
+    unsafe {
+        let mut gc: grid_cell = zeroed();
+
+        grid_get_cell(gd, px, py, &raw mut gc);
+        if gc.data.size != 1 || gc.flags.intersects(grid_flag::PADDING) {
+            return 0;
+        }
+        gc.data.data[0]
+    }
+}
+
+/// Step a reader to the next or previous character on a line, following the
+/// line on to the next if it is wrapped. Returns false and leaves the reader
+/// where it was at the start or end of the line.
+unsafe fn window_copy_object_step(gr: *mut grid_reader, forward: bool) -> bool {
+    unsafe {
+        let gd = (*gr).gd;
+        let (cx, cy) = ((*gr).cx, (*gr).cy);
+
+        if forward {
+            grid_reader_cursor_right(gr, 0, 0);
+            if (*gr).cx >= grid_reader_line_length(gr) {
+                if (*grid_get_line(gd, cy))
+                    .flags
+                    .intersects(grid_line_flag::WRAPPED)
+                {
+                    grid_reader_cursor_right(gr, 1, 0);
+                }
+                if (*gr).cy == cy || grid_reader_line_length(gr) == 0 {
+                    grid_reader_start(gr, gd, cx, cy);
+                    return false;
+                }
+            }
+        } else {
+            if cx == 0
+                && (cy == 0
+                    || !(*grid_get_line(gd, cy - 1))
+                        .flags
+                        .intersects(grid_line_flag::WRAPPED))
+            {
+                return false;
+            }
+            grid_reader_cursor_left(gr, 0);
+            if (*gr).cy != cy {
+                grid_reader_cursor_left(gr, 0);
+            }
+            window_copy_object_unpad(gr);
+        }
+        true
+    }
+}
+
+/// Move a reader from a padding cell back to the start of its character.
+unsafe fn window_copy_object_unpad(gr: *mut grid_reader) {
+    unsafe {
+        let mut gc: grid_cell = zeroed();
+
+        while (*gr).cx > 0 {
+            grid_get_cell((*gr).gd, (*gr).cx, (*gr).cy, &raw mut gc);
+            if !gc.flags.intersects(grid_flag::PADDING) {
+                break;
+            }
+            (*gr).cx -= 1;
+        }
+    }
+}
+
+/// Get the class of the character under a reader for word objects:
+/// whitespace, separator or word. With big set, only whitespace splits words.
+unsafe fn window_copy_object_class(gr: *mut grid_reader, separators: *const u8, big: bool) -> u32 {
+    unsafe {
+        if grid_reader_in_set(gr, WHITESPACE) != 0 {
+            0
+        } else if !big && grid_reader_in_set(gr, separators) != 0 {
+            1
+        } else {
+            2
+        }
+    }
+}
+
+/// Move a reader over the characters next to it in one class, returning the
+/// position of the last one.
+unsafe fn window_copy_object_extend(
+    gd: *mut grid,
+    (px, py): (u32, u32),
+    forward: bool,
+    separators: *const u8,
+    big: bool,
+) -> (u32, u32) {
+    unsafe {
+        let mut gr: grid_reader = zeroed();
+        grid_reader_start(&raw mut gr, gd, px, py);
+        let class = window_copy_object_class(&raw mut gr, separators, big);
+
+        let mut last = (px, py);
+        while window_copy_object_step(&raw mut gr, forward)
+            && window_copy_object_class(&raw mut gr, separators, big) == class
+        {
+            last = (gr.cx, gr.cy);
+        }
+        last
+    }
+}
+
+/// Get the position and class of the character next to a position on the
+/// same line, if any.
+unsafe fn window_copy_object_next(
+    gd: *mut grid,
+    (px, py): (u32, u32),
+    forward: bool,
+    separators: *const u8,
+    big: bool,
+) -> Option<((u32, u32), u32)> {
+    unsafe {
+        let mut gr: grid_reader = zeroed();
+        grid_reader_start(&raw mut gr, gd, px, py);
+        if !window_copy_object_step(&raw mut gr, forward) {
+            return None;
+        }
+        let class = window_copy_object_class(&raw mut gr, separators, big);
+        Some(((gr.cx, gr.cy), class))
+    }
+}
+
+/// Find a word object at a position.
+unsafe fn window_copy_object_word(
+    gd: *mut grid,
+    px: u32,
+    py: u32,
+    separators: *const u8,
+    big: bool,
+    around: bool,
+) -> Option<(u32, u32, u32, u32)> {
+    unsafe {
+        let xx = grid_line_length(gd, py);
+        if xx == 0 {
+            return None;
+        }
+        let mut gr: grid_reader = zeroed();
+        grid_reader_start(&raw mut gr, gd, px.min(xx - 1), py);
+        window_copy_object_unpad(&raw mut gr);
+        let here = (gr.cx, gr.cy);
+        let class = window_copy_object_class(&raw mut gr, separators, big);
+
+        let mut start = window_copy_object_extend(gd, here, false, separators, big);
+        let mut end = window_copy_object_extend(gd, here, true, separators, big);
+
+        if around {
+            let next = window_copy_object_next(gd, end, true, separators, big);
+            if class == 0 {
+                // Whitespace and the word after it.
+                if let Some((pos, _)) = next {
+                    end = window_copy_object_extend(gd, pos, true, separators, big);
+                }
+            } else if let Some((pos, 0)) = next {
+                // The word and the whitespace after it.
+                end = window_copy_object_extend(gd, pos, true, separators, big);
+            } else if let Some((pos, 0)) =
+                window_copy_object_next(gd, start, false, separators, big)
+            {
+                // Otherwise the whitespace before it.
+                start = window_copy_object_extend(gd, pos, false, separators, big);
+            }
+        }
+        Some((start.0, start.1, end.0, end.1))
+    }
+}
+
+/// Find a quoted string object at a position. Quotes are paired from the
+/// start of the line; if the position is not inside a pair, the next pair
+/// after it is used.
+unsafe fn window_copy_object_quote(
+    gd: *mut grid,
+    px: u32,
+    py: u32,
+    quote: u8,
+    around: bool,
+) -> Option<(u32, u32, u32, u32)> {
+    unsafe {
+        let mut gr: grid_reader = zeroed();
+        grid_reader_start(&raw mut gr, gd, 0, py);
+        grid_reader_cursor_start_of_line(&raw mut gr, 1);
+
+        let mut quotes = Vec::new();
+        loop {
+            if window_copy_object_char(gd, gr.cx, gr.cy) == quote {
+                quotes.push((gr.cx, gr.cy));
+            }
+            if !window_copy_object_step(&raw mut gr, true) {
+                break;
+            }
+        }
+        let (open, close) = quotes
+            .chunks_exact(2)
+            .map(|pair| (pair[0], pair[1]))
+            .find(|&(_, (cx, cy))| (py, px) <= (cy, cx))?;
+
+        if !around {
+            grid_reader_start(&raw mut gr, gd, open.0, open.1);
+            window_copy_object_step(&raw mut gr, true);
+            let start = (gr.cx, gr.cy);
+            if start == close {
+                return None;
+            }
+            grid_reader_start(&raw mut gr, gd, close.0, close.1);
+            window_copy_object_step(&raw mut gr, false);
+            return Some((start.0, start.1, gr.cx, gr.cy));
+        }
+
+        let mut end = close;
+        grid_reader_start(&raw mut gr, gd, close.0, close.1);
+        while window_copy_object_step(&raw mut gr, true)
+            && window_copy_object_char(gd, gr.cx, gr.cy) == b' '
+        {
+            end = (gr.cx, gr.cy);
+        }
+        Some((open.0, open.1, end.0, end.1))
+    }
+}
+
+/// Find a bracket object around a position, matching brackets as
+/// previous-matching-bracket and next-matching-bracket do.
+unsafe fn window_copy_object_bracket(
+    gd: *mut grid,
+    px: u32,
+    py: u32,
+    open: u8,
+    close: u8,
+    around: bool,
+) -> Option<(u32, u32, u32, u32)> {
+    unsafe {
+        let (ox, oy) = if window_copy_object_char(gd, px, py) == open {
+            (px, py)
+        } else {
+            window_copy_bracket_back(gd, px, py, open, close)?
+        };
+        let (cx, cy) = window_copy_bracket_forward(gd, ox, oy, open, close)?;
+        if around {
+            return Some((ox, oy, cx, cy));
+        }
+
+        // Step inside the brackets, skipping the line ends next to them.
+        let (mut sx, mut sy) = (ox + 1, oy);
+        if sx >= grid_line_length(gd, sy) && sy < cy {
+            sx = 0;
+            sy += 1;
+        }
+        let (mut ex, mut ey) = (cx, cy);
+        if ex == 0 && ey > sy {
+            ey -= 1;
+            ex = grid_line_length(gd, ey);
+        }
+        if ex == 0 || (sy == ey && sx >= ex) {
+            return None;
+        }
+        Some((sx, sy, ex - 1, ey))
+    }
+}
+
+/// Find a paragraph object, a run of empty or non-empty lines.
+unsafe fn window_copy_object_paragraph(
+    gd: *mut grid,
+    py: u32,
+    around: bool,
+) -> Option<(u32, u32, u32, u32)> {
+    unsafe {
+        let yy = (*gd).hsize + (*gd).sy - 1;
+        let empty = |y| grid_line_length(gd, y) == 0;
+
+        let blank = empty(py);
+        let mut start = py;
+        while start > 0 && empty(start - 1) == blank {
+            start -= 1;
+        }
+        let mut end = py;
+        while end < yy && empty(end + 1) == blank {
+            end += 1;
+        }
+
+        if around {
+            if end < yy {
+                end += 1;
+                while end < yy && empty(end + 1) != blank {
+                    end += 1;
+                }
+            } else {
+                while start > 0 && empty(start - 1) != blank {
+                    start -= 1;
+                }
+            }
+        }
+        let ex = grid_line_length(gd, end).saturating_sub(1);
+        Some((0, start, ex, end))
+    }
+}
+
+/// Find the output of the command at the cursor from the OSC 133 prompt and
+/// output marks. Around includes the prompt and command line.
+unsafe fn window_copy_object_output(
+    gd: *mut grid,
+    py: u32,
+    around: bool,
+) -> Option<(u32, u32, u32, u32)> {
+    unsafe {
+        let yy = (*gd).hsize + (*gd).sy - 1;
+        let flags = |y| (*grid_get_line(gd, y)).flags;
+
+        // Look up for the start of the output. If a prompt comes first, the
+        // cursor is on a command line, so use the output that follows it.
+        let mut output = None;
+        let mut prompt = None;
+        let mut y = py;
+        loop {
+            if flags(y).intersects(grid_line_flag::START_OUTPUT) {
+                output = Some(y);
+                break;
+            }
+            if flags(y).intersects(grid_line_flag::START_PROMPT) {
+                prompt = Some(y);
+                break;
+            }
+            if y == 0 {
+                break;
+            }
+            y -= 1;
+        }
+        let output = match output {
+            Some(output) => output,
+            None => {
+                let mut y = prompt? + 1;
+                while y <= yy && !flags(y).intersects(grid_line_flag::START_PROMPT) {
+                    if flags(y).intersects(grid_line_flag::START_OUTPUT) {
+                        break;
+                    }
+                    y += 1;
+                }
+                if y > yy || !flags(y).intersects(grid_line_flag::START_OUTPUT) {
+                    return None;
+                }
+                y
+            }
+        };
+
+        let mut end = output;
+        while end < yy && !flags(end + 1).intersects(grid_line_flag::START_PROMPT) {
+            end += 1;
+        }
+        while end > output && grid_line_length(gd, end) == 0 {
+            end -= 1;
+        }
+
+        let mut start = output;
+        if around {
+            while start > 0 && !flags(start).intersects(grid_line_flag::START_PROMPT) {
+                start -= 1;
+            }
+        }
+        let ex = grid_line_length(gd, end).saturating_sub(1);
+        Some((0, start, ex, end))
+    }
+}
+
+/// Find a text object at a position, returning its first and last cells.
+unsafe fn window_copy_object_find(
+    gd: *mut grid,
+    px: u32,
+    py: u32,
+    object: u8,
+    separators: *const u8,
+    around: bool,
+) -> Option<(u32, u32, u32, u32)> {
+    unsafe {
+        match object {
+            b'w' => window_copy_object_word(gd, px, py, separators, false, around),
+            b'W' => window_copy_object_word(gd, px, py, separators, true, around),
+            b'"' | b'\'' | b'`' => window_copy_object_quote(gd, px, py, object, around),
+            b'(' | b')' | b'b' => window_copy_object_bracket(gd, px, py, b'(', b')', around),
+            b'[' | b']' => window_copy_object_bracket(gd, px, py, b'[', b']', around),
+            b'{' | b'}' | b'B' => window_copy_object_bracket(gd, px, py, b'{', b'}', around),
+            b'<' | b'>' => window_copy_object_bracket(gd, px, py, b'<', b'>', around),
+            b'p' => window_copy_object_paragraph(gd, py, around),
+            b'o' => window_copy_object_output(gd, py, around),
+            _ => None,
+        }
+    }
+}
+
+pub unsafe fn window_copy_cmd_select_object(
+    cs: *mut window_copy_cmd_state,
+) -> window_copy_cmd_action {
+    unsafe {
+        let wme: *mut window_mode_entry = (*cs).wme;
+        let data: *mut window_copy_mode_data = (*wme).data.cast();
+        let session_options: *mut options = (*(*cs).s).options;
+        let arg1 = cstr_to_str(args_string((*cs).args, 1)).as_bytes();
+
+        let &[kind @ (b'i' | b'a'), object] = arg1 else {
+            return window_copy_cmd_action::WINDOW_COPY_CMD_NOTHING;
+        };
+        let around = kind == b'a';
+        (*data).separators = options_get_string_(session_options, c"word-separators");
+
+        let gd = (*(*data).backing).grid;
+        let px = (*data).cx;
+        let py = (*gd).hsize + (*data).cy - (*data).oy;
+        let found = window_copy_object_find(gd, px, py, object, (*data).separators, around);
+        let Some((sx, sy, mut ex, ey)) = found else {
+            return window_copy_cmd_action::WINDOW_COPY_CMD_NOTHING;
+        };
+        if (*data).modekeys == modekey::MODEKEY_EMACS {
+            ex += 1;
+        }
+
+        (*data).lineflag = line_sel::LINE_SEL_NONE;
+        (*data).rectflag = 0;
+        (*data).selflag = selflag::SEL_CHAR;
+        window_copy_scroll_to(wme, sx, sy, true);
+        window_copy_start_selection(wme);
+        window_copy_scroll_to(wme, ex, ey, false);
+
+        window_copy_cmd_action::WINDOW_COPY_CMD_REDRAW
+    }
+}
+
 pub unsafe fn window_copy_cmd_set_mark(cs: *mut window_copy_cmd_state) -> window_copy_cmd_action {
     unsafe {
-        let data: *mut window_copy_mode_data = (*(*cs).wme).data.cast();
+        let wme: *mut window_mode_entry = (*cs).wme;
+        let data: *mut window_copy_mode_data = (*wme).data.cast();
+
+        if args_count((*cs).args) > 1 {
+            let Some(mark) = window_copy_mark_name(args_string((*cs).args, 1)) else {
+                return window_copy_cmd_action::WINDOW_COPY_CMD_NOTHING;
+            };
+            let gd = (*(*data).backing).grid;
+            let py = (*gd).hsize + (*data).cy - (*data).oy;
+            grid_set_mark(gd, mark, (*data).cx, py);
+
+            // Also mark the pane so the mark outlasts copy mode.
+            if (*data).viewmode == 0 {
+                let base = (*(*wme).swp).base.grid;
+                let ay = (*gd).htrimmed + py as u64;
+                if ay >= (*base).htrimmed
+                    && ay - (*base).htrimmed < ((*base).hsize + (*base).sy) as u64
+                {
+                    grid_set_mark(base, mark, (*data).cx, (ay - (*base).htrimmed) as u32);
+                }
+            }
+            return window_copy_cmd_action::WINDOW_COPY_CMD_REDRAW;
+        }
 
         (*data).mx = (*data).cx;
         (*data).my = screen_hsize((*data).backing) + (*data).cy - (*data).oy;
@@ -2428,6 +2999,7 @@ pub unsafe fn window_copy_cmd_goto_line(cs: *mut window_copy_cmd_state) -> windo
         let arg1 = args_string((*cs).args, 1);
 
         if *arg1 != b'\0' {
+            window_copy_jump_push(wme);
             window_copy_goto_line(wme, arg1);
         }
         window_copy_cmd_action::WINDOW_COPY_CMD_NOTHING
@@ -2527,12 +3099,76 @@ pub unsafe fn window_copy_cmd_jump_to_mark(
 ) -> window_copy_cmd_action {
     unsafe {
         let wme: *mut window_mode_entry = (*cs).wme;
+        let data: *mut window_copy_mode_data = (*wme).data.cast();
+
+        if args_count((*cs).args) > 1 {
+            let gd = (*(*data).backing).grid;
+            let Some((px, py)) = window_copy_mark_name(args_string((*cs).args, 1))
+                .and_then(|mark| grid_get_mark(gd, mark))
+            else {
+                return window_copy_cmd_action::WINDOW_COPY_CMD_NOTHING;
+            };
+            window_copy_jump_push(wme);
+            window_copy_scroll_to(wme, px.min((*gd).sx - 1), py, false);
+            return window_copy_cmd_action::WINDOW_COPY_CMD_NOTHING;
+        }
 
+        window_copy_jump_push(wme);
         window_copy_jump_to_mark(wme);
         window_copy_cmd_action::WINDOW_COPY_CMD_NOTHING
     }
 }
 
+pub unsafe fn window_copy_cmd_previous_jump(
+    cs: *mut window_copy_cmd_state,
+) -> window_copy_cmd_action {
+    unsafe {
+        let wme: *mut window_mode_entry = (*cs).wme;
+        let data: *mut window_copy_mode_data = (*wme).data.cast();
+        let gd = (*(*data).backing).grid;
+        let pos = ((*data).cx, (*gd).hsize + (*data).cy - (*data).oy);
+
+        // Remember where we are so next-jump can come back.
+        if (*data).jumplistat == (*data).jumplistsize {
+            window_copy_jump_push(wme);
+        }
+        loop {
+            if (*data).jumplistat == 0 {
+                return window_copy_cmd_action::WINDOW_COPY_CMD_NOTHING;
+            }
+            (*data).jumplistat -= 1;
+            if (*data).jumplist[(*data).jumplistat as usize] != pos {
+                break;
+            }
+        }
+
+        let (px, py) = (*data).jumplist[(*data).jumplistat as usize];
+        if py < (*gd).hsize + (*gd).sy {
+            window_copy_scroll_to(wme, px.min((*gd).sx - 1), py, false);
+        }
+        window_copy_cmd_action::WINDOW_COPY_CMD_NOTHING
+    }
+}
+
+pub unsafe fn window_copy_cmd_next_jump(cs: *mut window_copy_cmd_state) -> window_copy_cmd_action {
+    unsafe {
+        let wme: *mut window_mode_entry = (*cs).wme;
+        let data: *mut window_copy_mode_data = (*wme).data.cast();
+        let gd = (*(*data).backing).grid;
+
+        if (*data).jumplistat + 1 >= (*data).jumplistsize {
+            return window_copy_cmd_action::WINDOW_COPY_CMD_NOTHING;
+        }
+        (*data).jumplistat += 1;
+
+        let (px, py) = (*data).jumplist[(*data).jumplistat as usize];
+        if py < (*gd).hsize + (*gd).sy {
+            window_copy_scroll_to(wme, px.min((*gd).sx - 1), py, false);
+        }
+        window_copy_cmd_action::WINDOW_COPY_CMD_NOTHING
+    }
+}
+
 pub unsafe fn window_copy_cmd_next_prompt(
     cs: *mut window_copy_cmd_state,
 ) -> window_copy_cmd_action {
@@ -2573,6 +3209,7 @@ pub unsafe fn window_copy_cmd_search_backward(
             (*data).searchtype = window_copy::WINDOW_COPY_SEARCHUP;
             (*data).searchregex = 1;
             (*data).timeout = 0;
+            window_copy_jump_push(wme);
             while np != 0 {
                 window_copy_search_up(wme, 1);
                 np -= 1;
@@ -2598,6 +3235,7 @@ pub unsafe fn window_copy_cmd_search_backward_text(
             (*data).searchtype = window_copy::WINDOW_COPY_SEARCHUP;
             (*data).searchregex = 0;
             (*data).timeout = 0;
+            window_copy_jump_push(wme);
             while np != 0 {
                 window_copy_search_up(wme, 0);
                 np -= 1;
@@ -2623,6 +3261,7 @@ pub unsafe fn window_copy_cmd_search_forward(
             (*data).searchtype = window_copy::WINDOW_COPY_SEARCHDOWN;
             (*data).searchregex = 1;
             (*data).timeout = 0;
+            window_copy_jump_push(wme);
             while np != 0 {
                 window_copy_search_down(wme, 1);
                 np -= 1;
@@ -2648,6 +3287,7 @@ pub unsafe fn window_copy_cmd_search_forward_text(
             (*data).searchtype = window_copy::WINDOW_COPY_SEARCHDOWN;
             (*data).searchregex = 0;
             (*data).timeout = 0;
+            window_copy_jump_push(wme);
             while np != 0 {
                 window_copy_search_down(wme, 0);
                 np -= 1;
@@ -2693,6 +3333,7 @@ pub unsafe fn window_copy_cmd_search_backward_incremental(
                 (*data).searchregex = 0;
                 free_((*data).searchstr);
                 (*data).searchstr = xstrdup(arg1).as_ptr();
+                (*data).searchcflags = 0;
                 if window_copy_search_up(wme, 0) == 0 {
                     window_copy_clear_marks(wme);
                     return window_copy_cmd_action::WINDOW_COPY_CMD_REDRAW;
@@ -2703,6 +3344,7 @@ pub unsafe fn window_copy_cmd_search_backward_incremental(
                 (*data).searchregex = 0;
                 free_((*data).searchstr);
                 (*data).searchstr = xstrdup(arg1).as_ptr();
+                (*data).searchcflags = 0;
                 if window_copy_search_down(wme, 0) == 0 {
                     window_copy_clear_marks(wme);
                     return window_copy_cmd_action::WINDOW_COPY_CMD_REDRAW;
@@ -2750,6 +3392,7 @@ pub unsafe fn window_copy_cmd_search_forward_incremental(
                 (*data).searchregex = 0;
                 free_((*data).searchstr);
                 (*data).searchstr = xstrdup(arg1).as_ptr();
+                (*data).searchcflags = 0;
                 if window_copy_search_down(wme, 0) == 0 {
                     window_copy_clear_marks(wme);
                     return window_copy_cmd_action::WINDOW_COPY_CMD_REDRAW;
@@ -2760,6 +3403,7 @@ pub unsafe fn window_copy_cmd_search_forward_incremental(
                 (*data).searchregex = 0;
                 free_((*data).searchstr);
                 (*data).searchstr = xstrdup(arg1).as_ptr();
+                (*data).searchcflags = 0;
                 if window_copy_search_up(wme, 0) == 0 {
                     window_copy_clear_marks(wme);
                     return window_copy_cmd_action::WINDOW_COPY_CMD_REDRAW;
@@ -2807,7 +3451,7 @@ struct window_copy_cmd_table_entry {
     f: unsafe fn(*mut window_copy_cmd_state) -> window_copy_cmd_action,
 }
 
-static WINDOW_COPY_CMD_TABLE: [window_copy_cmd_table_entry; 85] = [
+static WINDOW_COPY_CMD_TABLE: [window_copy_cmd_table_entry; 90] = [
     window_copy_cmd_table_entry {
         command: SyncCharPtr::new(c"append-selection"),
         minargs: 0,
@@ -2955,6 +3599,20 @@ static WINDOW_COPY_CMD_TABLE: [window_copy_cmd_table_entry; 85] = [
         clear: window_copy_cmd_clear::WINDOW_COPY_CMD_CLEAR_ALWAYS,
         f: window_copy_cmd_copy_selection_and_cancel,
     },
+    window_copy_cmd_table_entry {
+        command: SyncCharPtr::new(c"copy-selection-as"),
+        minargs: 1,
+        maxargs: 2,
+        clear: window_copy_cmd_clear::WINDOW_COPY_CMD_CLEAR_ALWAYS,
+        f: window_copy_cmd_copy_selection_as,
+    },
+    window_copy_cmd_table_entry {
+        command: SyncCharPtr::new(c"copy-selection-as-and-cancel"),
+        minargs: 1,
+        maxargs: 2,
+        clear: window_copy_cmd_clear::WINDOW_COPY_CMD_CLEAR_ALWAYS,
+        f: window_copy_cmd_copy_selection_as_and_cancel,
+    },
     window_copy_cmd_table_entry {
         command: SyncCharPtr::new(c"cursor-down"),
         minargs: 0,
@@ -3084,7 +3742,7 @@ static WINDOW_COPY_CMD_TABLE: [window_copy_cmd_table_entry; 85] = [
     window_copy_cmd_table_entry {
         command: SyncCharPtr::new(c"jump-to-mark"),
         minargs: 0,
-        maxargs: 0,
+        maxargs: 1,
         clear: window_copy_cmd_clear::WINDOW_COPY_CMD_CLEAR_ALWAYS,
         f: window_copy_cmd_jump_to_mark,
     },
@@ -3109,6 +3767,13 @@ static WINDOW_COPY_CMD_TABLE: [window_copy_cmd_table_entry; 85] = [
         clear: window_copy_cmd_clear::WINDOW_COPY_CMD_CLEAR_EMACS_ONLY,
         f: window_copy_cmd_middle_line,
     },
+    window_copy_cmd_table_entry {
+        command: SyncCharPtr::new(c"next-jump"),
+        minargs: 0,
+        maxargs: 0,
+        clear: window_copy_cmd_clear::WINDOW_COPY_CMD_CLEAR_EMACS_ONLY,
+        f: window_copy_cmd_next_jump,
+    },
     window_copy_cmd_table_entry {
         command: SyncCharPtr::new(c"next-matching-bracket"),
         minargs: 0,
@@ -3200,6 +3865,13 @@ static WINDOW_COPY_CMD_TABLE: [window_copy_cmd_table_entry; 85] = [
         clear: window_copy_cmd_clear::WINDOW_COPY_CMD_CLEAR_ALWAYS,
         f: window_copy_cmd_pipe_and_cancel,
     },
+    window_copy_cmd_table_entry {
+        command: SyncCharPtr::new(c"previous-jump"),
+        minargs: 0,
+        maxargs: 0,
+        clear: window_copy_cmd_clear::WINDOW_COPY_CMD_CLEAR_EMACS_ONLY,
+        f: window_copy_cmd_previous_jump,
+    },
     window_copy_cmd_table_entry {
         command: SyncCharPtr::new(c"previous-matching-bracket"),
         minargs: 0,
@@ -3361,6 +4033,13 @@ static WINDOW_COPY_CMD_TABLE: [window_copy_cmd_table_entry; 85] = [
         clear: window_copy_cmd_clear::WINDOW_COPY_CMD_CLEAR_ALWAYS,
         f: window_copy_cmd_select_line,
     },
+    window_copy_cmd_table_entry {
+        command: SyncCharPtr::new(c"select-object"),
+        minargs: 1,
+        maxargs: 1,
+        clear: window_copy_cmd_clear::WINDOW_COPY_CMD_CLEAR_ALWAYS,
+        f: window_copy_cmd_select_object,
+    },
     window_copy_cmd_table_entry {
         command: SyncCharPtr::new(c"select-word"),
         minargs: 0,
@@ -3371,7 +4050,7 @@ static WINDOW_COPY_CMD_TABLE: [window_copy_cmd_table_entry; 85] = [
     window_copy_cmd_table_entry {
         command: SyncCharPtr::new(c"set-mark"),
         minargs: 0,
-        maxargs: 0,
+        maxargs: 1,
         clear: window_copy_cmd_clear::WINDOW_COPY_CMD_CLEAR_ALWAYS,
         f: window_copy_cmd_set_mark,
     },
@@ -4112,6 +4791,17 @@ pub unsafe fn window_copy_move_right(s: *mut screen, fx: *mut u32, fy: *mut u32,
     }
 }
 
+/// Whether to ignore case when searching for the search string.
+unsafe fn window_copy_search_cis(data: *mut window_copy_mode_data) -> bool {
+    unsafe {
+        if (*data).searchcflags != 0 {
+            (*data).searchcflags & REG_ICASE != 0
+        } else {
+            window_copy_is_lowercase((*data).searchstr)
+        }
+    }
+}
+
 pub unsafe fn window_copy_is_lowercase(mut ptr: *const u8) -> bool {
     unsafe {
         while *ptr != b'\0' {
@@ -4430,7 +5120,7 @@ pub unsafe fn window_copy_search(
         screen_write_stop(&raw mut ctx);
 
         wrapflag = options_get_number_((*(*wp).window).options, c"wrap-search") as i32;
-        cis = window_copy_is_lowercase(str) as i32;
+        cis = window_copy_search_cis(data) as i32;
 
         let keys =
             modekey::try_from(options_get_number_((*(*wp).window).options, c"mode-keys") as i32);
@@ -4618,7 +5308,7 @@ pub unsafe fn window_copy_search_marks(
                 width = screen_size_x(ssp);
             }
 
-            cis = window_copy_is_lowercase((*data).searchstr) as i32;
+            cis = window_copy_search_cis(data) as i32;
 
             if regex != 0 {
                 sbuf = xmalloc(ssize as usize).as_ptr().cast();
@@ -5105,6 +5795,24 @@ pub unsafe fn window_copy_write_line(
                 &raw mut cgc,
                 &raw mut mkgc,
             );
+
+            // Show the first named mark on this line in its last column, if
+            // that is blank and not needed for the end of line marker.
+            let gd = (*(*data).backing).grid;
+            let ml = grid_peek_line(gd, hsize - (*data).oy + py);
+            let mx = screen_size_x(s) - size as u32 - 1;
+            if !ml.is_null()
+                && (*ml).marks != 0
+                && mx >= grid_line_length(gd, hsize - (*data).oy + py)
+                && !(py == (*data).cy && (*data).cx == screen_size_x(s))
+            {
+                screen_write_cursormove(ctx, mx as i32, py as i32, 0);
+                screen_write_putc(
+                    ctx,
+                    &raw const mkgc,
+                    b'a' + (*ml).marks.trailing_zeros() as u8,
+                );
+            }
         }
 
         if py == (*data).cy && (*data).cx == screen_size_x(s) {
@@ -5122,7 +5830,7 @@ pub unsafe fn window_copy_write_lines(
 ) {
     unsafe {
         for yy in py..(py + ny) {
-            window_copy_write_line(wme, ctx, py);
+            window_copy_write_line(wme, ctx, yy);
         }
     }
 }
@@ -5432,7 +6140,11 @@ pub unsafe fn window_copy_set_selection(
     }
 }
 
-pub unsafe fn window_copy_get_selection(wme: *mut window_mode_entry, len: *mut usize) -> *mut u8 {
+pub unsafe fn window_copy_get_selection(
+    wme: *mut window_mode_entry,
+    format: grid_export_format,
+    len: *mut usize,
+) -> *mut u8 {
     unsafe {
         let wp: *mut window_pane = (*wme).wp;
         let data: *mut window_copy_mode_data = (*wme).data.cast();
@@ -5450,6 +6162,10 @@ pub unsafe fn window_copy_get_selection(wme: *mut window_mode_entry, len: *mut u
         let mut restsx = 0;
 
         if (*data).screen.sel.is_null() && (*data).lineflag == line_sel::LINE_SEL_NONE {
+            if format != grid_export_format::GRID_EXPORT_TEXT {
+                *len = 0;
+                return null_mut();
+            }
             buf = window_copy_match_at_cursor(data);
             if !buf.is_null() {
                 *len = strlen(buf);
@@ -5540,12 +6256,22 @@ pub unsafe fn window_copy_get_selection(wme: *mut window_mode_entry, len: *mut u
             restsx = 0;
         }
 
-        /* Copy the lines. */
+        /* Copy the lines, keeping their colours if not plain text. */
+        let mut ge = if format != grid_export_format::GRID_EXPORT_TEXT {
+            Some(grid_export_create(
+                format,
+                &raw const (*wp).palette,
+                &raw mut (*data).screen,
+            ))
+        } else {
+            None
+        };
         for i in sy..=ey {
             window_copy_copy_line(
                 wme,
                 &raw mut buf,
                 &raw mut off,
+                ge.as_mut(),
                 i,
                 if i == sy { firstsx } else { restsx },
                 if i == ey { lastex } else { restex },
@@ -5566,6 +6292,13 @@ pub unsafe fn window_copy_get_selection(wme: *mut window_mode_entry, len: *mut u
                 || lastex != ey_last)
         {
             off -= 1;
+            if let Some(ge) = ge.as_mut() {
+                grid_export_trim_newline(ge);
+            }
+        }
+        if let Some(ge) = ge {
+            free_(buf);
+            return grid_export_finish(ge, len);
         }
         *len = off;
         buf
@@ -5600,7 +6333,12 @@ pub unsafe fn window_copy_pipe_run(
     len: *mut usize,
 ) -> *mut c_void {
     unsafe {
-        let buf = window_copy_get_selection(wme, len);
+        let format = grid_export_format::try_from(options_get_number_(
+            GLOBAL_OPTIONS,
+            c"copy-pipe-format",
+        ) as i32)
+        .unwrap_or(grid_export_format::GRID_EXPORT_TEXT);
+        let buf = window_copy_get_selection(wme, format, len);
         if cmd.is_null() || *cmd == b'\0' {
             cmd = options_get_string_(GLOBAL_OPTIONS, c"copy-command");
         }
@@ -5649,10 +6387,14 @@ pub unsafe fn window_copy_copy_pipe(
     }
 }
 
-pub unsafe fn window_copy_copy_selection(wme: *mut window_mode_entry, prefix: *const u8) {
+pub unsafe fn window_copy_copy_selection(
+    wme: *mut window_mode_entry,
+    prefix: *const u8,
+    format: grid_export_format,
+) {
     unsafe {
         let mut len: usize = 0;
-        let buf = window_copy_get_selection(wme, &raw mut len);
+        let buf = window_copy_get_selection(wme, format, &raw mut len);
         if !buf.is_null() {
             window_copy_copy_buffer(wme, prefix, buf.cast(), len);
         }
@@ -5668,7 +6410,8 @@ pub unsafe fn window_copy_append_selection(wme: *mut window_mode_entry) {
         let mut ctx: screen_write_ctx = zeroed();
         let mut bufsize = 0;
         let mut len: usize = 0;
-        let mut buf = window_copy_get_selection(wme, &raw mut len);
+        let mut buf =
+            window_copy_get_selection(wme, grid_export_format::GRID_EXPORT_TEXT, &raw mut len);
         if buf.is_null() {
             return;
         }
@@ -5698,6 +6441,7 @@ pub unsafe fn window_copy_copy_line(
     wme: *mut window_mode_entry,
     buf: *mut *mut u8,
     off: *mut usize,
+    mut ge: Option<&mut grid_export>,
     sy: u32,
     mut sx: u32,
     mut ex: u32,
@@ -5751,6 +6495,11 @@ pub unsafe fn window_copy_copy_line(
                         libc::memcpy((&raw mut ud.data).cast(), s.cast(), ud.size as usize);
                     }
                 }
+                if let Some(ge) = ge.as_deref_mut() {
+                    gc.data = ud;
+                    gc.attr &= !grid_attr::GRID_ATTR_CHARSET;
+                    grid_export_cell(ge, &gc);
+                }
 
                 *buf = xrealloc((*buf).cast(), (*off) + ud.size as usize)
                     .as_ptr()
@@ -5769,6 +6518,9 @@ pub unsafe fn window_copy_copy_line(
             *buf = xrealloc((*buf).cast(), (*off) + 1).as_ptr().cast();
             *(*buf).add(*off) = b'\n';
             (*off) += 1;
+            if let Some(ge) = ge {
+                grid_export_newline(ge);
+            }
         }
     }
 }
@@ -6472,7 +7224,7 @@ pub unsafe fn window_copy_scroll_up(wme: *mut window_mode_entry, mut ny: u32) {
         }
         (*data).oy -= ny;
 
-        if (*data).searchmark.is_null() && (*data).timeout == 0 {
+        if !(*data).searchmark.is_null() && (*data).timeout == 0 {
             window_copy_search_marks(wme, null_mut(), (*data).searchregex, 1);
         }
         window_copy_update_selection(wme, 0, 0);
@@ -6708,6 +7460,40 @@ pub unsafe fn window_copy_drag_release(c: *mut client, m: *mut mouse_event) {
     }
 }
 
+/// Convert a mark name (a to z) to its index.
+unsafe fn window_copy_mark_name(name: *const u8) -> Option<u32> {
+    unsafe {
+        if !(*name).is_ascii_lowercase() || *name.add(1) != b'\0' {
+            return None;
+        }
+        Some((*name - b'a') as u32)
+    }
+}
+
+/// Add the cursor position to the jump list before a large move.
+pub unsafe fn window_copy_jump_push(wme: *mut window_mode_entry) {
+    unsafe {
+        let data: *mut window_copy_mode_data = (*wme).data.cast();
+        let pos = (
+            (*data).cx,
+            screen_hsize((*data).backing) + (*data).cy - (*data).oy,
+        );
+
+        // Jumping from part way back through the list drops what was after.
+        (*data).jumplistsize = (*data).jumplistat;
+        if (*data).jumplistsize != 0 && (*data).jumplist[(*data).jumplistsize as usize - 1] == pos {
+            return;
+        }
+        if (*data).jumplistsize as usize == WINDOW_COPY_JUMPLIST {
+            (*data).jumplist.copy_within(1.., 0);
+            (*data).jumplistsize -= 1;
+        }
+        (*data).jumplist[(*data).jumplistsize as usize] = pos;
+        (*data).jumplistsize += 1;
+        (*data).jumplistat = (*data).jumplistsize;
+    }
+}
+
 pub unsafe fn window_copy_jump_to_mark(wme: *mut window_mode_entry) {
     unsafe {
         let data: *mut window_copy_mode_data = (*wme).data.cast();
@@ -6778,7 +7564,7 @@ pub unsafe fn window_copy_acquire_cursor_down(
     no_reset: i32,
 ) {
     unsafe {
-        let cy = py - hsize + oy;
+        let cy = py + oy - hsize;
         let yy = sy - 1;
         let mut ny;
         let nd;
@@ -6804,3 +7590,114 @@ pub unsafe fn window_copy_acquire_cursor_down(
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// Find an object such as "iw" at a position in a grid of the given
+    /// width holding some lines, with the lines listed in wrapped flagged as
+    /// wrapped on to the next.
+    fn object(
+        sx: u32,
+        lines: &[&str],
+        wrapped: &[u32],
+        (px, py): (u32, u32),
+        what: &str,
+    ) -> Option<(u32, u32, u32, u32)> {
+        unsafe {
+            let gd = grid_create(sx, lines.len() as u32, 0);
+            let mut gc = GRID_DEFAULT_CELL;
+            for (y, line) in lines.iter().enumerate() {
+                for (x, ch) in line.bytes().enumerate() {
+                    utf8_set(&raw mut gc.data, ch);
+                    grid_set_cell(gd, x as u32, y as u32, &raw const gc);
+                }
+            }
+            for &y in wrapped {
+                (*grid_get_line(gd, y)).flags |= grid_line_flag::WRAPPED;
+            }
+
+            let &[kind, object] = what.as_bytes() else {
+                panic!("bad object: {what}");
+            };
+            let found = window_copy_object_find(gd, px, py, object, c!(".,"), kind == b'a');
+            grid_destroy(gd);
+            found
+        }
+    }
+
+    #[test]
+    fn test_object_word() {
+        let line = &["foo bar  baz"];
+        assert_eq!(object(20, line, &[], (5, 0), "iw"), Some((4, 0, 6, 0)));
+        assert_eq!(object(20, line, &[], (5, 0), "aw"), Some((4, 0, 8, 0)));
+        assert_eq!(object(20, line, &[], (10, 0), "aw"), Some((7, 0, 11, 0)));
+        assert_eq!(object(20, line, &[], (7, 0), "iw"), Some((7, 0, 8, 0)));
+        assert_eq!(object(20, line, &[], (7, 0), "aw"), Some((7, 0, 11, 0)));
+
+        assert_eq!(
+            object(20, &["a.b c"], &[], (0, 0), "iw"),
+            Some((0, 0, 0, 0))
+        );
+        assert_eq!(
+            object(20, &["a.b c"], &[], (0, 0), "iW"),
+            Some((0, 0, 2, 0))
+        );
+        assert_eq!(
+            object(20, &["a.b c"], &[], (0, 0), "aW"),
+            Some((0, 0, 3, 0))
+        );
+        assert_eq!(object(20, &[""], &[], (0, 0), "iw"), None);
+    }
+
+    #[test]
+    fn test_object_word_wrapped() {
+        let lines = &["aaaa bbbbb", "bb cc"];
+        assert_eq!(object(10, lines, &[0], (7, 0), "iw"), Some((5, 0, 1, 1)));
+        assert_eq!(object(10, lines, &[0], (0, 1), "iw"), Some((5, 0, 1, 1)));
+        assert_eq!(object(10, lines, &[0], (0, 1), "aw"), Some((5, 0, 2, 1)));
+        assert_eq!(object(10, lines, &[], (0, 1), "iw"), Some((0, 1, 1, 1)));
+    }
+
+    #[test]
+    fn test_object_quote() {
+        let line = &["say \"hi there\" ok"];
+        assert_eq!(object(20, line, &[], (6, 0), "i\""), Some((5, 0, 12, 0)));
+        assert_eq!(object(20, line, &[], (6, 0), "a\""), Some((4, 0, 14, 0)));
+        assert_eq!(object(20, line, &[], (1, 0), "i\""), Some((5, 0, 12, 0)));
+        assert_eq!(object(20, line, &[], (15, 0), "i\""), None);
+        assert_eq!(object(20, line, &[], (6, 0), "i'"), None);
+        assert_eq!(object(20, &["x '' y"], &[], (2, 0), "i'"), None);
+        assert_eq!(
+            object(20, &["x '' y"], &[], (2, 0), "a'"),
+            Some((2, 0, 4, 0))
+        );
+    }
+
+    #[test]
+    fn test_object_bracket() {
+        let line = &["f(a, (b)) x"];
+        assert_eq!(object(20, line, &[], (2, 0), "i("), Some((2, 0, 7, 0)));
+        assert_eq!(object(20, line, &[], (2, 0), "a("), Some((1, 0, 8, 0)));
+        assert_eq!(object(20, line, &[], (6, 0), "ib"), Some((6, 0, 6, 0)));
+        assert_eq!(object(20, line, &[], (6, 0), "a)"), Some((5, 0, 7, 0)));
+        assert_eq!(object(20, line, &[], (5, 0), "a("), Some((5, 0, 7, 0)));
+        assert_eq!(object(20, line, &[], (10, 0), "i("), None);
+        assert_eq!(object(20, &["()"], &[], (0, 0), "i("), None);
+
+        let lines = &["{", "  x", "}"];
+        assert_eq!(object(20, lines, &[], (2, 1), "i{"), Some((0, 1, 2, 1)));
+        assert_eq!(object(20, lines, &[], (2, 1), "aB"), Some((0, 0, 0, 2)));
+    }
+
+    #[test]
+    fn test_object_paragraph() {
+        let lines = &["a", "b", "", "c"];
+        assert_eq!(object(20, lines, &[], (0, 0), "ip"), Some((0, 0, 0, 1)));
+        assert_eq!(object(20, lines, &[], (0, 0), "ap"), Some((0, 0, 0, 2)));
+        assert_eq!(object(20, lines, &[], (0, 3), "ip"), Some((0, 3, 0, 3)));
+        assert_eq!(object(20, lines, &[], (0, 3), "ap"), Some((0, 2, 0, 3)));
+        assert_eq!(object(20, lines, &[], (0, 2), "ip"), Some((0, 2, 0, 2)));
+    }
+}
@@ -0,0 +1,269 @@
+use crate::*;
+
+use crate::compat::queue::tailq_foreach;
+
+pub static WINDOW_RESIZE_MODE: window_mode = window_mode {
+    name: SyncCharPtr::new(c"resize-mode"),
+    default_format: SyncCharPtr::null(),
+
+    init: window_resize_init,
+    free: window_resize_free,
+    resize: window_resize_resize,
+    update: None,
+    key: None,
+    key_table: Some(window_resize_key_table),
+    command: Some(window_resize_command),
+    formats: None,
+};
+
+pub struct window_resize_mode_data {
+    pub screen: screen,
+
+    /// Layout when the mode was entered and the size and offset of each of
+    /// its cells, to put back if the resize is cancelled.
+    pub root: *mut layout_cell,
+    pub saved: Vec<(u32, u32, u32, u32)>,
+
+    /// Direction that number keys and the size display apply to.
+    pub type_: layout_type,
+}
+
+/// Save the size and offset of every cell.
+unsafe fn window_resize_save(lc: *mut layout_cell, saved: &mut Vec<(u32, u32, u32, u32)>) {
+    unsafe {
+        saved.push(((*lc).sx, (*lc).sy, (*lc).xoff, (*lc).yoff));
+        if (*lc).type_ != layout_type::LAYOUT_WINDOWPANE {
+            for lcchild in tailq_foreach(&raw mut (*lc).cells).map(NonNull::as_ptr) {
+                window_resize_save(lcchild, saved);
+            }
+        }
+    }
+}
+
+/// Put back saved cell sizes into a layout of the same shape.
+unsafe fn window_resize_restore(
+    lc: *mut layout_cell,
+    saved: &mut std::slice::Iter<(u32, u32, u32, u32)>,
+) {
+    unsafe {
+        if let Some(&(sx, sy, xoff, yoff)) = saved.next() {
+            (*lc).sx = sx;
+            (*lc).sy = sy;
+            (*lc).xoff = xoff;
+            (*lc).yoff = yoff;
+        }
+        if (*lc).type_ != layout_type::LAYOUT_WINDOWPANE {
+            for lcchild in tailq_foreach(&raw mut (*lc).cells).map(NonNull::as_ptr) {
+                window_resize_restore(lcchild, saved);
+            }
+        }
+    }
+}
+
+pub unsafe fn window_resize_init(
+    wme: NonNull<window_mode_entry>,
+    _fs: *mut cmd_find_state,
+    _args: *mut args,
+) -> *mut screen {
+    unsafe {
+        let wp = (*wme.as_ptr()).wp;
+        let w = (*wp).window;
+
+        let mut saved = Vec::new();
+        window_resize_save((*w).layout_root, &mut saved);
+        let data = Box::into_raw(Box::new(window_resize_mode_data {
+            screen: zeroed(),
+            root: (*w).layout_root,
+            saved,
+            type_: layout_type::LAYOUT_LEFTRIGHT,
+        }));
+        (*wme.as_ptr()).data = data.cast();
+
+        let s = &raw mut (*data).screen;
+        screen_init(
+            s,
+            screen_size_x(&raw mut (*wp).base),
+            screen_size_y(&raw mut (*wp).base),
+            0,
+        );
+        (*s).mode &= !mode_flag::MODE_CURSOR;
+
+        window_resize_draw_screen(wme);
+
+        s
+    }
+}
+
+pub unsafe fn window_resize_free(wme: NonNull<window_mode_entry>) {
+    unsafe {
+        let data = (*wme.as_ptr()).data as *mut window_resize_mode_data;
+
+        screen_free(&raw mut (*data).screen);
+        drop(Box::from_raw(data));
+    }
+}
+
+pub unsafe fn window_resize_resize(wme: NonNull<window_mode_entry>, sx: u32, sy: u32) {
+    unsafe {
+        let data = (*wme.as_ptr()).data as *mut window_resize_mode_data;
+
+        screen_resize(&raw mut (*data).screen, sx, sy, 0);
+        window_resize_draw_screen(wme);
+    }
+}
+
+pub unsafe fn window_resize_key_table(_wme: *mut window_mode_entry) -> *const u8 {
+    c!("resize-mode")
+}
+
+/// Draw the pane's contents with its size in a box in the middle.
+unsafe fn window_resize_draw_screen(wme: NonNull<window_mode_entry>) {
+    unsafe {
+        let wp = (*wme.as_ptr()).wp;
+        let w = (*wp).window;
+        let data = (*wme.as_ptr()).data as *mut window_resize_mode_data;
+        let s = &raw mut (*data).screen;
+        let sx = screen_size_x(s);
+        let sy = screen_size_y(s);
+        let mut ctx: screen_write_ctx = zeroed();
+
+        screen_write_start(&raw mut ctx, s);
+        screen_write_clearscreen(&raw mut ctx, 8);
+        screen_write_cursormove(&raw mut ctx, 0, 0, 0);
+        screen_write_fast_copy(
+            &raw mut ctx,
+            &raw mut (*wp).base,
+            0,
+            (*(*wp).base.grid).hsize,
+            sx,
+            sy,
+        );
+
+        let what = if (*data).type_ == layout_type::LAYOUT_LEFTRIGHT {
+            "width"
+        } else {
+            "height"
+        };
+        let text = format!(
+            " {}x{} ({}%x{}%) {} ",
+            sx,
+            sy,
+            sx * 100 / (*w).sx.max(1),
+            sy * 100 / (*w).sy.max(1),
+            what
+        );
+        let nx = text.len() as u32 + 2;
+        let ny = 3;
+        if sx >= nx && sy >= ny {
+            let px = (sx - nx) / 2;
+            let py = (sy - ny) / 2;
+
+            let mut gc: grid_cell = GRID_DEFAULT_CELL;
+            style_apply(&raw mut gc, (*w).options, c!("mode-style"), null_mut());
+
+            screen_write_cursormove(&raw mut ctx, px as i32, py as i32, 0);
+            screen_write_box(
+                &raw mut ctx,
+                nx,
+                ny,
+                box_lines::BOX_LINES_DEFAULT,
+                &raw const gc,
+                null(),
+            );
+            screen_write_cursormove(&raw mut ctx, px as i32 + 1, py as i32 + 1, 0);
+            screen_write_nputs!(&raw mut ctx, -1, &raw const gc, "{}", text);
+        }
+
+        screen_write_stop(&raw mut ctx);
+    }
+}
+
+/// Put the layout back as it was when the mode was entered, if it has not
+/// been replaced since.
+unsafe fn window_resize_cancel(wme: *mut window_mode_entry) {
+    unsafe {
+        let w = (*(*wme).wp).window;
+        let data = (*wme).data as *mut window_resize_mode_data;
+
+        let mut now = Vec::new();
+        window_resize_save((*w).layout_root, &mut now);
+        if (*w).layout_root != (*data).root || now.len() != (*data).saved.len() {
+            return;
+        }
+        window_resize_restore((*w).layout_root, &mut (*data).saved.iter());
+        layout_fix_panes(w, null_mut());
+        notify_window(c"window-layout-changed", w);
+        server_redraw_window(w);
+    }
+}
+
+pub unsafe fn window_resize_command(
+    wme: NonNull<window_mode_entry>,
+    _c: *mut client,
+    _s: *mut session,
+    _wl: *mut winlink,
+    args: *mut args,
+    _m: *mut mouse_event,
+) {
+    unsafe {
+        let wme = wme.as_ptr();
+        let wp = (*wme).wp;
+        let w = (*wp).window;
+        let data = (*wme).data as *mut window_resize_mode_data;
+        let count = args_count(args);
+
+        let np = (*wme).prefix.min(i32::MAX as u32) as i32;
+        (*wme).prefix = 1;
+        if count == 0 || (*wp).layout_cell.is_null() {
+            return;
+        }
+
+        let (type_, change) = match cstr_to_str(args_string(args, 0)) {
+            "resize-left" => (layout_type::LAYOUT_LEFTRIGHT, -np),
+            "resize-right" => (layout_type::LAYOUT_LEFTRIGHT, np),
+            "resize-up" => (layout_type::LAYOUT_TOPBOTTOM, -np),
+            "resize-down" => (layout_type::LAYOUT_TOPBOTTOM, np),
+            "ratio" if count == 2 => {
+                let Ok(n) = strtonum(args_string(args, 1), 1u32, 9) else {
+                    return;
+                };
+                let size = if (*data).type_ == layout_type::LAYOUT_LEFTRIGHT {
+                    (*w).sx
+                } else {
+                    (*w).sy
+                };
+                server_unzoom_window(w);
+                layout_resize_pane_to(wp, (*data).type_, size * n / 10);
+                window_resize_draw_screen(NonNull::new_unchecked(wme));
+                server_redraw_window(w);
+                return;
+            }
+            "switch-direction" => {
+                (*data).type_ = if (*data).type_ == layout_type::LAYOUT_LEFTRIGHT {
+                    layout_type::LAYOUT_TOPBOTTOM
+                } else {
+                    layout_type::LAYOUT_LEFTRIGHT
+                };
+                window_resize_draw_screen(NonNull::new_unchecked(wme));
+                (*wp).flags |= window_pane_flags::PANE_REDRAW;
+                return;
+            }
+            "accept" => {
+                window_pane_reset_mode(wp);
+                return;
+            }
+            "cancel" => {
+                window_resize_cancel(wme);
+                window_pane_reset_mode(wp);
+                return;
+            }
+            _ => return,
+        };
+
+        (*data).type_ = type_;
+        server_unzoom_window(w);
+        layout_resize_pane(wp, type_, change, 1);
+        window_resize_draw_screen(NonNull::new_unchecked(wme));
+        server_redraw_window(w);
+    }
+}
@@ -0,0 +1,593 @@
+use super::*;
+
+use crate::compat::queue::{tailq_first, tailq_foreach};
+use crate::compat::tree::rb_foreach;
+use crate::libc::{
+    REG_EXTENDED, REG_ICASE, regcomp, regex_t, regexec, regfree, regmatch_t, strstr,
+};
+
+const WINDOW_SEARCH_DEFAULT_FORMAT: *const u8 =
+    c!("#{?search_match,#{search_context},#{search_count} matches}");
+
+const WINDOW_SEARCH_DEFAULT_KEY_FORMAT: *const u8 = concat!(
+    "#{?#{e|<:#{line},10},", //
+    "#{line}",
+    ",",
+    "#{?#{e|<:#{line},36},",
+    "M-#{a:#{e|+:97,#{e|-:#{line},10}}}",
+    ",",
+    "",
+    "}",
+    "}\0"
+)
+.as_ptr()
+.cast();
+
+/// Most matches listed for one pane.
+const WINDOW_SEARCH_MAX_MATCHES: usize = 1000;
+
+/// Bytes of the line shown before the match.
+const WINDOW_SEARCH_CONTEXT: usize = 20;
+
+static WINDOW_SEARCH_MENU_ITEMS: [menu_item; 3] = [
+    menu_item::new(c"Select", '\r' as u64, null_mut()),
+    menu_item::new(c"", KEYC_NONE, null_mut()),
+    menu_item::new(c"Cancel", 'q' as u64, null_mut()),
+];
+
+pub static WINDOW_SEARCH_MODE: window_mode = window_mode {
+    name: SyncCharPtr::new(c"search-mode"),
+    default_format: SyncCharPtr::from_ptr(WINDOW_SEARCH_DEFAULT_FORMAT),
+
+    init: window_search_init,
+    free: window_search_free,
+    resize: window_search_resize,
+    update: Some(window_search_update),
+    key: Some(window_search_key),
+    key_table: None,
+    command: None,
+    formats: None,
+};
+
+#[repr(u32)]
+enum window_search_sort_type {
+    WINDOW_SEARCH_BY_PANE,
+    WINDOW_SEARCH_BY_COUNT,
+}
+
+const WINDOW_SEARCH_SORT_LIST_LEN: u32 = 2;
+static mut WINDOW_SEARCH_SORT_LIST: [SyncCharPtr; 2] =
+    [SyncCharPtr::new(c"pane"), SyncCharPtr::new(c"count")];
+
+/// A pane with matches, or one match. Pane items have no text and are
+/// positioned on their first match.
+pub struct window_search_itemdata {
+    pub pane: u32,
+    pub line: u64,
+    pub x: u32,
+    pub count: u32,
+
+    pub text: *mut u8,
+    pub start: usize,
+    pub end: usize,
+}
+
+pub struct window_search_modedata {
+    pub wp: *mut window_pane,
+    pub fs: cmd_find_state,
+
+    pub data: *mut mode_tree_data,
+    pub format: *mut u8,
+    pub key_format: *mut u8,
+
+    pub pattern: *mut u8,
+    pub cflags: i32,
+    pub regex: regex_t,
+
+    pub item_list: Vec<*mut window_search_itemdata>,
+}
+
+unsafe fn window_search_free_items(data: *mut window_search_modedata) {
+    unsafe {
+        for item in (*data).item_list.drain(..) {
+            free_((*item).text);
+            free_(item);
+        }
+    }
+}
+
+/// Convert a byte offset into a line's text into a cell column.
+unsafe fn window_search_column(gd: *mut grid, py: u32, offset: usize) -> u32 {
+    unsafe {
+        let gl = grid_peek_line(gd, py);
+        let mut gc: grid_cell = zeroed();
+        let mut at = 0;
+
+        for xx in 0..(*gl).cellused {
+            grid_get_cell(gd, xx, py, &raw mut gc);
+            if gc.flags.intersects(grid_flag::PADDING) {
+                continue;
+            }
+            if at >= offset {
+                return xx;
+            }
+            at += gc.data.size as usize;
+        }
+        (*gl).cellused
+    }
+}
+
+/// Find the first match on each line of a pane's history and screen.
+unsafe fn window_search_pane(
+    data: *mut window_search_modedata,
+    wp: *mut window_pane,
+) -> Vec<*mut window_search_itemdata> {
+    unsafe {
+        let gd = (*wp).base.grid;
+        let mut list = Vec::new();
+        let mut rm: regmatch_t = zeroed();
+
+        for py in 0..(*gd).hsize + (*gd).sy {
+            let text = grid_string_cells(
+                gd,
+                0,
+                py,
+                (*gd).sx,
+                null_mut(),
+                grid_string_flags::GRID_STRING_TRIM_SPACES,
+                null_mut(),
+            );
+            if regexec(&raw const (*data).regex, text, 1, &raw mut rm, 0) != 0
+                || rm.rm_so == rm.rm_eo
+            {
+                free_(text);
+                continue;
+            }
+
+            let item = xcalloc1::<window_search_itemdata>() as *mut window_search_itemdata;
+            (*item).pane = (*wp).id;
+            (*item).line = (*gd).htrimmed + py as u64;
+            (*item).x = window_search_column(gd, py, rm.rm_so as usize);
+            (*item).text = text;
+            (*item).start = rm.rm_so as usize;
+            (*item).end = rm.rm_eo as usize;
+            list.push(item);
+
+            if list.len() == WINDOW_SEARCH_MAX_MATCHES {
+                break;
+            }
+        }
+        list
+    }
+}
+
+/// Build the text around a match with the match itself highlighted.
+unsafe fn window_search_context(item: *mut window_search_itemdata) -> String {
+    unsafe {
+        let text = CStr::from_ptr((*item).text.cast()).to_bytes();
+
+        let mut start = (*item).start.saturating_sub(WINDOW_SEARCH_CONTEXT);
+        while start < (*item).start && (text[start] & 0xc0) == 0x80 {
+            start += 1;
+        }
+
+        let escape = |bytes: &[u8]| String::from_utf8_lossy(bytes).replace('#', "##");
+        format!(
+            "{}#[reverse]{}#[noreverse]{}",
+            escape(&text[start..(*item).start]),
+            escape(&text[(*item).start..(*item).end]),
+            escape(&text[(*item).end..]),
+        )
+    }
+}
+
+/// Fill in the formats for a pane or match item.
+unsafe fn window_search_format(
+    data: *mut window_search_modedata,
+    item: *mut window_search_itemdata,
+) -> *mut format_tree {
+    unsafe {
+        let ft = format_create(null_mut(), null_mut(), FORMAT_NONE, format_flags::empty());
+
+        let wp = window_pane_find_by_id((*item).pane);
+        if !wp.is_null() {
+            let wl = tailq_first(&raw mut (*(*wp).window).winlinks);
+            let s = if wl.is_null() {
+                null_mut()
+            } else {
+                (*wl).session
+            };
+            format_defaults(
+                ft,
+                null_mut(),
+                NonNull::new(s),
+                NonNull::new(wl),
+                NonNull::new(wp),
+            );
+        }
+
+        format_add!(ft, c!("search_pattern"), "{}", _s((*data).pattern));
+        format_add!(ft, c!("search_line"), "{}", (*item).line);
+        format_add!(ft, c!("search_column"), "{}", (*item).x);
+        if (*item).text.is_null() {
+            format_add!(ft, c!("search_match"), "0");
+            format_add!(ft, c!("search_count"), "{}", (*item).count);
+        } else {
+            format_add!(ft, c!("search_match"), "1");
+            format_add!(ft, c!("search_context"), "{}", window_search_context(item));
+        }
+        ft
+    }
+}
+
+unsafe fn window_search_build(
+    modedata: NonNull<c_void>,
+    sort_crit: *mut mode_tree_sort_criteria,
+    _tag: *mut u64,
+    filter: *const u8,
+) {
+    unsafe {
+        let data: *mut window_search_modedata = modedata.cast().as_ptr();
+        let mut groups: Vec<(
+            *mut window_search_itemdata,
+            Vec<*mut window_search_itemdata>,
+        )> = Vec::new();
+        let mut seen: Vec<*mut window> = Vec::new();
+
+        window_search_free_items(data);
+
+        for s in rb_foreach(&raw mut SESSIONS).map(NonNull::as_ptr) {
+            for wl in rb_foreach(&raw mut (*s).windows).map(NonNull::as_ptr) {
+                let w = (*wl).window;
+                if seen.contains(&w) {
+                    continue;
+                }
+                seen.push(w);
+
+                for wp in tailq_foreach::<_, discr_entry>(&raw mut (*w).panes).map(NonNull::as_ptr)
+                {
+                    let matches = window_search_pane(data, wp);
+                    let Some(&first) = matches.first() else {
+                        continue;
+                    };
+
+                    let item = xcalloc1::<window_search_itemdata>() as *mut window_search_itemdata;
+                    (*item).pane = (*wp).id;
+                    (*item).line = (*first).line;
+                    (*item).x = (*first).x;
+                    (*item).count = matches.len() as u32;
+                    groups.push((item, matches));
+                }
+            }
+        }
+
+        if (*sort_crit).field == window_search_sort_type::WINDOW_SEARCH_BY_COUNT as u32 {
+            groups.sort_by_key(|g| std::cmp::Reverse((*g.0).count));
+        }
+        if (*sort_crit).reversed != 0 {
+            groups.reverse();
+        }
+
+        for (item, matches) in groups {
+            (*data).item_list.push(item);
+            (*data).item_list.extend_from_slice(&matches);
+
+            let mut shown = Vec::new();
+            for m in matches {
+                if !filter.is_null() {
+                    let ft = window_search_format(data, m);
+                    let cp = format_expand(ft, filter);
+                    let flag = format_true(cp);
+                    free_(cp);
+                    format_free(ft);
+                    if flag == 0 {
+                        continue;
+                    }
+                }
+                shown.push(m);
+            }
+            if shown.is_empty() {
+                continue;
+            }
+
+            let ft = window_search_format(data, item);
+            let name = format_expand(ft, c!("#{session_name}:#{window_index}.#{pane_index}"));
+            let text = format_expand(ft, (*data).format);
+            let parent = mode_tree_add(
+                (*data).data,
+                null_mut(),
+                item.cast(),
+                ((*item).pane as u64) << 40,
+                name,
+                text,
+                -1,
+            );
+            free_(text);
+            free_(name);
+            format_free(ft);
+
+            for m in shown {
+                let ft = window_search_format(data, m);
+                let name = format_nul!("{}", (*m).line);
+                let text = format_expand(ft, (*data).format);
+                mode_tree_add(
+                    (*data).data,
+                    parent,
+                    m.cast(),
+                    (((*m).pane as u64) << 40) | (((*m).line + 1) & 0xff_ffff_ffff),
+                    name,
+                    text,
+                    -1,
+                );
+                free_(text);
+                free_(name);
+                format_free(ft);
+            }
+        }
+    }
+}
+
+unsafe fn window_search_draw(
+    _modedata: *mut c_void,
+    itemdata: Option<NonNull<c_void>>,
+    ctx: *mut screen_write_ctx,
+    sx: u32,
+    sy: u32,
+) {
+    unsafe {
+        let Some(item) = itemdata.map(NonNull::cast::<window_search_itemdata>) else {
+            return;
+        };
+        let item = item.as_ptr();
+
+        let wp = window_pane_find_by_id((*item).pane);
+        if wp.is_null() {
+            return;
+        }
+        let gd = (*wp).base.grid;
+        let total = (*gd).hsize + (*gd).sy;
+        if (*item).line < (*gd).htrimmed || (*item).line - (*gd).htrimmed >= total as u64 {
+            return;
+        }
+        let py = ((*item).line - (*gd).htrimmed) as u32;
+
+        // Show the lines around the match, a third of the way down.
+        let mut top = py.saturating_sub(sy / 3);
+        if top + sy > total {
+            top = total.saturating_sub(sy);
+        }
+        screen_write_fast_copy(ctx, &raw mut (*wp).base, 0, top, sx, sy);
+    }
+}
+
+unsafe fn window_search_search(
+    _modedata: *mut c_void,
+    itemdata: NonNull<c_void>,
+    ss: *const u8,
+) -> bool {
+    unsafe {
+        let item: *mut window_search_itemdata = itemdata.cast().as_ptr();
+        !(*item).text.is_null() && !strstr((*item).text, ss).is_null()
+    }
+}
+
+unsafe fn window_search_menu(modedata: NonNull<c_void>, c: *mut client, key: key_code) {
+    unsafe {
+        let data: NonNull<window_search_modedata> = modedata.cast();
+        let wp: *mut window_pane = (*data.as_ptr()).wp;
+
+        if let Some(wme) = NonNull::new(tailq_first(&raw mut (*wp).modes))
+            && (*wme.as_ptr()).data == modedata.as_ptr()
+        {
+            window_search_key(wme, c, null_mut(), null_mut(), key, null_mut())
+        }
+    }
+}
+
+unsafe fn window_search_get_key(
+    modedata: NonNull<c_void>,
+    itemdata: NonNull<c_void>,
+    line: u32,
+) -> key_code {
+    unsafe {
+        let data: *mut window_search_modedata = modedata.cast().as_ptr();
+        let item: *mut window_search_itemdata = itemdata.cast().as_ptr();
+
+        let ft = window_search_format(data, item);
+        format_add!(ft, c!("line"), "{line}");
+
+        let expanded = format_expand(ft, (*data).key_format);
+        let key = key_string_lookup_string(expanded);
+        free_(expanded);
+        format_free(ft);
+        key
+    }
+}
+
+unsafe fn window_search_init(
+    wme: NonNull<window_mode_entry>,
+    fs: *mut cmd_find_state,
+    args: *mut args,
+) -> *mut screen {
+    unsafe {
+        let mut s = null_mut();
+        let wp = (*wme.as_ptr()).wp;
+        let data = Box::leak(Box::new(window_search_modedata {
+            wp,
+            fs: zeroed(),
+            data: null_mut(),
+            format: null_mut(),
+            key_format: null_mut(),
+            pattern: null_mut(),
+            cflags: 0,
+            regex: zeroed(),
+            item_list: Vec::new(),
+        }));
+        (*wme.as_ptr()).data = data as *mut window_search_modedata as *mut c_void;
+        cmd_find_copy_state(&raw mut data.fs, fs);
+
+        if args.is_null() || !args_has_(args, 'F') {
+            data.format = xstrdup(WINDOW_SEARCH_DEFAULT_FORMAT).as_ptr();
+        } else {
+            data.format = xstrdup(args_get_(args, 'F')).as_ptr();
+        }
+        if args.is_null() || !args_has_(args, 'K') {
+            data.key_format = xstrdup(WINDOW_SEARCH_DEFAULT_KEY_FORMAT).as_ptr();
+        } else {
+            data.key_format = xstrdup(args_get_(args, 'K')).as_ptr();
+        }
+
+        // The command has already checked the pattern compiles.
+        data.pattern = xstrdup(args_string(args, 0)).as_ptr();
+        data.cflags = window_search_flags(args);
+        regcomp(&raw mut data.regex, data.pattern, data.cflags);
+
+        data.data = mode_tree_start(
+            wp,
+            args,
+            Some(window_search_build),
+            Some(window_search_draw),
+            Some(window_search_search),
+            Some(window_search_menu),
+            None,
+            Some(window_search_get_key),
+            data as *mut window_search_modedata as *mut c_void,
+            WINDOW_SEARCH_MENU_ITEMS.as_slice(),
+            &raw mut WINDOW_SEARCH_SORT_LIST as *mut *const u8,
+            WINDOW_SEARCH_SORT_LIST_LEN,
+            &raw mut s,
+        );
+        mode_tree_zoom(data.data, args);
+
+        mode_tree_build(data.data);
+        mode_tree_draw(data.data);
+
+        s
+    }
+}
+
+unsafe fn window_search_free(wme: NonNull<window_mode_entry>) {
+    unsafe {
+        let data = (*wme.as_ptr()).data as *mut window_search_modedata;
+
+        if data.is_null() {
+            return;
+        }
+
+        mode_tree_free((*data).data);
+        window_search_free_items(data);
+
+        regfree(&raw mut (*data).regex);
+        free_((*data).pattern);
+        free_((*data).format);
+        free_((*data).key_format);
+
+        drop(Box::from_raw(data));
+    }
+}
+
+unsafe fn window_search_resize(wme: NonNull<window_mode_entry>, sx: u32, sy: u32) {
+    unsafe {
+        let data = (*wme.as_ptr()).data as *mut window_search_modedata;
+        mode_tree_resize((*data).data, sx, sy);
+    }
+}
+
+unsafe fn window_search_update(wme: NonNull<window_mode_entry>) {
+    unsafe {
+        let data = (*wme.as_ptr()).data as *mut window_search_modedata;
+
+        mode_tree_build((*data).data);
+        mode_tree_draw((*data).data);
+        (*(*data).wp).flags |= window_pane_flags::PANE_REDRAW;
+    }
+}
+
+unsafe fn window_search_key(
+    wme: NonNull<window_mode_entry>,
+    c: *mut client,
+    _s: *mut session,
+    _wl: *mut winlink,
+    mut key: key_code,
+    m: *mut mouse_event,
+) {
+    unsafe {
+        let wp = (*wme.as_ptr()).wp;
+        let data = (*wme.as_ptr()).data as *mut window_search_modedata;
+        let mtd: *mut mode_tree_data = (*data).data;
+        let mut chosen = None;
+
+        let mut finished = mode_tree_key(mtd, c, &raw mut key, m, null_mut(), null_mut()) != 0;
+        if key == '\r' as key_code {
+            let item: *mut window_search_itemdata = mode_tree_get_current(mtd).cast().as_ptr();
+            chosen = Some((
+                (*item).pane,
+                (*item).line,
+                (*item).x,
+                xstrdup((*data).pattern).as_ptr(),
+                (*data).cflags,
+            ));
+            finished = true;
+        }
+
+        if finished || (*data).item_list.is_empty() {
+            window_pane_reset_mode(wp);
+        } else {
+            mode_tree_draw(mtd);
+            (*wp).flags |= window_pane_flags::PANE_REDRAW;
+        }
+
+        // The mode data is gone now, so only use what was copied out of it.
+        if let Some((pane, line, px, pattern, cflags)) = chosen {
+            let twp = window_pane_find_by_id(pane);
+            if !twp.is_null() {
+                if !c.is_null() {
+                    let target = format_nul!("%{}", pane);
+                    mode_tree_run_command(c, null_mut(), c!("switch-client -Zt '%%'"), target);
+                    free_(target);
+                }
+                window_copy_show_match(twp, line, px, pattern, cflags);
+            }
+            free_(pattern);
+        }
+    }
+}
+
+/// Regular expression flags for the search-panes arguments.
+pub unsafe fn window_search_flags(args: *mut args) -> i32 {
+    unsafe {
+        if args_has_(args, 'i') {
+            REG_EXTENDED | REG_ICASE
+        } else {
+            REG_EXTENDED
+        }
+    }
+}
+
+/// Check if any pane has a line matching a pattern.
+pub unsafe fn window_search_any(regex: *const regex_t) -> bool {
+    unsafe {
+        let mut rm: regmatch_t = zeroed();
+
+        for wp in rb_foreach(&raw mut ALL_WINDOW_PANES).map(NonNull::as_ptr) {
+            let gd = (*wp).base.grid;
+            for py in 0..(*gd).hsize + (*gd).sy {
+                let text = grid_string_cells(
+                    gd,
+                    0,
+                    py,
+                    (*gd).sx,
+                    null_mut(),
+                    grid_string_flags::GRID_STRING_TRIM_SPACES,
+                    null_mut(),
+                );
+                let found = regexec(regex, text, 1, &raw mut rm, 0) == 0 && rm.rm_so != rm.rm_eo;
+                free_(text);
+                if found {
+                    return true;
+                }
+            }
+        }
+        false
+    }
+}
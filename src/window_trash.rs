@@ -0,0 +1,436 @@
+use super::*;
+
+use crate::compat::queue::tailq_first;
+use crate::libc::{qsort, strstr};
+
+const WINDOW_TRASH_DEFAULT_COMMAND: *const u8 = c!("restore-trash '%%'");
+const WINDOW_TRASH_DEFAULT_FORMAT: *const u8 = c!(
+    "#{t/p:trash_time}: #{trash_type} #{trash_name}#{?trash_session, from #{trash_session},}#{?trash_dead_panes, (#{trash_dead_panes} dead),}"
+);
+
+const WINDOW_TRASH_DEFAULT_KEY_FORMAT: *const u8 = concat!(
+    "#{?#{e|<:#{line},10},", //
+    "#{line}",
+    ",",
+    "#{?#{e|<:#{line},36},",
+    "M-#{a:#{e|+:97,#{e|-:#{line},10}}}",
+    ",",
+    "",
+    "}",
+    "}\0"
+)
+.as_ptr()
+.cast();
+
+static WINDOW_TRASH_MENU_ITEMS: [menu_item; 9] = [
+    menu_item::new(c"Restore", '\r' as u64, null_mut()),
+    menu_item::new(c"Restore To New Session", 'N' as u64, null_mut()),
+    menu_item::new(c"", KEYC_NONE, null_mut()),
+    menu_item::new(c"Tag", 't' as u64, null_mut()),
+    menu_item::new(c"Tag None", 'T' as u64, null_mut()),
+    menu_item::new(c"", KEYC_NONE, null_mut()),
+    menu_item::new(c"Delete", 'd' as u64, null_mut()),
+    menu_item::new(c"Delete Tagged", 'D' as u64, null_mut()),
+    menu_item::new(c"Cancel", 'q' as u64, null_mut()),
+];
+
+pub static WINDOW_TRASH_MODE: window_mode = window_mode {
+    name: SyncCharPtr::new(c"trash-mode"),
+    default_format: SyncCharPtr::from_ptr(WINDOW_TRASH_DEFAULT_FORMAT),
+
+    init: window_trash_init,
+    free: window_trash_free,
+    resize: window_trash_resize,
+    update: Some(window_trash_update),
+    key: Some(window_trash_key),
+    key_table: None,
+    command: None,
+    formats: None,
+};
+
+#[repr(u32)]
+enum window_trash_sort_type {
+    WINDOW_TRASH_BY_TIME,
+    WINDOW_TRASH_BY_NAME,
+}
+
+const WINDOW_TRASH_SORT_LIST_LEN: u32 = 2;
+static mut WINDOW_TRASH_SORT_LIST: [SyncCharPtr; 2] =
+    [SyncCharPtr::new(c"time"), SyncCharPtr::new(c"name")];
+
+static mut WINDOW_TRASH_SORT: *mut mode_tree_sort_criteria = null_mut();
+
+pub struct window_trash_itemdata {
+    pub id: u32,
+    pub name: *mut u8,
+}
+
+pub struct window_trash_modedata {
+    pub wp: *mut window_pane,
+    pub fs: cmd_find_state,
+
+    pub data: *mut mode_tree_data,
+    pub command: *mut u8,
+    pub format: *mut u8,
+    pub key_format: *mut u8,
+
+    pub item_list: Vec<*mut window_trash_itemdata>,
+}
+
+unsafe fn window_trash_free_item(item: *mut window_trash_itemdata) {
+    unsafe {
+        free_((*item).name);
+        free_(item);
+    }
+}
+
+unsafe fn window_trash_free_items(data: *mut window_trash_modedata) {
+    unsafe {
+        for item in (*data).item_list.drain(..) {
+            window_trash_free_item(item);
+        }
+    }
+}
+
+unsafe extern "C" fn window_trash_cmp(a0: *const c_void, b0: *const c_void) -> i32 {
+    unsafe {
+        let a = *(a0 as *const *const window_trash_itemdata);
+        let b = *(b0 as *const *const window_trash_itemdata);
+        let mut result = 0i32;
+
+        if (*WINDOW_TRASH_SORT).field == window_trash_sort_type::WINDOW_TRASH_BY_NAME as u32 {
+            result = libc::strcmp((*a).name, (*b).name);
+        }
+
+        /* Use WINDOW_TRASH_BY_TIME as default order and tie breaker. */
+        if result == 0 {
+            result = (*b).id as i32 - (*a).id as i32;
+        }
+
+        if (*WINDOW_TRASH_SORT).reversed != 0 {
+            result = -result;
+        }
+
+        result
+    }
+}
+
+unsafe fn window_trash_build(
+    modedata: NonNull<c_void>,
+    sort_crit: *mut mode_tree_sort_criteria,
+    _tag: *mut u64,
+    filter: *const u8,
+) {
+    unsafe {
+        let data: *mut window_trash_modedata = modedata.cast().as_ptr();
+        let mut s = None;
+        let mut wl = None;
+        let mut wp = None;
+
+        window_trash_free_items(data);
+        let mut te = trash_walk(null_mut());
+        while !te.is_null() {
+            let item = xcalloc1::<window_trash_itemdata>() as *mut window_trash_itemdata;
+            (*item).id = (*te).id;
+            (*item).name = xstrdup((*(*te).window).name).as_ptr();
+            (*data).item_list.push(item);
+            te = trash_walk(te);
+        }
+
+        WINDOW_TRASH_SORT = sort_crit;
+        qsort(
+            (*data).item_list.as_mut_ptr().cast(),
+            (*data).item_list.len(),
+            size_of::<*const window_trash_itemdata>(),
+            Some(window_trash_cmp),
+        );
+
+        if cmd_find_valid_state(&raw mut (*data).fs) {
+            s = NonNull::new((*data).fs.s);
+            wl = NonNull::new((*data).fs.wl);
+            wp = NonNull::new((*data).fs.wp);
+        }
+
+        for &item in (*data).item_list.iter() {
+            let te = trash_find((*item).id);
+            if te.is_null() {
+                continue;
+            }
+            let ft = format_create(null_mut(), null_mut(), FORMAT_NONE, format_flags::empty());
+            format_defaults(ft, null_mut(), s, wl, wp);
+            format_defaults_trash(ft, te);
+
+            if !filter.is_null() {
+                let cp = format_expand(ft, filter);
+                if format_true(cp) == 0 {
+                    free_(cp);
+                    format_free(ft);
+                    continue;
+                }
+                free_(cp);
+            }
+
+            let name = format_nul!("{}", (*item).id);
+            let text = format_expand(ft, (*data).format);
+            mode_tree_add(
+                (*data).data,
+                null_mut(),
+                item.cast(),
+                (*item).id as u64,
+                name,
+                text,
+                -1,
+            );
+            free_(text);
+            free_(name);
+
+            format_free(ft);
+        }
+    }
+}
+
+unsafe fn window_trash_draw(
+    _modedata: *mut c_void,
+    itemdata: Option<NonNull<c_void>>,
+    ctx: *mut screen_write_ctx,
+    sx: u32,
+    sy: u32,
+) {
+    unsafe {
+        let Some(item) = itemdata.map(NonNull::cast::<window_trash_itemdata>) else {
+            return;
+        };
+        let te = trash_find((*item.as_ptr()).id);
+        if te.is_null() {
+            return;
+        }
+        let wp = (*(*te).window).active;
+        if !wp.is_null() {
+            screen_write_preview(ctx, &raw mut (*wp).base, sx, sy);
+        }
+    }
+}
+
+unsafe fn window_trash_search(
+    _modedata: *mut c_void,
+    itemdata: NonNull<c_void>,
+    ss: *const u8,
+) -> bool {
+    unsafe {
+        let item: NonNull<window_trash_itemdata> = itemdata.cast();
+        !strstr((*item.as_ptr()).name, ss).is_null()
+    }
+}
+
+unsafe fn window_trash_menu(modedata: NonNull<c_void>, c: *mut client, key: key_code) {
+    unsafe {
+        let data: NonNull<window_trash_modedata> = modedata.cast();
+        let wp: *mut window_pane = (*data.as_ptr()).wp;
+
+        if let Some(wme) = NonNull::new(tailq_first(&raw mut (*wp).modes))
+            && (*wme.as_ptr()).data == modedata.as_ptr()
+        {
+            window_trash_key(wme, c, null_mut(), null_mut(), key, null_mut())
+        }
+    }
+}
+
+unsafe fn window_trash_get_key(
+    modedata: NonNull<c_void>,
+    itemdata: NonNull<c_void>,
+    line: u32,
+) -> key_code {
+    unsafe {
+        let data: NonNull<window_trash_modedata> = modedata.cast();
+        let item: NonNull<window_trash_itemdata> = itemdata.cast();
+        let mut s = None;
+        let mut wl = None;
+        let mut wp = None;
+
+        if cmd_find_valid_state(&raw mut (*data.as_ptr()).fs) {
+            s = NonNull::new((*data.as_ptr()).fs.s);
+            wl = NonNull::new((*data.as_ptr()).fs.wl);
+            wp = NonNull::new((*data.as_ptr()).fs.wp);
+        }
+        let te = trash_find((*item.as_ptr()).id);
+        if te.is_null() {
+            return KEYC_NONE;
+        }
+
+        let ft = format_create(null_mut(), null_mut(), FORMAT_NONE, format_flags::empty());
+        format_defaults(ft, null_mut(), s, wl, wp);
+        format_defaults_trash(ft, te);
+        format_add!(ft, c!("line"), "{line}");
+
+        let expanded = format_expand(ft, (*data.as_ptr()).key_format);
+        let key = key_string_lookup_string(expanded);
+        free_(expanded);
+        format_free(ft);
+        key
+    }
+}
+
+unsafe fn window_trash_init(
+    wme: NonNull<window_mode_entry>,
+    fs: *mut cmd_find_state,
+    args: *mut args,
+) -> *mut screen {
+    unsafe {
+        let mut s = null_mut();
+        let wp = (*wme.as_ptr()).wp;
+        let data = Box::leak(Box::new(window_trash_modedata {
+            wp,
+            fs: zeroed(),
+            data: null_mut(),
+            command: null_mut(),
+            format: null_mut(),
+            key_format: null_mut(),
+            item_list: Vec::new(),
+        }));
+        (*wme.as_ptr()).data = data as *mut window_trash_modedata as *mut c_void;
+        cmd_find_copy_state(&raw mut data.fs, fs);
+
+        if args.is_null() || !args_has_(args, 'F') {
+            data.format = xstrdup(WINDOW_TRASH_DEFAULT_FORMAT).as_ptr();
+        } else {
+            data.format = xstrdup(args_get_(args, 'F')).as_ptr();
+        }
+        if args.is_null() || !args_has_(args, 'K') {
+            data.key_format = xstrdup(WINDOW_TRASH_DEFAULT_KEY_FORMAT).as_ptr();
+        } else {
+            data.key_format = xstrdup(args_get_(args, 'K')).as_ptr();
+        }
+        if args.is_null() || args_count(args) == 0 {
+            data.command = xstrdup(WINDOW_TRASH_DEFAULT_COMMAND).as_ptr();
+        } else {
+            data.command = xstrdup(args_string(args, 0)).as_ptr();
+        }
+
+        data.data = mode_tree_start(
+            wp,
+            args,
+            Some(window_trash_build),
+            Some(window_trash_draw),
+            Some(window_trash_search),
+            Some(window_trash_menu),
+            None,
+            Some(window_trash_get_key),
+            data as *mut window_trash_modedata as *mut c_void,
+            WINDOW_TRASH_MENU_ITEMS.as_slice(),
+            &raw mut WINDOW_TRASH_SORT_LIST as *mut *const u8,
+            WINDOW_TRASH_SORT_LIST_LEN,
+            &raw mut s,
+        );
+        mode_tree_zoom(data.data, args);
+
+        mode_tree_build(data.data);
+        mode_tree_draw(data.data);
+
+        s
+    }
+}
+
+unsafe fn window_trash_free(wme: NonNull<window_mode_entry>) {
+    unsafe {
+        let data = (*wme.as_ptr()).data as *mut window_trash_modedata;
+
+        if data.is_null() {
+            return;
+        }
+
+        mode_tree_free((*data).data);
+        window_trash_free_items(data);
+
+        free_((*data).format);
+        free_((*data).key_format);
+        free_((*data).command);
+
+        drop(Box::from_raw(data));
+    }
+}
+
+unsafe fn window_trash_resize(wme: NonNull<window_mode_entry>, sx: u32, sy: u32) {
+    unsafe {
+        let data = (*wme.as_ptr()).data as *mut window_trash_modedata;
+        mode_tree_resize((*data).data, sx, sy);
+    }
+}
+
+unsafe fn window_trash_update(wme: NonNull<window_mode_entry>) {
+    unsafe {
+        let data = (*wme.as_ptr()).data as *mut window_trash_modedata;
+
+        mode_tree_build((*data).data);
+        mode_tree_draw((*data).data);
+        (*(*data).wp).flags |= window_pane_flags::PANE_REDRAW;
+    }
+}
+
+unsafe fn window_trash_do_delete(
+    modedata: NonNull<c_void>,
+    itemdata: NonNull<c_void>,
+    _c: *mut client,
+    _key: key_code,
+) {
+    unsafe {
+        let data: NonNull<window_trash_modedata> = modedata.cast();
+        let item: NonNull<window_trash_itemdata> = itemdata.cast();
+
+        if item == mode_tree_get_current((*data.as_ptr()).data).cast()
+            && mode_tree_down((*data.as_ptr()).data, 0) == 0
+        {
+            mode_tree_up((*data.as_ptr()).data, 0);
+        }
+        let te = trash_find((*item.as_ptr()).id);
+        if !te.is_null() {
+            trash_free(te);
+        }
+    }
+}
+
+unsafe fn window_trash_key(
+    wme: NonNull<window_mode_entry>,
+    c: *mut client,
+    _s: *mut session,
+    _wl: *mut winlink,
+    mut key: key_code,
+    m: *mut mouse_event,
+) {
+    unsafe {
+        let wp = (*wme.as_ptr()).wp;
+        let data = (*wme.as_ptr()).data as *mut window_trash_modedata;
+        let mtd: *mut mode_tree_data = (*data).data;
+
+        let mut finished = mode_tree_key(mtd, c, &raw mut key, m, null_mut(), null_mut()) != 0;
+        match key as u8 {
+            b'd' => {
+                let item = mode_tree_get_current(mtd);
+                window_trash_do_delete(NonNull::new(data.cast()).unwrap(), item, c, key);
+                mode_tree_build(mtd);
+            }
+            b'D' => {
+                mode_tree_each_tagged(mtd, Some(window_trash_do_delete), c, key, 0);
+                mode_tree_build(mtd);
+            }
+            b'\r' | b'N' => {
+                let item: NonNull<window_trash_itemdata> = mode_tree_get_current(mtd).cast();
+                let name = format_nul!("{}", (*item.as_ptr()).id);
+                if key == b'N' as key_code {
+                    mode_tree_run_command(c, null_mut(), c!("restore-trash -N '%%'"), name);
+                } else {
+                    mode_tree_run_command(c, null_mut(), (*data).command, name);
+                }
+                free_(name);
+                finished = true;
+            }
+            _ => (),
+        }
+
+        if finished || trash_count() == 0 {
+            window_pane_reset_mode(wp);
+        } else {
+            mode_tree_draw(mtd);
+            (*wp).flags |= window_pane_flags::PANE_REDRAW;
+        }
+    }
+}
@@ -1361,12 +1361,16 @@ unsafe fn window_tree_kill_each(
             }
             window_tree_type::WINDOW_TREE_WINDOW => {
                 if let Some(wl) = wl {
-                    server_kill_window((*wl.as_ptr()).window, 0);
+                    trash_kill_window(
+                        (*wl.as_ptr()).window,
+                        s.map_or(null_mut(), NonNull::as_ptr),
+                        0,
+                    );
                 }
             }
             window_tree_type::WINDOW_TREE_PANE => {
                 if let Some(wp) = wp {
-                    server_kill_pane(wp.as_ptr());
+                    trash_kill_pane(wp.as_ptr(), s.map_or(null_mut(), NonNull::as_ptr));
                 }
             }
         }